@@ -0,0 +1,18 @@
+//! Writes the C header, `sudoku.h`, from the `extern "C"` functions in `src/lib.rs`.  The header
+//! goes in `OUT_DIR`, and `tests/header.rs` checks that `include/sudoku.h` matches it.
+use std::env;
+use std::path::PathBuf;
+
+fn main() {
+    let dir = PathBuf::from(env::var("CARGO_MANIFEST_DIR").unwrap());
+    let out = PathBuf::from(env::var("OUT_DIR").unwrap());
+    println!("cargo:rerun-if-changed=src/lib.rs");
+    println!("cargo:rerun-if-changed=cbindgen.toml");
+    let config = cbindgen::Config::from_file(dir.join("cbindgen.toml")).unwrap();
+    cbindgen::Builder::new()
+        .with_crate(&dir)
+        .with_config(config)
+        .generate()
+        .expect("Unable to generate the C header")
+        .write_to_file(out.join("sudoku.h"));
+}
@@ -0,0 +1,252 @@
+/* Generated by cbindgen from ffi/src/lib.rs.  Do not edit. */
+
+#ifndef SUDOKU_H
+#define SUDOKU_H
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+/**
+ * Changes whenever a function or type in the header changes in a way that breaks callers.
+ */
+#define SUDOKU_ABI_VERSION 1
+
+/**
+ * The number of cells in a puzzle, and so the length of a grid of values.
+ */
+#define SUDOKU_CELLS 81
+
+typedef enum SudokuStatus {
+  SUDOKU_STATUS_OK = 0,
+  /**
+   * A pointer argument was null.
+   */
+  SUDOKU_STATUS_NULL_POINTER,
+  /**
+   * A grid did not have exactly 81 values.
+   */
+  SUDOKU_STATUS_WRONG_LENGTH,
+  /**
+   * A cell index above 80 or a value above 9.
+   */
+  SUDOKU_STATUS_OUT_OF_RANGE,
+  /**
+   * A given is repeated in its row, column, or box.
+   */
+  SUDOKU_STATUS_CONFLICTING_GIVENS,
+  /**
+   * Text that could not be read as a puzzle.
+   */
+  SUDOKU_STATUS_INVALID_TEXT,
+  /**
+   * The cell is a given and cannot be changed.
+   */
+  SUDOKU_STATUS_GIVEN,
+  /**
+   * There is nothing left to do.
+   */
+  SUDOKU_STATUS_SOLVED,
+  /**
+   * The puzzle cannot be solved from the numbers filled in.
+   */
+  SUDOKU_STATUS_NO_SOLUTION,
+  /**
+   * An output buffer is too small.
+   */
+  SUDOKU_STATUS_BUFFER_TOO_SMALL,
+  /**
+   * The library panicked.
+   */
+  SUDOKU_STATUS_INTERNAL_ERROR,
+} SudokuStatus;
+
+/**
+ * A solving technique, in order from easiest to hardest.
+ */
+typedef enum SudokuTechnique {
+  /**
+   * No technique was needed.
+   */
+  SUDOKU_TECHNIQUE_NONE = 0,
+  SUDOKU_TECHNIQUE_NAKED_SINGLE,
+  SUDOKU_TECHNIQUE_HIDDEN_SINGLE,
+  SUDOKU_TECHNIQUE_NAKED_TUPLE,
+  SUDOKU_TECHNIQUE_LOCKED_CANDIDATES,
+  SUDOKU_TECHNIQUE_BRUTE_FORCE,
+} SudokuTechnique;
+
+/**
+ * A puzzle, with the givens and any numbers filled in since.  Only used through a pointer.
+ */
+typedef struct SudokuPuzzle SudokuPuzzle;
+
+/**
+ * The next number to fill in.
+ */
+typedef struct SudokuHint {
+  /**
+   * The hardest technique needed to find the number.
+   */
+  enum SudokuTechnique technique;
+  /**
+   * The cell to fill in.
+   */
+  size_t index;
+  /**
+   * The number that goes in the cell.
+   */
+  uint8_t value;
+  /**
+   * How many pencil marks were removed on the way to finding the number.
+   */
+  size_t eliminations;
+} SudokuHint;
+
+/**
+ * How hard a puzzle is to solve from the numbers filled in so far.
+ */
+typedef struct SudokuRating {
+  /**
+   * The hardest technique needed.
+   */
+  enum SudokuTechnique hardest;
+  /**
+   * The number of solutions, counting no more than 2.
+   */
+  size_t solutions;
+  /**
+   * The number of times each human technique was used.
+   */
+  size_t naked_single;
+  size_t hidden_single;
+  size_t naked_tuple;
+  size_t locked_candidates;
+  /**
+   * True if the human techniques could not finish the puzzle.
+   */
+  bool brute_force;
+} SudokuRating;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+/**
+ * The version of the header the library was built from.  Compare it with `SUDOKU_ABI_VERSION`
+ * to make sure a shared library matches the header.
+ */
+uint32_t sudoku_abi_version(void);
+
+/**
+ * A description of the status, which lives as long as the program.  The status is taken as a
+ * number, so a value that is not a `SudokuStatus` gets a message rather than being undefined.
+ */
+const char *sudoku_status_message(uint32_t status);
+
+/**
+ * Creates an empty puzzle.  Free it with `sudoku_free`.
+ */
+struct SudokuPuzzle *sudoku_new(void);
+
+/**
+ * Frees a puzzle.  Does nothing if `puzzle` is null.
+ *
+ * # Safety
+ *
+ * `puzzle` must be null or come from `sudoku_new`, and must not be used again.
+ */
+void sudoku_free(struct SudokuPuzzle *puzzle);
+
+/**
+ * Starts the puzzle again from `len` givens, which must be 81 values from 0 to 9.  The puzzle is
+ * left alone if the givens are not valid.
+ *
+ * # Safety
+ *
+ * `puzzle` must come from `sudoku_new`, and `values` must point to `len` bytes.
+ */
+enum SudokuStatus sudoku_set_givens(struct SudokuPuzzle *puzzle, const uint8_t *values, size_t len);
+
+/**
+ * Starts the puzzle again from text in any format the library reads, such as 81 digits with `.`
+ * or `0` for empty cells.
+ *
+ * # Safety
+ *
+ * `puzzle` must come from `sudoku_new`, and `text` must be a nul terminated string.
+ */
+enum SudokuStatus sudoku_parse(struct SudokuPuzzle *puzzle, const char *text);
+
+/**
+ * Copies the 81 values of the cells, givens and filled in, to `out`.
+ *
+ * # Safety
+ *
+ * `puzzle` must come from `sudoku_new`, and `out` must point to `len` writable bytes.
+ */
+enum SudokuStatus sudoku_get_values(const struct SudokuPuzzle *puzzle, uint8_t *out, size_t len);
+
+/**
+ * Fills in a cell that is not a given, or empties it if `value` is 0.  The number is not checked
+ * against the others, the way a player may make a mistake.
+ *
+ * # Safety
+ *
+ * `puzzle` must come from `sudoku_new`.
+ */
+enum SudokuStatus sudoku_set_value(struct SudokuPuzzle *puzzle, size_t index, uint8_t value);
+
+/**
+ * Solves the puzzle from the numbers filled in so far, stopping after `max_solutions`.  The
+ * number found is written to `found`, and each solution's 81 values are written to `solutions`
+ * one after another.  `solutions` may be null to only count them.
+ *
+ * # Safety
+ *
+ * `puzzle` must come from `sudoku_new`, `found` must be writable, and `solutions` must be null or
+ * point to `len` writable bytes.
+ */
+enum SudokuStatus sudoku_solve(const struct SudokuPuzzle *puzzle,
+                               size_t max_solutions,
+                               uint8_t *solutions,
+                               size_t len,
+                               size_t *found);
+
+/**
+ * Finds the next number to fill in, with the easiest techniques that work.  Returns
+ * `SUDOKU_STATUS_SOLVED` if every cell is filled in, and `SUDOKU_STATUS_NO_SOLUTION` if a number
+ * filled in is wrong.
+ *
+ * # Safety
+ *
+ * `puzzle` must come from `sudoku_new`, and `hint` must be writable.
+ */
+enum SudokuStatus sudoku_hint(const struct SudokuPuzzle *puzzle, struct SudokuHint *hint);
+
+/**
+ * Rates the puzzle by the techniques needed to solve it from the numbers filled in so far.
+ *
+ * # Safety
+ *
+ * `puzzle` must come from `sudoku_new`, and `rating` must be writable.
+ */
+enum SudokuStatus sudoku_rate(const struct SudokuPuzzle *puzzle, struct SudokuRating *rating);
+
+/**
+ * The numbers still possible in an empty cell, as bits: bit 0 for 1, up to bit 8 for 9.  Returns 0
+ * for a filled in cell or an index out of range.
+ *
+ * # Safety
+ *
+ * `puzzle` must be null or come from `sudoku_new`.
+ */
+uint16_t sudoku_candidates(const struct SudokuPuzzle *puzzle, size_t index);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* SUDOKU_H */
@@ -0,0 +1,565 @@
+//! A C API for the sudoku library, for callers that cannot link Rust directly.  The header,
+//! `include/sudoku.h`, is generated from this file when the crate is built.
+//!
+//! Cells are numbered 0 to 80, row by row, and hold 1 to 9, or 0 when empty.  Functions that can
+//! fail return a `SudokuStatus`.  A panic inside the library is reported as
+//! `SUDOKU_STATUS_INTERNAL_ERROR` instead of unwinding into the caller.
+use std::ffi::CStr;
+use std::os::raw::c_char;
+use std::panic::{self, AssertUnwindSafe};
+use std::slice;
+use sudoku::{check_givens, BruteForce, Puzzle, SudError, Technique};
+
+/// Changes whenever a function or type in the header changes in a way that breaks callers.
+pub const SUDOKU_ABI_VERSION: u32 = 1;
+
+/// The number of cells in a puzzle, and so the length of a grid of values.
+pub const SUDOKU_CELLS: usize = 81;
+
+/// A puzzle, with the givens and any numbers filled in since.  Only used through a pointer.
+pub struct SudokuPuzzle {
+    puzzle: Puzzle,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SudokuStatus {
+    Ok = 0,
+    /// A pointer argument was null.
+    NullPointer,
+    /// A grid did not have exactly 81 values.
+    WrongLength,
+    /// A cell index above 80 or a value above 9.
+    OutOfRange,
+    /// A given is repeated in its row, column, or box.
+    ConflictingGivens,
+    /// Text that could not be read as a puzzle.
+    InvalidText,
+    /// The cell is a given and cannot be changed.
+    Given,
+    /// There is nothing left to do.
+    Solved,
+    /// The puzzle cannot be solved from the numbers filled in.
+    NoSolution,
+    /// An output buffer is too small.
+    BufferTooSmall,
+    /// The library panicked.
+    InternalError,
+}
+
+impl SudokuStatus {
+    /// Every status, in the order of their values.
+    const ALL: [SudokuStatus; 11] = [
+        SudokuStatus::Ok,
+        SudokuStatus::NullPointer,
+        SudokuStatus::WrongLength,
+        SudokuStatus::OutOfRange,
+        SudokuStatus::ConflictingGivens,
+        SudokuStatus::InvalidText,
+        SudokuStatus::Given,
+        SudokuStatus::Solved,
+        SudokuStatus::NoSolution,
+        SudokuStatus::BufferTooSmall,
+        SudokuStatus::InternalError,
+    ];
+}
+
+impl From<SudError> for SudokuStatus {
+    fn from(e: SudError) -> Self {
+        match e {
+            SudError::WrongLength { .. } => SudokuStatus::WrongLength,
+            SudError::OutOfRange { .. } => SudokuStatus::OutOfRange,
+            SudError::ConflictingGivens { .. } => SudokuStatus::ConflictingGivens,
+            _ => SudokuStatus::InvalidText,
+        }
+    }
+}
+
+/// A solving technique, in order from easiest to hardest.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SudokuTechnique {
+    /// No technique was needed.
+    None = 0,
+    NakedSingle,
+    HiddenSingle,
+    NakedTuple,
+    LockedCandidates,
+    BruteForce,
+}
+
+impl From<Option<Technique>> for SudokuTechnique {
+    fn from(technique: Option<Technique>) -> Self {
+        match technique {
+            None => SudokuTechnique::None,
+            Some(Technique::NakedSingle) => SudokuTechnique::NakedSingle,
+            Some(Technique::HiddenSingle) => SudokuTechnique::HiddenSingle,
+            Some(Technique::NakedTuple) => SudokuTechnique::NakedTuple,
+            Some(Technique::LockedCandidates) => SudokuTechnique::LockedCandidates,
+            Some(Technique::BruteForce) => SudokuTechnique::BruteForce,
+        }
+    }
+}
+
+/// The next number to fill in.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SudokuHint {
+    /// The hardest technique needed to find the number.
+    pub technique: SudokuTechnique,
+    /// The cell to fill in.
+    pub index: usize,
+    /// The number that goes in the cell.
+    pub value: u8,
+    /// How many pencil marks were removed on the way to finding the number.
+    pub eliminations: usize,
+}
+
+/// How hard a puzzle is to solve from the numbers filled in so far.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SudokuRating {
+    /// The hardest technique needed.
+    pub hardest: SudokuTechnique,
+    /// The number of solutions, counting no more than 2.
+    pub solutions: usize,
+    /// The number of times each human technique was used.
+    pub naked_single: usize,
+    pub hidden_single: usize,
+    pub naked_tuple: usize,
+    pub locked_candidates: usize,
+    /// True if the human techniques could not finish the puzzle.
+    pub brute_force: bool,
+}
+
+/// Runs `f`, turning a panic into `SudokuStatus::InternalError`.
+fn guard(f: impl FnOnce() -> SudokuStatus) -> SudokuStatus {
+    panic::catch_unwind(AssertUnwindSafe(f)).unwrap_or(SudokuStatus::InternalError)
+}
+
+/// True if a filled in number is repeated in its row, column, or box.
+fn conflict(puz: &Puzzle) -> bool {
+    (0..SUDOKU_CELLS).any(|i| puz.cells[i].num() != 0 && !puz.valid_entry(i))
+}
+
+/// The version of the header the library was built from.  Compare it with `SUDOKU_ABI_VERSION`
+/// to make sure a shared library matches the header.
+#[no_mangle]
+pub extern "C" fn sudoku_abi_version() -> u32 {
+    SUDOKU_ABI_VERSION
+}
+
+/// A description of the status, which lives as long as the program.  The status is taken as a
+/// number, so a value that is not a `SudokuStatus` gets a message rather than being undefined.
+#[no_mangle]
+pub extern "C" fn sudoku_status_message(status: u32) -> *const c_char {
+    let message: &'static [u8] = match SudokuStatus::ALL.get(status as usize) {
+        Some(SudokuStatus::Ok) => b"ok\0",
+        Some(SudokuStatus::NullPointer) => b"a pointer argument was null\0",
+        Some(SudokuStatus::WrongLength) => b"a grid must have exactly 81 values\0",
+        Some(SudokuStatus::OutOfRange) => b"a cell index or value is out of range\0",
+        Some(SudokuStatus::ConflictingGivens) => {
+            b"a given is repeated in its row, column, or box\0"
+        }
+        Some(SudokuStatus::InvalidText) => b"the text is not a puzzle\0",
+        Some(SudokuStatus::Given) => b"the cell is a given\0",
+        Some(SudokuStatus::Solved) => b"the puzzle is solved\0",
+        Some(SudokuStatus::NoSolution) => b"the puzzle cannot be solved\0",
+        Some(SudokuStatus::BufferTooSmall) => b"the output buffer is too small\0",
+        Some(SudokuStatus::InternalError) => b"internal error\0",
+        None => b"unknown status\0",
+    };
+    message.as_ptr() as *const c_char
+}
+
+/// Creates an empty puzzle.  Free it with `sudoku_free`.
+#[no_mangle]
+pub extern "C" fn sudoku_new() -> *mut SudokuPuzzle {
+    Box::into_raw(Box::new(SudokuPuzzle {
+        puzzle: Puzzle::new(),
+    }))
+}
+
+/// Frees a puzzle.  Does nothing if `puzzle` is null.
+///
+/// # Safety
+///
+/// `puzzle` must be null or come from `sudoku_new`, and must not be used again.
+#[no_mangle]
+pub unsafe extern "C" fn sudoku_free(puzzle: *mut SudokuPuzzle) {
+    if !puzzle.is_null() {
+        drop(Box::from_raw(puzzle));
+    }
+}
+
+/// Starts the puzzle again from `len` givens, which must be 81 values from 0 to 9.  The puzzle is
+/// left alone if the givens are not valid.
+///
+/// # Safety
+///
+/// `puzzle` must come from `sudoku_new`, and `values` must point to `len` bytes.
+#[no_mangle]
+pub unsafe extern "C" fn sudoku_set_givens(
+    puzzle: *mut SudokuPuzzle,
+    values: *const u8,
+    len: usize,
+) -> SudokuStatus {
+    let puzzle = match puzzle.as_mut() {
+        Some(puzzle) if !values.is_null() => puzzle,
+        _ => return SudokuStatus::NullPointer,
+    };
+    let values = slice::from_raw_parts(values, len);
+    guard(|| {
+        if values.len() != SUDOKU_CELLS {
+            return SudokuStatus::WrongLength;
+        }
+        if values.iter().any(|v| *v > 9) {
+            return SudokuStatus::OutOfRange;
+        }
+        if let Err(e) = check_givens(values) {
+            return e.into();
+        }
+        let mut puz = Puzzle::new();
+        puz.set_initial(values.to_vec());
+        puzzle.puzzle = puz;
+        SudokuStatus::Ok
+    })
+}
+
+/// Starts the puzzle again from text in any format the library reads, such as 81 digits with `.`
+/// or `0` for empty cells.
+///
+/// # Safety
+///
+/// `puzzle` must come from `sudoku_new`, and `text` must be a nul terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn sudoku_parse(
+    puzzle: *mut SudokuPuzzle,
+    text: *const c_char,
+) -> SudokuStatus {
+    let puzzle = match puzzle.as_mut() {
+        Some(puzzle) if !text.is_null() => puzzle,
+        _ => return SudokuStatus::NullPointer,
+    };
+    let text = match CStr::from_ptr(text).to_str() {
+        Ok(text) => text,
+        Err(_) => return SudokuStatus::InvalidText,
+    };
+    guard(|| match text.parse::<Puzzle>() {
+        Ok(puz) => {
+            puzzle.puzzle = puz;
+            SudokuStatus::Ok
+        }
+        Err(e) => e.into(),
+    })
+}
+
+/// Copies the 81 values of the cells, givens and filled in, to `out`.
+///
+/// # Safety
+///
+/// `puzzle` must come from `sudoku_new`, and `out` must point to `len` writable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn sudoku_get_values(
+    puzzle: *const SudokuPuzzle,
+    out: *mut u8,
+    len: usize,
+) -> SudokuStatus {
+    let puzzle = match puzzle.as_ref() {
+        Some(puzzle) if !out.is_null() => puzzle,
+        _ => return SudokuStatus::NullPointer,
+    };
+    if len < SUDOKU_CELLS {
+        return SudokuStatus::BufferTooSmall;
+    }
+    let out = slice::from_raw_parts_mut(out, SUDOKU_CELLS);
+    for (value, cell) in out.iter_mut().zip(puzzle.puzzle.cells.iter()) {
+        *value = cell.num();
+    }
+    SudokuStatus::Ok
+}
+
+/// Fills in a cell that is not a given, or empties it if `value` is 0.  The number is not checked
+/// against the others, the way a player may make a mistake.
+///
+/// # Safety
+///
+/// `puzzle` must come from `sudoku_new`.
+#[no_mangle]
+pub unsafe extern "C" fn sudoku_set_value(
+    puzzle: *mut SudokuPuzzle,
+    index: usize,
+    value: u8,
+) -> SudokuStatus {
+    let puzzle = match puzzle.as_mut() {
+        Some(puzzle) => puzzle,
+        None => return SudokuStatus::NullPointer,
+    };
+    if index >= SUDOKU_CELLS || value > 9 {
+        return SudokuStatus::OutOfRange;
+    }
+    guard(|| {
+        if !puzzle.puzzle.set_entry(index, value) {
+            return SudokuStatus::Given;
+        }
+        puzzle.puzzle.reset_pencil_marks();
+        SudokuStatus::Ok
+    })
+}
+
+/// Solves the puzzle from the numbers filled in so far, stopping after `max_solutions`.  The
+/// number found is written to `found`, and each solution's 81 values are written to `solutions`
+/// one after another.  `solutions` may be null to only count them.
+///
+/// # Safety
+///
+/// `puzzle` must come from `sudoku_new`, `found` must be writable, and `solutions` must be null or
+/// point to `len` writable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn sudoku_solve(
+    puzzle: *const SudokuPuzzle,
+    max_solutions: usize,
+    solutions: *mut u8,
+    len: usize,
+    found: *mut usize,
+) -> SudokuStatus {
+    let (puzzle, found) = match (puzzle.as_ref(), found.as_mut()) {
+        (Some(puzzle), Some(found)) => (puzzle, found),
+        _ => return SudokuStatus::NullPointer,
+    };
+    *found = 0;
+    let out = if solutions.is_null() {
+        None
+    } else {
+        match max_solutions.checked_mul(SUDOKU_CELLS) {
+            Some(needed) if needed <= len => Some(slice::from_raw_parts_mut(solutions, needed)),
+            _ => return SudokuStatus::BufferTooSmall,
+        }
+    };
+    guard(|| {
+        if max_solutions == 0 || conflict(&puzzle.puzzle) {
+            return SudokuStatus::Ok;
+        }
+        let all = puzzle.puzzle.clone().brute_force_solve_max(max_solutions);
+        if let Some(out) = out {
+            for (chunk, solution) in out.chunks_mut(SUDOKU_CELLS).zip(all.iter()) {
+                chunk.copy_from_slice(solution);
+            }
+        }
+        *found = all.len();
+        SudokuStatus::Ok
+    })
+}
+
+/// Finds the next number to fill in, with the easiest techniques that work.  Returns
+/// `SUDOKU_STATUS_SOLVED` if every cell is filled in, and `SUDOKU_STATUS_NO_SOLUTION` if a number
+/// filled in is wrong.
+///
+/// # Safety
+///
+/// `puzzle` must come from `sudoku_new`, and `hint` must be writable.
+#[no_mangle]
+pub unsafe extern "C" fn sudoku_hint(
+    puzzle: *const SudokuPuzzle,
+    hint: *mut SudokuHint,
+) -> SudokuStatus {
+    let (puzzle, hint) = match (puzzle.as_ref(), hint.as_mut()) {
+        (Some(puzzle), Some(hint)) => (puzzle, hint),
+        _ => return SudokuStatus::NullPointer,
+    };
+    guard(|| {
+        if puzzle.puzzle.is_solved() {
+            return SudokuStatus::Solved;
+        }
+        if conflict(&puzzle.puzzle) {
+            return SudokuStatus::NoSolution;
+        }
+        match puzzle.puzzle.hint() {
+            Some(step) => {
+                let (index, value) = step.placements[0];
+                *hint = SudokuHint {
+                    technique: Some(step.technique).into(),
+                    index,
+                    value,
+                    eliminations: step.eliminations.len(),
+                };
+                SudokuStatus::Ok
+            }
+            None => SudokuStatus::NoSolution,
+        }
+    })
+}
+
+/// Rates the puzzle by the techniques needed to solve it from the numbers filled in so far.
+///
+/// # Safety
+///
+/// `puzzle` must come from `sudoku_new`, and `rating` must be writable.
+#[no_mangle]
+pub unsafe extern "C" fn sudoku_rate(
+    puzzle: *const SudokuPuzzle,
+    rating: *mut SudokuRating,
+) -> SudokuStatus {
+    let (puzzle, out) = match (puzzle.as_ref(), rating.as_mut()) {
+        (Some(puzzle), Some(out)) => (puzzle, out),
+        _ => return SudokuStatus::NullPointer,
+    };
+    guard(|| {
+        if conflict(&puzzle.puzzle) {
+            return SudokuStatus::NoSolution;
+        }
+        let rating = puzzle.puzzle.rate();
+        *out = SudokuRating {
+            hardest: rating.hardest().into(),
+            solutions: rating.solutions,
+            naked_single: rating.count(Technique::NakedSingle),
+            hidden_single: rating.count(Technique::HiddenSingle),
+            naked_tuple: rating.count(Technique::NakedTuple),
+            locked_candidates: rating.count(Technique::LockedCandidates),
+            brute_force: rating.brute_force,
+        };
+        SudokuStatus::Ok
+    })
+}
+
+/// The numbers still possible in an empty cell, as bits: bit 0 for 1, up to bit 8 for 9.  Returns 0
+/// for a filled in cell or an index out of range.
+///
+/// # Safety
+///
+/// `puzzle` must be null or come from `sudoku_new`.
+#[no_mangle]
+pub unsafe extern "C" fn sudoku_candidates(puzzle: *const SudokuPuzzle, index: usize) -> u16 {
+    match puzzle.as_ref() {
+        Some(puzzle) if index < SUDOKU_CELLS && puzzle.puzzle.cells[index].num() == 0 => {
+            puzzle.puzzle.cells[index]
+                .poss_iter()
+                .fold(0, |bits, val| bits | 1 << (val - 1))
+        }
+        _ => 0,
+    }
+}
+
+#[cfg(test)]
+mod ffi_tests {
+    use super::*;
+    use std::ptr;
+
+    const EXAMPLE: &str =
+        "..3.2.6..9..3.5..1..18.64....81.29..7.......8..67.82....26.95..8..2.3..9..5.1.3..";
+
+    fn values(text: &str) -> Vec<u8> {
+        text.bytes()
+            .map(|b| if b == b'.' { 0 } else { b - b'0' })
+            .collect()
+    }
+
+    #[test]
+    fn set_givens_test() {
+        let puz = sudoku_new();
+        let mut givens = values(EXAMPLE);
+        let mut out = [0u8; SUDOKU_CELLS];
+        unsafe {
+            assert_eq!(
+                sudoku_set_givens(puz, givens.as_ptr(), givens.len()),
+                SudokuStatus::Ok
+            );
+            assert_eq!(
+                sudoku_get_values(puz, out.as_mut_ptr(), out.len()),
+                SudokuStatus::Ok
+            );
+            assert_eq!(&out[..], &givens[..]);
+
+            assert_eq!(
+                sudoku_set_givens(puz, givens.as_ptr(), 80),
+                SudokuStatus::WrongLength
+            );
+            givens[0] = 3;
+            assert_eq!(
+                sudoku_set_givens(puz, givens.as_ptr(), givens.len()),
+                SudokuStatus::ConflictingGivens
+            );
+            givens[0] = 10;
+            assert_eq!(
+                sudoku_set_givens(puz, givens.as_ptr(), givens.len()),
+                SudokuStatus::OutOfRange
+            );
+            assert_eq!(
+                sudoku_set_givens(ptr::null_mut(), givens.as_ptr(), givens.len()),
+                SudokuStatus::NullPointer
+            );
+            assert_eq!(
+                sudoku_set_value(puz, 2, 4),
+                SudokuStatus::Given,
+                "invalid givens leave the puzzle alone"
+            );
+            sudoku_free(puz);
+        }
+    }
+
+    #[test]
+    fn solve_hint_rate_test() {
+        let puz = sudoku_new();
+        let text = std::ffi::CString::new(EXAMPLE).unwrap();
+        let mut solutions = [0u8; SUDOKU_CELLS * 2];
+        let mut found = 0;
+        let mut hint = SudokuHint {
+            technique: SudokuTechnique::None,
+            index: 0,
+            value: 0,
+            eliminations: 0,
+        };
+        unsafe {
+            assert_eq!(sudoku_parse(puz, text.as_ptr()), SudokuStatus::Ok);
+            assert_eq!(
+                sudoku_solve(puz, 2, solutions.as_mut_ptr(), solutions.len(), &mut found),
+                SudokuStatus::Ok
+            );
+            assert_eq!(found, 1);
+            assert_eq!(solutions[2], 3);
+            assert_eq!(
+                sudoku_solve(puz, 3, solutions.as_mut_ptr(), solutions.len(), &mut found),
+                SudokuStatus::BufferTooSmall
+            );
+
+            assert_eq!(sudoku_hint(puz, &mut hint), SudokuStatus::Ok);
+            assert_eq!(hint.technique, SudokuTechnique::NakedSingle);
+            assert_eq!(hint.value, solutions[hint.index]);
+            assert_ne!(
+                sudoku_candidates(puz, hint.index) & 1 << (hint.value - 1),
+                0
+            );
+            assert_eq!(sudoku_candidates(puz, 2), 0);
+            assert_eq!(sudoku_candidates(puz, SUDOKU_CELLS), 0);
+
+            let mut rating = std::mem::zeroed::<SudokuRating>();
+            assert_eq!(sudoku_rate(puz, &mut rating), SudokuStatus::Ok);
+            assert_eq!(rating.solutions, 1);
+            assert!(rating.naked_single > 0);
+
+            // A wrong number that does not conflict yet
+            assert_eq!(sudoku_set_value(puz, 0, 1), SudokuStatus::Ok);
+            assert_eq!(sudoku_hint(puz, &mut hint), SudokuStatus::NoSolution);
+            assert_eq!(sudoku_set_value(puz, 0, 0), SudokuStatus::Ok);
+
+            for (index, value) in solutions[..SUDOKU_CELLS].iter().enumerate() {
+                let status = sudoku_set_value(puz, index, *value);
+                assert!(status == SudokuStatus::Ok || status == SudokuStatus::Given);
+            }
+            assert_eq!(sudoku_hint(puz, &mut hint), SudokuStatus::Solved);
+            sudoku_free(puz);
+        }
+    }
+
+    #[test]
+    fn status_message_test() {
+        let message = unsafe { CStr::from_ptr(sudoku_status_message(SudokuStatus::Given as u32)) };
+        assert_eq!(message.to_str(), Ok("the cell is a given"));
+        for (value, status) in SudokuStatus::ALL.iter().enumerate() {
+            assert_eq!(*status as usize, value);
+        }
+        let message = unsafe { CStr::from_ptr(sudoku_status_message(1000)) };
+        assert_eq!(message.to_str(), Ok("unknown status"));
+        assert_eq!(sudoku_abi_version(), SUDOKU_ABI_VERSION);
+    }
+}
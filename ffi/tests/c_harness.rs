@@ -0,0 +1,53 @@
+//! Builds `tests/harness.c` against the static library and the generated header, and runs it.
+//! `cargo test` only builds the library as an rlib, so the test builds the static library first.
+use std::env;
+use std::path::PathBuf;
+use std::process::Command;
+
+/// The directory the library was built in, which is the parent of this test's `deps` directory.
+fn target_dir() -> PathBuf {
+    let mut dir = env::current_exe().unwrap();
+    dir.pop();
+    if dir.ends_with("deps") {
+        dir.pop();
+    }
+    dir
+}
+
+#[test]
+fn c_harness_test() {
+    let crate_dir = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    let target = target_dir();
+    let exe = target.join("sudoku_ffi_harness");
+    let compiler = env::var("CC").unwrap_or_else(|_| "cc".to_string());
+
+    let mut cargo = Command::new(env!("CARGO"));
+    cargo.args(["build", "--lib", "--manifest-path"]);
+    cargo.arg(crate_dir.join("Cargo.toml"));
+    if !cfg!(debug_assertions) {
+        cargo.arg("--release");
+    }
+    let status = cargo.status().unwrap();
+    assert!(status.success(), "Could not build the static library");
+
+    let status = Command::new(&compiler)
+        .arg("-Wall")
+        .arg("-Werror")
+        .arg("-I")
+        .arg(crate_dir.join("include"))
+        .arg(crate_dir.join("tests").join("harness.c"))
+        .arg(target.join("libsudoku_ffi.a"))
+        .args(["-lpthread", "-ldl", "-lm", "-o"])
+        .arg(&exe)
+        .status()
+        .expect("A C compiler is needed to build the harness");
+    assert!(status.success(), "{} could not build the harness", compiler);
+
+    let output = Command::new(&exe).output().unwrap();
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "ok\n");
+}
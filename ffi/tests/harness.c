@@ -0,0 +1,95 @@
+/* Calls every function in sudoku.h the way a C program would.  Prints "ok" and exits with 0 if
+ * everything works, or prints the failed check and exits with 1. */
+#include <stdio.h>
+#include <string.h>
+
+#include "sudoku.h"
+
+#define CHECK(cond)                                                          \
+    do {                                                                     \
+        if (!(cond)) {                                                       \
+            fprintf(stderr, "%s:%d: check failed: %s\n", __FILE__, __LINE__, \
+                    #cond);                                                  \
+            return 1;                                                        \
+        }                                                                    \
+    } while (0)
+
+static const char *EXAMPLE =
+    "53..7....6..195....98....6.8...6...34..8.3..17...2...6.6....28....419..5....8..79";
+static const char *SOLUTION =
+    "534678912672195348198342567859761423426853791713924856961537284287419635345286179";
+
+int main(void) {
+    uint8_t givens[SUDOKU_CELLS];
+    uint8_t values[SUDOKU_CELLS];
+    uint8_t solutions[2 * SUDOKU_CELLS];
+    size_t found = 0;
+    size_t i;
+    SudokuHint hint;
+    SudokuRating rating;
+    SudokuPuzzle *puz;
+
+    CHECK(sudoku_abi_version() == SUDOKU_ABI_VERSION);
+    for (i = 0; i < SUDOKU_CELLS; i++) {
+        givens[i] = EXAMPLE[i] == '.' ? 0 : (uint8_t)(EXAMPLE[i] - '0');
+    }
+
+    puz = sudoku_new();
+    CHECK(puz != NULL);
+    CHECK(sudoku_set_givens(puz, givens, SUDOKU_CELLS) == SUDOKU_STATUS_OK);
+    CHECK(sudoku_get_values(puz, values, sizeof values) == SUDOKU_STATUS_OK);
+    CHECK(memcmp(values, givens, SUDOKU_CELLS) == 0);
+    CHECK(sudoku_set_givens(puz, givens, 9) == SUDOKU_STATUS_WRONG_LENGTH);
+    CHECK(sudoku_set_givens(NULL, givens, SUDOKU_CELLS) == SUDOKU_STATUS_NULL_POINTER);
+    CHECK(sudoku_parse(puz, "not a puzzle") == SUDOKU_STATUS_INVALID_TEXT);
+    CHECK(strcmp(sudoku_status_message(SUDOKU_STATUS_GIVEN), "the cell is a given") == 0);
+    CHECK(strcmp(sudoku_status_message(1000), "unknown status") == 0);
+
+    /* Solve, and count without a buffer */
+    CHECK(sudoku_solve(puz, 2, solutions, sizeof solutions, &found) == SUDOKU_STATUS_OK);
+    CHECK(found == 1);
+    for (i = 0; i < SUDOKU_CELLS; i++) {
+        CHECK(solutions[i] == SOLUTION[i] - '0');
+    }
+    CHECK(sudoku_solve(puz, 3, solutions, sizeof solutions, &found) ==
+          SUDOKU_STATUS_BUFFER_TOO_SMALL);
+    CHECK(sudoku_solve(puz, (size_t)-1, NULL, 0, &found) == SUDOKU_STATUS_OK);
+    CHECK(found == 1);
+
+    /* Candidates of r1c3, which can be 1, 2 or 4 */
+    CHECK(sudoku_candidates(puz, 2) == ((1 << 0) | (1 << 1) | (1 << 3)));
+    CHECK(sudoku_candidates(puz, 0) == 0);
+
+    CHECK(sudoku_rate(puz, &rating) == SUDOKU_STATUS_OK);
+    CHECK(rating.solutions == 1);
+    CHECK(!rating.brute_force);
+    CHECK(rating.hardest != SUDOKU_TECHNIQUE_NONE);
+    CHECK(rating.naked_single > 0);
+
+    /* Follow the hints to the end */
+    for (;;) {
+        SudokuStatus status = sudoku_hint(puz, &hint);
+        if (status == SUDOKU_STATUS_SOLVED) {
+            break;
+        }
+        CHECK(status == SUDOKU_STATUS_OK);
+        CHECK(hint.value == SOLUTION[hint.index] - '0');
+        CHECK(sudoku_set_value(puz, hint.index, hint.value) == SUDOKU_STATUS_OK);
+    }
+    CHECK(sudoku_get_values(puz, values, sizeof values) == SUDOKU_STATUS_OK);
+    for (i = 0; i < SUDOKU_CELLS; i++) {
+        CHECK(values[i] == SOLUTION[i] - '0');
+    }
+    CHECK(sudoku_set_value(puz, 0, 1) == SUDOKU_STATUS_GIVEN);
+    CHECK(sudoku_set_value(puz, 81, 1) == SUDOKU_STATUS_OUT_OF_RANGE);
+
+    /* A wrong number that does not conflict yet */
+    CHECK(sudoku_parse(puz, EXAMPLE) == SUDOKU_STATUS_OK);
+    CHECK(sudoku_set_value(puz, 2, 1) == SUDOKU_STATUS_OK);
+    CHECK(sudoku_hint(puz, &hint) == SUDOKU_STATUS_NO_SOLUTION);
+
+    sudoku_free(puz);
+    sudoku_free(NULL);
+    printf("ok\n");
+    return 0;
+}
@@ -0,0 +1,17 @@
+//! The build writes the header to `OUT_DIR`, so the copy in `include/` is only updated by hand.
+use std::fs;
+use std::path::PathBuf;
+
+#[test]
+fn header_test() {
+    let generated = PathBuf::from(concat!(env!("OUT_DIR"), "/sudoku.h"));
+    let checked_in = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("include")
+        .join("sudoku.h");
+    assert!(
+        fs::read_to_string(&generated).unwrap() == fs::read_to_string(&checked_in).unwrap(),
+        "{} is out of date, copy it from {}",
+        checked_in.display(),
+        generated.display()
+    );
+}
@@ -0,0 +1,430 @@
+//! Checks the puzzles in a file, without knowing anything about the protocol.  Lines and columns
+//! start at 0, as they do in the protocol, and columns count UTF-16 code units, which is what the
+//! protocol means by a character.
+use std::collections::HashMap;
+use std::path::Path;
+use std::time::{Duration, Instant};
+use sudoku::{Alphabet, ParseMode, Puzzle, SudError, Technique};
+
+/// The number of cells in a puzzle.
+const CELLS: usize = 81;
+
+/// How long a puzzle may take to rate, so that a puzzle with few givens cannot hang the editor.
+const RATE_TIME: Duration = Duration::from_secs(1);
+
+/// The formats that can be checked.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Format {
+    /// One puzzle per line, with `#` metadata lines.
+    Sdm,
+    /// One puzzle per file, as a grid, with `#` metadata lines and `[Puzzle]` sections.
+    Sdk,
+}
+
+impl Format {
+    /// The format for a file name or URI.  Anything but `.sdk` is read as `.sdm`.
+    pub fn from_path(path: &str) -> Format {
+        match Path::new(path).extension().and_then(|e| e.to_str()) {
+            Some(ext) if ext.eq_ignore_ascii_case("sdk") => Format::Sdk,
+            _ => Format::Sdm,
+        }
+    }
+}
+
+/// The severities of the protocol, which it numbers from 1.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Severity {
+    Error = 1,
+    Warning = 2,
+    Information = 3,
+}
+
+impl Severity {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+            Severity::Information => "info",
+        }
+    }
+}
+
+/// A problem, or a note, about part of the file.  The range is `start` up to, but not including,
+/// `end`, written as (line, column).
+#[derive(Clone, Debug, PartialEq)]
+pub struct Diagnostic {
+    pub start: (usize, usize),
+    pub end: (usize, usize),
+    pub severity: Severity,
+    pub message: String,
+}
+
+impl Diagnostic {
+    /// The same diagnostic on another line, for one found on line 0.
+    fn on_line(&self, number: usize) -> Diagnostic {
+        Diagnostic {
+            start: (self.start.0 + number, self.start.1),
+            end: (self.end.0 + number, self.end.1),
+            ..self.clone()
+        }
+    }
+
+    fn new(
+        start: (usize, usize),
+        end: (usize, usize),
+        severity: Severity,
+        message: String,
+    ) -> Self {
+        Diagnostic {
+            start,
+            end,
+            severity,
+            message,
+        }
+    }
+}
+
+/// The lines of one puzzle, with their line numbers in the file.
+type Lines<'a> = [(usize, &'a str)];
+
+/// Checks every puzzle in the text.
+pub fn check(text: &str, format: Format) -> Vec<Diagnostic> {
+    Cache::default().check(text, format)
+}
+
+/// The diagnostics of the `.sdm` lines checked last time, by the text of the line, with the line
+/// numbers set to 0.  Rating is slow, so when a document changes only the lines that changed are
+/// checked again, and a puzzle that could not be rated in time is not tried again until its line
+/// changes.
+#[derive(Debug, Default)]
+pub struct Cache {
+    lines: HashMap<String, Vec<Diagnostic>>,
+}
+
+impl Cache {
+    /// Checks every puzzle in the text, like `check`.  Only the lines of this text are kept for
+    /// next time.
+    pub fn check(&mut self, text: &str, format: Format) -> Vec<Diagnostic> {
+        let mut diagnostics = Vec::new();
+        match format {
+            Format::Sdm => {
+                let mut checked: HashMap<String, Vec<Diagnostic>> = HashMap::new();
+                for (number, line) in text.lines().enumerate() {
+                    let trimmed = line.trim();
+                    if trimmed.is_empty() || trimmed.starts_with('#') {
+                        continue;
+                    }
+                    let found = match checked.get(line).cloned() {
+                        Some(found) => found,
+                        None => self.lines.remove(line).unwrap_or_else(|| {
+                            let mut found = Vec::new();
+                            check_puzzle(&[(0, line)], &mut found);
+                            found
+                        }),
+                    };
+                    diagnostics.extend(found.iter().map(|d| d.on_line(number)));
+                    checked.insert(line.to_string(), found);
+                }
+                self.lines = checked;
+            }
+            // Like `read_sdk`, the puzzle is the lines before the first section and in the
+            // `[Puzzle]` sections
+            Format::Sdk => {
+                let mut in_puzzle = true;
+                let mut lines = Vec::new();
+                for (number, line) in text.lines().enumerate() {
+                    if line.trim_start().starts_with('[') {
+                        in_puzzle = line.trim().eq_ignore_ascii_case("[Puzzle]");
+                    } else if in_puzzle && !line.trim_end().starts_with('#') {
+                        lines.push((number, line));
+                    }
+                }
+                check_puzzle(&lines, &mut diagnostics);
+            }
+        }
+        diagnostics
+    }
+}
+
+/// The number of UTF-16 code units in the first `chars` characters of the line.
+fn utf16_column(line: &str, chars: usize) -> usize {
+    line.chars().take(chars).map(char::len_utf16).sum()
+}
+
+/// The (line, column) of each character that is read as a cell, and the character.  Whitespace
+/// and grid lines are skipped, as they are by `Alphabet::parse`.
+fn cell_positions(lines: &Lines) -> Vec<((usize, usize), char)> {
+    lines
+        .iter()
+        .flat_map(|(number, line)| {
+            line.chars()
+                .scan(0, move |column, c| {
+                    let at = (*number, *column);
+                    *column += c.len_utf16();
+                    Some((at, c))
+                })
+                .filter(|(_, c)| !c.is_whitespace() && !"|-+=".contains(*c))
+        })
+        .collect()
+}
+
+/// The range from the first to the last character of the lines, not counting blank lines.
+fn whole(lines: &Lines) -> ((usize, usize), (usize, usize)) {
+    let mut used = lines.iter().filter(|(_, l)| !l.trim().is_empty());
+    match (used.next(), used.next_back()) {
+        (Some(first), last) => {
+            let last = last.unwrap_or(first);
+            ((first.0, 0), (last.0, last.1.encode_utf16().count()))
+        }
+        (None, _) => ((0, 0), (0, 0)),
+    }
+}
+
+fn check_puzzle(lines: &Lines, diagnostics: &mut Vec<Diagnostic>) {
+    let text = lines
+        .iter()
+        .map(|(_, l)| *l)
+        .collect::<Vec<&str>>()
+        .join("\n");
+    let (start, end) = whole(lines);
+    let alphabet = Alphabet::default();
+    let input = match alphabet.parse(&text, ParseMode::Strict) {
+        Ok(input) => input,
+        Err(SudError::InvalidChar {
+            line,
+            column,
+            found,
+        }) => {
+            let (number, text) = lines[line - 1];
+            let at = (number, utf16_column(text, column - 1));
+            diagnostics.push(Diagnostic::new(
+                at,
+                (at.0, at.1 + found.len_utf16()),
+                Severity::Error,
+                format!("'{}' is not a number or an empty cell", found),
+            ));
+            return;
+        }
+        Err(SudError::OutOfRange {
+            line,
+            column,
+            value,
+        }) => {
+            let (number, text) = lines[line - 1];
+            let at = (number, utf16_column(text, column - 1));
+            diagnostics.push(Diagnostic::new(
+                at,
+                (at.0, at.1 + 1),
+                Severity::Error,
+                format!("{} is larger than 9", value),
+            ));
+            return;
+        }
+        Err(SudError::ConflictingGivens { .. }) => {
+            // Every character was read, so each cell can be found, and every conflict reported
+            let positions = cell_positions(lines);
+            let values = positions
+                .iter()
+                .map(|(_, c)| alphabet.value(*c).unwrap_or(0))
+                .collect::<Vec<_>>();
+            let mut puz = Puzzle::new();
+            puz.set_initial(values);
+            for (index, (at, _)) in positions.iter().enumerate() {
+                let value = puz.cells[index].num();
+                if value != 0 && !puz.valid_entry(index) {
+                    diagnostics.push(Diagnostic::new(
+                        *at,
+                        (at.0, at.1 + 1),
+                        Severity::Error,
+                        format!(
+                            "{} at r{}c{} is repeated in its row, column or box",
+                            value,
+                            index / 9 + 1,
+                            index % 9 + 1
+                        ),
+                    ));
+                }
+            }
+            return;
+        }
+        Err(SudError::WrongLength { found, .. }) => {
+            diagnostics.push(Diagnostic::new(
+                start,
+                end,
+                Severity::Error,
+                format!("Expected {} cells, found {}", CELLS, found),
+            ));
+            return;
+        }
+        Err(e) => {
+            diagnostics.push(Diagnostic::new(start, end, Severity::Error, e.to_string()));
+            return;
+        }
+    };
+
+    let mut puz = Puzzle::new();
+    puz.set_initial(input);
+    let rating = match puz.rate_until(Instant::now() + RATE_TIME) {
+        Ok(rating) => rating,
+        Err(_) => {
+            diagnostics.push(Diagnostic::new(
+                start,
+                end,
+                Severity::Warning,
+                "The puzzle could not be rated in time".to_string(),
+            ));
+            return;
+        }
+    };
+    let (severity, message) = match rating.solutions {
+        0 => (Severity::Error, "The puzzle has no solution".to_string()),
+        1 => match rating.hardest() {
+            None => (Severity::Information, "The puzzle is solved".to_string()),
+            Some(Technique::BruteForce) => (
+                Severity::Information,
+                "Difficulty: brute force, as the human techniques get stuck".to_string(),
+            ),
+            Some(hardest) => (Severity::Information, format!("Difficulty: {}", hardest)),
+        },
+        _ => (
+            Severity::Warning,
+            "The puzzle has more than one solution".to_string(),
+        ),
+    };
+    diagnostics.push(Diagnostic::new(start, end, severity, message));
+}
+
+#[cfg(test)]
+mod check_tests {
+    use super::*;
+
+    const EXAMPLE: &str =
+        "53..7....6..195....98....6.8...6...34..8.3..17...2...6.6....28....419..5....8..79";
+    const HARD: &str =
+        "..53.....8......2..7..1.5..4....53...1..7...6..32...8..6.5....9..4....3......97..";
+
+    #[test]
+    fn sdm_test() {
+        let text = format!(
+            "#A Someone\n{}\n\n{}\n53x\n55{}\n{}\n",
+            EXAMPLE,
+            HARD,
+            &EXAMPLE[2..],
+            ".".repeat(81)
+        );
+        let diagnostics = check(&text, Format::Sdm);
+        assert_eq!(diagnostics.len(), 6);
+
+        assert_eq!(diagnostics[0].start, (1, 0));
+        assert_eq!(diagnostics[0].end, (1, 81));
+        assert_eq!(diagnostics[0].severity, Severity::Information);
+        assert!(diagnostics[0]
+            .message
+            .starts_with("Difficulty: naked single"));
+        assert_eq!(diagnostics[1].start, (3, 0));
+        assert!(diagnostics[1].message.contains("brute force"));
+
+        assert_eq!(diagnostics[2].start, (4, 2));
+        assert_eq!(diagnostics[2].end, (4, 3));
+        assert_eq!(diagnostics[2].severity, Severity::Error);
+        // The two 5s in the first row
+        assert_eq!(diagnostics[3].start, (5, 0));
+        assert_eq!(diagnostics[4].start, (5, 1));
+        assert_eq!(diagnostics[4].severity, Severity::Error);
+
+        assert_eq!(diagnostics[5].start, (6, 0));
+        assert_eq!(diagnostics[5].severity, Severity::Warning);
+    }
+
+    #[test]
+    fn sdk_test() {
+        let mut text = String::from("#A Someone\n[Puzzle]\n");
+        for row in 0..9 {
+            text.push_str(&EXAMPLE[row * 9..row * 9 + 9]);
+            text.push('\n');
+        }
+        text.push_str("[State]\nanything\n");
+        let diagnostics = check(&text, Format::Sdk);
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].start, (2, 0));
+        assert_eq!(diagnostics[0].end, (10, 9));
+        assert_eq!(diagnostics[0].severity, Severity::Information);
+
+        // A 1 is added to the fourth row, below the 1 in the second row
+        let text = text.replacen("8...6...3", "8..16...3", 1);
+        let diagnostics = check(&text, Format::Sdk);
+        assert!(diagnostics
+            .iter()
+            .any(|d| d.start == (5, 3) && d.severity == Severity::Error));
+
+        let diagnostics = check("[Puzzle]\n123\n", Format::Sdk);
+        assert_eq!(diagnostics[0].message, "Expected 81 cells, found 3");
+        assert_eq!(diagnostics[0].start, (1, 0));
+
+        // Only the lines before the first section and in `[Puzzle]` hold the puzzle
+        let text = format!("[State]\n1\n[Puzzle]\n{}\n[Notes]\n2\n", EXAMPLE);
+        let diagnostics = check(&text, Format::Sdk);
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].start, (3, 0));
+        assert_eq!(diagnostics[0].severity, Severity::Information);
+    }
+
+    #[test]
+    fn utf16_test() {
+        // '😀' is two code units
+        let diagnostics = check(&format!("5😀{}", &EXAMPLE[1..]), Format::Sdm);
+        assert_eq!(diagnostics[0].start, (0, 1));
+        assert_eq!(diagnostics[0].end, (0, 3));
+        let diagnostics = check(&format!("{}😀", EXAMPLE), Format::Sdm);
+        assert_eq!(diagnostics[0].start, (0, 81));
+        assert_eq!(diagnostics[0].end, (0, 83));
+    }
+
+    #[test]
+    fn cache_test() {
+        let mut cache = Cache::default();
+        let text = format!("{}\n53x\n{}\n", EXAMPLE, EXAMPLE);
+        let diagnostics = cache.check(&text, Format::Sdm);
+        assert_eq!(diagnostics, check(&text, Format::Sdm));
+        assert_eq!(diagnostics[2].start, (2, 0));
+        assert_eq!(cache.lines.len(), 2);
+
+        // The lines that are left keep their diagnostics, and moved lines get their new number
+        let text = format!("#A Someone\n{}\n", EXAMPLE);
+        let diagnostics = cache.check(&text, Format::Sdm);
+        assert_eq!(diagnostics, check(&text, Format::Sdm));
+        assert_eq!(diagnostics[0].start, (1, 0));
+        assert_eq!(cache.lines.len(), 1);
+        assert!(cache.lines.contains_key(EXAMPLE));
+    }
+
+    #[test]
+    fn unsolvable_test() {
+        let text = format!("{:.<81}", "12345678.........9");
+        let diagnostics = check(&text, Format::Sdm);
+        assert_eq!(diagnostics[0].message, "The puzzle has no solution");
+        assert_eq!(diagnostics[0].severity, Severity::Error);
+    }
+
+    #[test]
+    fn rate_time_test() {
+        // The last cell cannot be filled in, which the search only finds after trying to fill in
+        // every other cell
+        let text = format!("{:.<72}12345678.", "........9");
+        let start = Instant::now();
+        let diagnostics = check(&text, Format::Sdm);
+        assert!(start.elapsed() < RATE_TIME * 5);
+        assert_eq!(
+            diagnostics[0].message,
+            "The puzzle could not be rated in time"
+        );
+        assert_eq!(diagnostics[0].severity, Severity::Warning);
+    }
+
+    #[test]
+    fn format_test() {
+        assert_eq!(Format::from_path("file:///puzzles/a.SDK"), Format::Sdk);
+        assert_eq!(Format::from_path("a.sdm"), Format::Sdm);
+        assert_eq!(Format::from_path("a"), Format::Sdm);
+    }
+}
@@ -0,0 +1,94 @@
+//! A checker for `.sdm` and `.sdk` files, which reports malformed lines, repeated givens, puzzles
+//! without exactly one solution, and how hard each puzzle is.  It speaks the Language Server
+//! Protocol on stdin and stdout so that editors show the problems as the file is written, or
+//! checks the files given on the command line.
+mod check;
+mod protocol;
+
+use crate::check::{check, Format, Severity};
+use crate::protocol::{read_message, write_message, Session};
+use std::fs;
+use std::io::{self, BufReader};
+use std::process;
+
+const USAGE: &str = "Usage: sudoku-lsp [--stdio | FILE...]
+
+Checks .sdm and .sdk puzzle files.  With no files, it is a language server on stdin and stdout.
+With files, it prints each problem as FILE:LINE:COLUMN and exits with 1 if there are errors.
+Files that do not end in .sdk are read as .sdm.
+
+Options:
+      --stdio  Run the language server (the default)
+  -h, --help   Print this message";
+
+fn serve() -> io::Result<i32> {
+    let mut input = BufReader::new(io::stdin());
+    let mut output = io::stdout();
+    let mut session = Session::new();
+    while let Some(message) = read_message(&mut input)? {
+        for reply in session.handle(&message) {
+            write_message(&mut output, &reply)?;
+        }
+        if let Some(code) = session.exit_code() {
+            return Ok(code);
+        }
+    }
+    // The client went away without asking the server to exit
+    Ok(1)
+}
+
+/// Prints the problems in the files.  Returns true if any has an error.
+fn check_files(files: &[String]) -> bool {
+    let mut failed = false;
+    for file in files {
+        let text = match fs::read_to_string(file) {
+            Ok(text) => text,
+            Err(e) => {
+                eprintln!("{}: {}", file, e);
+                failed = true;
+                continue;
+            }
+        };
+        for diagnostic in check(&text, Format::from_path(file)) {
+            failed |= diagnostic.severity == Severity::Error;
+            println!(
+                "{}:{}:{}: {}: {}",
+                file,
+                diagnostic.start.0 + 1,
+                diagnostic.start.1 + 1,
+                diagnostic.severity.name(),
+                diagnostic.message
+            );
+        }
+    }
+    failed
+}
+
+fn main() {
+    let mut files = Vec::new();
+    for arg in std::env::args().skip(1) {
+        match arg.as_str() {
+            "-h" | "--help" => {
+                println!("{}", USAGE);
+                return;
+            }
+            "--stdio" => (),
+            _ if arg.starts_with('-') => {
+                eprintln!("Unexpected argument '{}'\n\n{}", arg, USAGE);
+                process::exit(2);
+            }
+            _ => files.push(arg),
+        }
+    }
+
+    if !files.is_empty() {
+        process::exit(check_files(&files) as i32);
+    }
+    match serve() {
+        Ok(code) => process::exit(code),
+        Err(e) => {
+            eprintln!("{}", e);
+            process::exit(2);
+        }
+    }
+}
@@ -0,0 +1,246 @@
+//! The parts of the Language Server Protocol that the checker needs: JSON-RPC messages framed
+//! with a `Content-Length` header, and the notifications for opening, changing and closing
+//! documents.  The whole document is sent on every change.
+use crate::check::{Cache, Diagnostic, Format};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::io::{self, BufRead, Write};
+
+/// JSON-RPC error codes.
+const PARSE_ERROR: i64 = -32700;
+const INVALID_REQUEST: i64 = -32600;
+const METHOD_NOT_FOUND: i64 = -32601;
+
+/// Reads one message.  Returns `None` at the end of the input.
+pub fn read_message(input: &mut impl BufRead) -> io::Result<Option<String>> {
+    let mut length = None;
+    loop {
+        let mut header = String::new();
+        if input.read_line(&mut header)? == 0 {
+            return Ok(None);
+        }
+        let header = header.trim_end();
+        if header.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            if name.eq_ignore_ascii_case("Content-Length") {
+                length = value.trim().parse::<usize>().ok();
+            }
+        }
+    }
+    let length = length.ok_or_else(|| {
+        io::Error::new(io::ErrorKind::InvalidData, "Missing Content-Length header")
+    })?;
+    let mut body = vec![0; length];
+    input.read_exact(&mut body)?;
+    Ok(Some(String::from_utf8_lossy(&body).into_owned()))
+}
+
+pub fn write_message(output: &mut impl Write, message: &Value) -> io::Result<()> {
+    let body = message.to_string();
+    write!(output, "Content-Length: {}\r\n\r\n{}", body.len(), body)?;
+    output.flush()
+}
+
+fn diagnostic_json(diagnostic: &Diagnostic) -> Value {
+    json!({
+        "range": {
+            "start": { "line": diagnostic.start.0, "character": diagnostic.start.1 },
+            "end": { "line": diagnostic.end.0, "character": diagnostic.end.1 },
+        },
+        "severity": diagnostic.severity as u8,
+        "source": "sudoku",
+        "message": diagnostic.message,
+    })
+}
+
+fn publish(uri: &str, diagnostics: &[Diagnostic]) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "method": "textDocument/publishDiagnostics",
+        "params": {
+            "uri": uri,
+            "diagnostics": diagnostics.iter().map(diagnostic_json).collect::<Vec<Value>>(),
+        },
+    })
+}
+
+fn response(id: Value, result: Value) -> Value {
+    json!({ "jsonrpc": "2.0", "id": id, "result": result })
+}
+
+fn error(id: Value, code: i64, message: &str) -> Value {
+    json!({ "jsonrpc": "2.0", "id": id, "error": { "code": code, "message": message } })
+}
+
+/// The state of a session: the open documents with what was found in them, and whether the
+/// client has asked to shut down.
+#[derive(Default)]
+pub struct Session {
+    documents: HashMap<String, (Format, Cache)>,
+    shutdown: bool,
+    exit: Option<i32>,
+}
+
+impl Session {
+    pub fn new() -> Session {
+        Session::default()
+    }
+
+    /// The exit code once the client has sent `exit`: 0 if it shut down first, as it should.
+    pub fn exit_code(&self) -> Option<i32> {
+        self.exit
+    }
+
+    /// Handles one message and returns the messages to send back.
+    pub fn handle(&mut self, message: &str) -> Vec<Value> {
+        let message: Value = match serde_json::from_str(message) {
+            Ok(message) => message,
+            Err(e) => return vec![error(Value::Null, PARSE_ERROR, &e.to_string())],
+        };
+        let id = message.get("id").cloned();
+        let params = &message["params"];
+        let method = match message["method"].as_str() {
+            Some(method) => method,
+            // A response to a request from the server, which never sends any
+            None if message.get("result").is_some() || message.get("error").is_some() => {
+                return Vec::new()
+            }
+            None => {
+                return vec![error(
+                    id.unwrap_or(Value::Null),
+                    INVALID_REQUEST,
+                    "Missing method",
+                )]
+            }
+        };
+        match (method, id) {
+            ("initialize", Some(id)) => vec![response(
+                id,
+                json!({
+                    "capabilities": { "textDocumentSync": { "openClose": true, "change": 1 } },
+                    "serverInfo": { "name": "sudoku-lsp", "version": env!("CARGO_PKG_VERSION") },
+                }),
+            )],
+            ("shutdown", Some(id)) => {
+                self.shutdown = true;
+                vec![response(id, Value::Null)]
+            }
+            ("exit", _) => {
+                self.exit = Some(if self.shutdown { 0 } else { 1 });
+                Vec::new()
+            }
+            ("textDocument/didOpen", None) => {
+                let document = &params["textDocument"];
+                let uri = document["uri"].as_str().unwrap_or_default();
+                let format = match document["languageId"].as_str() {
+                    Some("sdk") => Format::Sdk,
+                    Some("sdm") => Format::Sdm,
+                    _ => Format::from_path(uri),
+                };
+                let mut cache = Cache::default();
+                let text = document["text"].as_str().unwrap_or_default();
+                let diagnostics = cache.check(text, format);
+                self.documents.insert(uri.to_string(), (format, cache));
+                vec![publish(uri, &diagnostics)]
+            }
+            ("textDocument/didChange", None) => {
+                let uri = params["textDocument"]["uri"].as_str().unwrap_or_default();
+                let (format, cache) = self
+                    .documents
+                    .entry(uri.to_string())
+                    .or_insert_with(|| (Format::from_path(uri), Cache::default()));
+                match params["contentChanges"]
+                    .as_array()
+                    .and_then(|changes| changes.last())
+                    .and_then(|change| change["text"].as_str())
+                {
+                    Some(text) => vec![publish(uri, &cache.check(text, *format))],
+                    None => Vec::new(),
+                }
+            }
+            ("textDocument/didClose", None) => {
+                let uri = params["textDocument"]["uri"].as_str().unwrap_or_default();
+                self.documents.remove(uri);
+                vec![publish(uri, &[])]
+            }
+            (_, Some(id)) => vec![error(
+                id,
+                METHOD_NOT_FOUND,
+                &format!("Unknown method {}", method),
+            )],
+            // Other notifications, such as `initialized` and `$/cancelRequest`, need no answer
+            (_, None) => Vec::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod protocol_tests {
+    use super::*;
+    use std::io::Cursor;
+
+    #[test]
+    fn framing_test() {
+        let mut out = Vec::new();
+        write_message(&mut out, &json!({ "id": 1 })).unwrap();
+        assert_eq!(out, b"Content-Length: 8\r\n\r\n{\"id\":1}");
+
+        out.extend_from_slice(
+            b"Content-Type: application/vscode-jsonrpc\r\ncontent-length: 2\r\n\r\n{}",
+        );
+        let mut input = Cursor::new(out);
+        assert_eq!(read_message(&mut input).unwrap().unwrap(), "{\"id\":1}");
+        assert_eq!(read_message(&mut input).unwrap().unwrap(), "{}");
+        assert_eq!(read_message(&mut input).unwrap(), None);
+        assert!(read_message(&mut Cursor::new(b"\r\n{}".to_vec())).is_err());
+    }
+
+    #[test]
+    fn session_test() {
+        let mut session = Session::new();
+        let replies =
+            session.handle(r#"{"jsonrpc":"2.0","id":1,"method":"initialize","params":{}}"#);
+        assert_eq!(
+            replies[0]["result"]["capabilities"]["textDocumentSync"]["change"],
+            1
+        );
+        assert!(session
+            .handle(r#"{"jsonrpc":"2.0","method":"initialized","params":{}}"#)
+            .is_empty());
+
+        let open = json!({
+            "jsonrpc": "2.0",
+            "method": "textDocument/didOpen",
+            "params": { "textDocument": {
+                "uri": "file:///a.sdm", "languageId": "plaintext", "version": 1, "text": "53x",
+            }},
+        });
+        let replies = session.handle(&open.to_string());
+        let params = &replies[0]["params"];
+        assert_eq!(params["uri"], "file:///a.sdm");
+        assert_eq!(params["diagnostics"][0]["severity"], 1);
+        assert_eq!(params["diagnostics"][0]["range"]["start"]["character"], 2);
+
+        let change = json!({
+            "jsonrpc": "2.0",
+            "method": "textDocument/didChange",
+            "params": {
+                "textDocument": { "uri": "file:///a.sdm", "version": 2 },
+                "contentChanges": [{ "text": "" }],
+            },
+        });
+        let replies = session.handle(&change.to_string());
+        assert_eq!(replies[0]["params"]["diagnostics"], json!([]));
+
+        let replies = session.handle(r#"{"jsonrpc":"2.0","id":2,"method":"textDocument/hover"}"#);
+        assert_eq!(replies[0]["error"]["code"], METHOD_NOT_FOUND);
+        assert_eq!(session.handle("{")[0]["error"]["code"], PARSE_ERROR);
+
+        session.handle(r#"{"jsonrpc":"2.0","id":3,"method":"shutdown"}"#);
+        assert_eq!(session.exit_code(), None);
+        session.handle(r#"{"jsonrpc":"2.0","method":"exit"}"#);
+        assert_eq!(session.exit_code(), Some(0));
+    }
+}
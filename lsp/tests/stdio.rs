@@ -0,0 +1,116 @@
+//! Runs the checker as a language server and as a command, the way an editor or a script would.
+use serde_json::{json, Value};
+use std::fs;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::process::{Command, Stdio};
+
+const EXAMPLE: &str =
+    "53..7....6..195....98....6.8...6...34..8.3..17...2...6.6....28....419..5....8..79";
+
+fn frame(message: Value) -> String {
+    let body = message.to_string();
+    format!("Content-Length: {}\r\n\r\n{}", body.len(), body)
+}
+
+/// Reads the messages written by the server.
+fn messages(output: &[u8]) -> Vec<Value> {
+    let mut reader = BufReader::new(output);
+    let mut messages = Vec::new();
+    loop {
+        let mut header = String::new();
+        if reader.read_line(&mut header).unwrap() == 0 {
+            return messages;
+        }
+        let length: usize = header
+            .trim()
+            .trim_start_matches("Content-Length: ")
+            .parse()
+            .unwrap();
+        reader.read_line(&mut String::new()).unwrap();
+        let mut body = vec![0; length];
+        reader.read_exact(&mut body).unwrap();
+        messages.push(serde_json::from_slice(&body).unwrap());
+    }
+}
+
+#[test]
+fn server_test() {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_sudoku-lsp"))
+        .arg("--stdio")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    let text = format!("#A Someone\n{}\n55{}\n", EXAMPLE, &EXAMPLE[2..]);
+    let input = [
+        json!({ "jsonrpc": "2.0", "id": 1, "method": "initialize", "params": {} }),
+        json!({ "jsonrpc": "2.0", "method": "initialized", "params": {} }),
+        json!({
+            "jsonrpc": "2.0",
+            "method": "textDocument/didOpen",
+            "params": { "textDocument": {
+                "uri": "file:///puzzles.sdm", "languageId": "sdm", "version": 1, "text": text,
+            }},
+        }),
+        json!({
+            "jsonrpc": "2.0",
+            "method": "textDocument/didClose",
+            "params": { "textDocument": { "uri": "file:///puzzles.sdm" } },
+        }),
+        json!({ "jsonrpc": "2.0", "id": 2, "method": "shutdown" }),
+        json!({ "jsonrpc": "2.0", "method": "exit" }),
+    ];
+    let input: String = input.iter().cloned().map(frame).collect();
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(input.as_bytes())
+        .unwrap();
+    let output = child.wait_with_output().unwrap();
+    assert!(output.status.success());
+
+    let messages = messages(&output.stdout);
+    assert_eq!(messages.len(), 4);
+    assert_eq!(messages[0]["id"], 1);
+    assert_eq!(messages[1]["method"], "textDocument/publishDiagnostics");
+    let diagnostics = messages[1]["params"]["diagnostics"].as_array().unwrap();
+    assert_eq!(diagnostics.len(), 3);
+    assert_eq!(diagnostics[0]["severity"], 3);
+    assert_eq!(diagnostics[0]["range"]["start"]["line"], 1);
+    assert_eq!(diagnostics[1]["severity"], 1);
+    assert_eq!(diagnostics[1]["range"]["start"]["line"], 2);
+    assert_eq!(messages[2]["params"]["diagnostics"], json!([]));
+    assert_eq!(
+        messages[3],
+        json!({ "jsonrpc": "2.0", "id": 2, "result": null })
+    );
+}
+
+#[test]
+fn files_test() {
+    let dir = std::env::temp_dir().join(format!("sudoku-lsp-{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let good = dir.join("good.sdm");
+    let bad = dir.join("bad.sdk");
+    fs::write(&good, format!("{}\n", EXAMPLE)).unwrap();
+    fs::write(&bad, "[Puzzle]\n123\n").unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_sudoku-lsp"))
+        .arg(&good)
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("good.sdm:1:1: info: Difficulty: naked single"));
+
+    let output = Command::new(env!("CARGO_BIN_EXE_sudoku-lsp"))
+        .arg(&good)
+        .arg(&bad)
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(1));
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("bad.sdk:2:1: error: Expected 81 cells, found 3"));
+    fs::remove_dir_all(&dir).unwrap();
+}
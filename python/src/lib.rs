@@ -0,0 +1,466 @@
+//! Python bindings for the sudoku library, built with pyo3 as the `sudoku` module.  Build a wheel
+//! with `maturin build` in this directory, or see `tests/python.rs` for using the library directly.
+//!
+//! Cells are numbered 0 to 80, row by row, and hold 1 to 9, or 0 when empty.  Library errors are
+//! raised as `ValueError`.  The batch functions, `solve_many` and `rate_many`, release the GIL
+//! while they work, so they can be run from several Python threads at once.
+use pyo3::exceptions::{PyIndexError, PyValueError};
+use pyo3::prelude::*;
+use std::collections::HashMap;
+use sudoku::{
+    read_pencil_grid, read_sdk, read_sdm, write_pencil_grid, write_sdk, write_sdm, BruteForce,
+    PuzOutput, Puzzle, ShareCode, SudError, Technique,
+};
+
+/// The number of cells in a puzzle.
+const CELLS: usize = 81;
+
+fn value_error(e: SudError) -> PyErr {
+    PyValueError::new_err(e.to_string())
+}
+
+fn check_index(index: usize) -> PyResult<()> {
+    if index < CELLS {
+        Ok(())
+    } else {
+        Err(PyIndexError::new_err(format!(
+            "Cell {} is not between 0 and {}",
+            index,
+            CELLS - 1
+        )))
+    }
+}
+
+/// Values as a list of ints.  A `Vec<u8>` would become `bytes` in Python.
+fn ints(values: &[u8]) -> Vec<usize> {
+    values.iter().map(|v| *v as usize).collect()
+}
+
+/// Cells whose numbers are repeated in their row, column, or box.
+fn conflicts(puz: &Puzzle) -> Vec<usize> {
+    (0..CELLS)
+        .filter(|i| puz.cells[*i].num() != 0 && !puz.valid_entry(*i))
+        .collect()
+}
+
+/// A sudoku puzzle, with the givens and any numbers filled in since.
+#[pyclass(name = "Puzzle", module = "sudoku", eq, from_py_object)]
+#[derive(Clone, PartialEq)]
+struct PyPuzzle {
+    puzzle: Puzzle,
+}
+
+#[pymethods]
+impl PyPuzzle {
+    /// Reads a puzzle, such as 81 digits with `.` or `0` for empty cells.
+    #[new]
+    fn new(text: &str) -> PyResult<Self> {
+        text.parse()
+            .map(|puzzle| PyPuzzle { puzzle })
+            .map_err(value_error)
+    }
+
+    /// Starts a puzzle from 81 givens, from 0 to 9.
+    #[staticmethod]
+    fn from_values(values: Vec<u8>) -> PyResult<Self> {
+        let text = values
+            .as_string()
+            .map_err(|_| PyValueError::new_err("Values must be from 0 to 9"))?;
+        PyPuzzle::new(&text)
+    }
+
+    #[staticmethod]
+    fn from_share_code(code: &str) -> PyResult<Self> {
+        ShareCode(code)
+            .to_puzzle()
+            .map(|puzzle| PyPuzzle { puzzle })
+            .map_err(value_error)
+    }
+
+    /// The 81 values of the cells, givens and filled in.
+    #[getter]
+    fn values(&self) -> Vec<usize> {
+        self.puzzle.cells.iter().map(|c| c.num() as usize).collect()
+    }
+
+    /// The 81 givens, with 0 for every other cell.
+    #[getter]
+    fn givens(&self) -> Vec<usize> {
+        self.puzzle
+            .cells
+            .iter()
+            .map(|c| if c.given() { c.num() as usize } else { 0 })
+            .collect()
+    }
+
+    /// The number of givens.
+    #[getter]
+    fn clues(&self) -> usize {
+        self.puzzle.cells.iter().filter(|c| c.given()).count()
+    }
+
+    /// Information such as the author and source, from `.sdk` and `.sdm` files.
+    #[getter]
+    fn metadata(&self) -> HashMap<&'static str, String> {
+        let meta = self.puzzle.meta();
+        let fields = [
+            ("author", &meta.author),
+            ("description", &meta.description),
+            ("difficulty", &meta.difficulty),
+            ("source", &meta.source),
+            ("url", &meta.url),
+            ("date", &meta.date),
+        ];
+        let mut out: HashMap<&'static str, String> = fields
+            .iter()
+            .filter_map(|(name, value)| value.as_ref().map(|v| (*name, v.clone())))
+            .collect();
+        if !meta.comments.is_empty() {
+            out.insert("comments", meta.comments.join("\n"));
+        }
+        out
+    }
+
+    /// The numbers still possible in an empty cell, or an empty list for a filled in cell.
+    fn candidates(&self, index: usize) -> PyResult<Vec<usize>> {
+        check_index(index)?;
+        let cell = &self.puzzle.cells[index];
+        if cell.num() != 0 {
+            return Ok(Vec::new());
+        }
+        Ok(cell.poss_iter().map(|v| *v as usize).collect())
+    }
+
+    /// Fills in a cell that is not a given, or empties it if `value` is 0.  The number is not
+    /// checked against the others, the way a player may make a mistake.
+    fn set_value(&mut self, index: usize, value: u8) -> PyResult<()> {
+        check_index(index)?;
+        if value > 9 {
+            return Err(PyValueError::new_err(format!(
+                "{} is not from 0 to 9",
+                value
+            )));
+        }
+        if !self.puzzle.set_entry(index, value) {
+            return Err(PyValueError::new_err(format!("Cell {} is a given", index)));
+        }
+        self.puzzle.reset_pencil_marks();
+        Ok(())
+    }
+
+    fn is_solved(&self) -> bool {
+        self.puzzle.is_solved()
+    }
+
+    /// Cells whose numbers are repeated in their row, column, or box.
+    fn conflicts(&self) -> Vec<usize> {
+        conflicts(&self.puzzle)
+    }
+
+    /// Solves the puzzle from the numbers filled in so far, stopping after `max` solutions.  Each
+    /// solution is a list of 81 values.
+    #[pyo3(signature = (max = 2))]
+    fn solve(&self, py: Python<'_>, max: usize) -> Vec<Vec<usize>> {
+        let puzzle = self.puzzle.clone();
+        py.detach(move || solve(puzzle, max))
+    }
+
+    /// The next number to fill in, found with the easiest techniques that work, or `None` if the
+    /// puzzle is solved or a number filled in is wrong.
+    fn hint(&self, py: Python<'_>) -> Option<Hint> {
+        if !conflicts(&self.puzzle).is_empty() {
+            return None;
+        }
+        let puzzle = self.puzzle.clone();
+        py.detach(move || puzzle.hint()).map(|step| Hint {
+            index: step.placements[0].0,
+            value: step.placements[0].1,
+            technique: step.technique.name().to_string(),
+            eliminations: step.eliminations.len(),
+        })
+    }
+
+    /// Rates the puzzle by the techniques needed to solve it from the numbers filled in so far.
+    fn rate(&self, py: Python<'_>) -> Rating {
+        let puzzle = self.puzzle.clone();
+        py.detach(move || Rating::new(&puzzle))
+    }
+
+    /// A short code for sharing the puzzle, optionally with the numbers filled in and the pencil
+    /// marks.
+    #[pyo3(signature = (progress = false, pencil_marks = false))]
+    fn share_code(&self, progress: bool, pencil_marks: bool) -> String {
+        self.puzzle.share_code(progress, pencil_marks)
+    }
+
+    /// The puzzle in the SadMan Software `.sdk` format.
+    fn to_sdk(&self) -> String {
+        write_sdk(&self.puzzle)
+    }
+
+    /// The puzzle as a grid of pencil marks, as used by HoDoKu and SudokuWiki.
+    fn to_pencil_grid(&self) -> String {
+        write_pencil_grid(&self.puzzle)
+    }
+
+    /// The puzzle drawn as a grid with box lines.
+    fn grid(&self) -> String {
+        self.puzzle.to_string()
+    }
+
+    fn copy(&self) -> Self {
+        self.clone()
+    }
+
+    /// The cells as 81 characters, with `.` for an empty cell.
+    fn __str__(&self) -> String {
+        let values: Vec<u8> = self.puzzle.cells.iter().map(|c| c.num()).collect();
+        values.as_string().unwrap_or_default()
+    }
+
+    fn __repr__(&self) -> String {
+        format!("Puzzle('{}')", self.__str__())
+    }
+}
+
+/// The next number to fill in.
+#[pyclass(module = "sudoku", frozen, get_all, skip_from_py_object)]
+#[derive(Clone)]
+struct Hint {
+    /// The cell to fill in.
+    index: usize,
+    /// The number that goes in the cell.
+    value: u8,
+    /// The hardest technique needed to find the number, such as "naked single".
+    technique: String,
+    /// How many pencil marks were removed on the way to finding the number.
+    eliminations: usize,
+}
+
+#[pymethods]
+impl Hint {
+    fn __repr__(&self) -> String {
+        format!(
+            "Hint(index={}, value={}, technique='{}', eliminations={})",
+            self.index, self.value, self.technique, self.eliminations
+        )
+    }
+}
+
+/// How hard a puzzle is to solve.
+#[pyclass(module = "sudoku", frozen, get_all, skip_from_py_object)]
+#[derive(Clone)]
+struct Rating {
+    /// The hardest technique needed, or `None` if the puzzle was already solved.
+    hardest: Option<String>,
+    /// The number of solutions, counting no more than 2.
+    solutions: usize,
+    /// True if the human techniques could not finish the puzzle.
+    brute_force: bool,
+    /// The number of times each human technique was used, by name.
+    counts: HashMap<String, usize>,
+}
+
+impl Rating {
+    fn new(puz: &Puzzle) -> Rating {
+        let rating = puz.rate();
+        Rating {
+            hardest: rating.hardest().map(|t| t.name().to_string()),
+            solutions: rating.solutions,
+            brute_force: rating.brute_force,
+            counts: Technique::HUMAN
+                .iter()
+                .map(|t| (t.name().to_string(), rating.count(*t)))
+                .collect(),
+        }
+    }
+}
+
+#[pymethods]
+impl Rating {
+    /// The rating as a flat dictionary, with a `<technique>` key for each count, for building a
+    /// table such as a pandas `DataFrame`.
+    fn to_dict(&self, py: Python<'_>) -> PyResult<HashMap<String, Py<PyAny>>> {
+        let mut out = HashMap::new();
+        out.insert(
+            "hardest".to_string(),
+            self.hardest.clone().into_pyobject(py)?.unbind(),
+        );
+        out.insert(
+            "solutions".to_string(),
+            self.solutions.into_pyobject(py)?.into_any().unbind(),
+        );
+        out.insert(
+            "brute_force".to_string(),
+            self.brute_force
+                .into_pyobject(py)?
+                .to_owned()
+                .into_any()
+                .unbind(),
+        );
+        for (name, count) in self.counts.iter() {
+            out.insert(
+                name.replace(' ', "_"),
+                count.into_pyobject(py)?.into_any().unbind(),
+            );
+        }
+        Ok(out)
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "Rating(hardest={}, solutions={}, brute_force={})",
+            self.hardest
+                .as_ref()
+                .map(|h| format!("'{}'", h))
+                .unwrap_or_else(|| "None".to_string()),
+            self.solutions,
+            if self.brute_force { "True" } else { "False" }
+        )
+    }
+}
+
+fn solve(mut puz: Puzzle, max: usize) -> Vec<Vec<usize>> {
+    if !conflicts(&puz).is_empty() {
+        return Vec::new();
+    }
+    puz.brute_force_solve_max(max)
+        .iter()
+        .map(|solution| ints(solution))
+        .collect()
+}
+
+/// A puzzle given to a batch function, as a `Puzzle` or as text.
+#[derive(FromPyObject)]
+enum PuzzleArg {
+    Puzzle(PyPuzzle),
+    Text(String),
+}
+
+impl PuzzleArg {
+    fn into_puzzle(self) -> Option<Puzzle> {
+        match self {
+            PuzzleArg::Puzzle(puz) => Some(puz.puzzle),
+            PuzzleArg::Text(text) => text.parse().ok(),
+        }
+    }
+}
+
+/// Solves each puzzle, stopping after `max` solutions of each.  Puzzles can be `Puzzle`s or text,
+/// and text that is not a puzzle gives `None`.  The GIL is released while solving.
+#[pyfunction]
+#[pyo3(signature = (puzzles, max = 1))]
+fn solve_many(py: Python<'_>, puzzles: Vec<PuzzleArg>, max: usize) -> Vec<Option<Vec<Vec<usize>>>> {
+    let puzzles: Vec<Option<Puzzle>> = puzzles.into_iter().map(PuzzleArg::into_puzzle).collect();
+    py.detach(move || {
+        puzzles
+            .into_iter()
+            .map(|puz| puz.map(|puz| solve(puz, max)))
+            .collect()
+    })
+}
+
+/// Rates each puzzle.  Puzzles can be `Puzzle`s or text, and text that is not a puzzle gives
+/// `None`.  The GIL is released while rating.
+#[pyfunction]
+fn rate_many(py: Python<'_>, puzzles: Vec<PuzzleArg>) -> Vec<Option<Rating>> {
+    let puzzles: Vec<Option<Puzzle>> = puzzles.into_iter().map(PuzzleArg::into_puzzle).collect();
+    py.detach(move || {
+        puzzles
+            .iter()
+            .map(|puz| puz.as_ref().map(Rating::new))
+            .collect()
+    })
+}
+
+/// Reads puzzles in the SadMan Software `.sdm` format, one per line.
+#[pyfunction(name = "read_sdm")]
+fn py_read_sdm(text: &str) -> PyResult<Vec<PyPuzzle>> {
+    read_sdm(text)
+        .map(|puzzles| {
+            puzzles
+                .into_iter()
+                .map(|puzzle| PyPuzzle { puzzle })
+                .collect()
+        })
+        .map_err(value_error)
+}
+
+/// Writes puzzles in the SadMan Software `.sdm` format.
+#[pyfunction(name = "write_sdm")]
+fn py_write_sdm(puzzles: Vec<PyPuzzle>) -> String {
+    let puzzles: Vec<Puzzle> = puzzles.into_iter().map(|p| p.puzzle).collect();
+    write_sdm(&puzzles)
+}
+
+/// Reads a puzzle in the SadMan Software `.sdk` format.
+#[pyfunction(name = "read_sdk")]
+fn py_read_sdk(text: &str) -> PyResult<PyPuzzle> {
+    read_sdk(text)
+        .map(|puzzle| PyPuzzle { puzzle })
+        .map_err(value_error)
+}
+
+/// Reads a grid of pencil marks, as written by HoDoKu and SudokuWiki.
+#[pyfunction(name = "read_pencil_grid")]
+fn py_read_pencil_grid(text: &str) -> PyResult<PyPuzzle> {
+    read_pencil_grid(text)
+        .map(|puzzle| PyPuzzle { puzzle })
+        .map_err(value_error)
+}
+
+#[pymodule]
+#[pyo3(name = "sudoku")]
+fn sudoku_module(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<PyPuzzle>()?;
+    m.add_class::<Hint>()?;
+    m.add_class::<Rating>()?;
+    m.add(
+        "TECHNIQUES",
+        Technique::ALL.iter().map(|t| t.name()).collect::<Vec<_>>(),
+    )?;
+    m.add_function(wrap_pyfunction!(solve_many, m)?)?;
+    m.add_function(wrap_pyfunction!(rate_many, m)?)?;
+    m.add_function(wrap_pyfunction!(py_read_sdm, m)?)?;
+    m.add_function(wrap_pyfunction!(py_write_sdm, m)?)?;
+    m.add_function(wrap_pyfunction!(py_read_sdk, m)?)?;
+    m.add_function(wrap_pyfunction!(py_read_pencil_grid, m)?)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod python_tests {
+    use super::*;
+    use pyo3::types::PyDict;
+    use std::ffi::CString;
+
+    const EXAMPLE: &str =
+        "53..7....6..195....98....6.8...6...34..8.3..17...2...6.6....28....419..5....8..79";
+
+    #[test]
+    fn module_test() {
+        Python::attach(|py| {
+            let m = pyo3::wrap_pymodule!(sudoku_module)(py);
+            let locals = PyDict::new(py);
+            locals.set_item("sudoku", m).unwrap();
+            locals.set_item("EXAMPLE", EXAMPLE).unwrap();
+            let code = CString::new(
+                r#"
+puz = sudoku.Puzzle(EXAMPLE)
+assert str(puz) == EXAMPLE
+assert puz.clues == 30
+assert puz.candidates(2) == [1, 2, 4]
+assert len(puz.solve()) == 1
+assert puz.hint().technique == "naked single"
+assert puz.rate().solutions == 1
+results = sudoku.rate_many([EXAMPLE, puz, "not a puzzle"])
+assert results[0].to_dict() == results[1].to_dict()
+assert results[2] is None
+assert sudoku.solve_many([EXAMPLE], 2)[0] == puz.solve()
+"#,
+            )
+            .unwrap();
+            py.run(&code, None, Some(&locals)).unwrap();
+        });
+    }
+}
@@ -0,0 +1,75 @@
+//! Builds the extension module and runs `tests/test_sudoku.py` with it, using the Python that
+//! pyo3 builds against.  `.cargo/config.toml` sets `PYO3_PYTHON` to a virtual environment made
+//! from the version pinned in `python/.python-version`, and only the standard library is needed.
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+use std::process::Command;
+
+/// The directory the library was built in, which is the parent of this test's `deps` directory.
+fn target_dir() -> PathBuf {
+    let mut dir = env::current_exe().unwrap();
+    dir.pop();
+    if dir.ends_with("deps") {
+        dir.pop();
+    }
+    dir
+}
+
+#[test]
+fn python_test() {
+    let crate_dir = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    let target = target_dir();
+
+    // `cargo test` only builds the library as an rlib
+    let mut cargo = Command::new(env!("CARGO"));
+    cargo.args(["build", "--lib", "--manifest-path"]);
+    cargo.arg(crate_dir.join("Cargo.toml"));
+    if !cfg!(debug_assertions) {
+        cargo.arg("--release");
+    }
+    assert!(
+        cargo.status().unwrap().success(),
+        "Could not build the module"
+    );
+
+    // Python only imports the module under its own name
+    let module_dir = target.join("python");
+    fs::create_dir_all(&module_dir).unwrap();
+    let library = if cfg!(target_os = "macos") {
+        "libsudoku_python.dylib"
+    } else {
+        "libsudoku_python.so"
+    };
+    fs::copy(target.join(library), module_dir.join("sudoku.so")).unwrap();
+
+    let python = env::var("PYO3_PYTHON").expect("PYO3_PYTHON is set in .cargo/config.toml");
+    let pinned = fs::read_to_string(crate_dir.join(".python-version")).unwrap();
+    let version = Command::new(&python)
+        .arg("--version")
+        .output()
+        .unwrap_or_else(|e| {
+            panic!(
+                "Could not run {}, create it as the README says: {}",
+                python, e
+            )
+        });
+    assert_eq!(
+        String::from_utf8_lossy(&version.stdout).trim(),
+        format!("Python {}", pinned.trim()),
+        "{} is not the pinned version",
+        python
+    );
+
+    let output = Command::new(&python)
+        .args(["-m", "unittest", "discover", "-s"])
+        .arg(crate_dir.join("tests"))
+        .env("PYTHONPATH", &module_dir)
+        .output()
+        .unwrap_or_else(|e| panic!("Could not run {}: {}", python, e));
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+}
@@ -0,0 +1,131 @@
+"""Tests for the sudoku extension module.  Run by `cargo test -p sudoku-python`, which builds the
+module and puts it on the path, or with `python -m unittest` after `maturin develop`."""
+import threading
+import time
+import unittest
+
+import sudoku
+
+EXAMPLE = "53..7....6..195....98....6.8...6...34..8.3..17...2...6.6....28....419..5....8..79"
+SOLUTION = "534678912672195348198342567859761423426853791713924856961537284287419635345286179"
+# Needs brute force after the human techniques
+HARD = "..53.....8......2..7..1.5..4....53...1..7...6..32...8..6.5....9..4....3......97.."
+
+
+class PuzzleTest(unittest.TestCase):
+    def test_parse(self):
+        puz = sudoku.Puzzle(EXAMPLE)
+        self.assertEqual(str(puz), EXAMPLE)
+        self.assertEqual(repr(puz), "Puzzle('%s')" % EXAMPLE)
+        self.assertEqual(puz.values[:3], [5, 3, 0])
+        self.assertEqual(puz.clues, 30)
+        self.assertEqual(sudoku.Puzzle.from_values(puz.values), puz)
+        self.assertEqual(sudoku.Puzzle.from_share_code(puz.share_code()), puz)
+        with self.assertRaisesRegex(ValueError, "conflicting givens"):
+            sudoku.Puzzle("55" + EXAMPLE[2:])
+        with self.assertRaises(ValueError):
+            sudoku.Puzzle.from_values([10] * 81)
+
+    def test_set_value(self):
+        puz = sudoku.Puzzle(EXAMPLE)
+        self.assertEqual(puz.candidates(2), [1, 2, 4])
+        copy = puz.copy()
+        puz.set_value(2, 4)
+        self.assertEqual(puz.values[2], 4)
+        self.assertEqual(puz.givens[2], 0)
+        self.assertNotEqual(puz, copy)
+        self.assertNotIn(4, puz.candidates(3))
+        puz.set_value(3, 5)
+        self.assertEqual(puz.conflicts(), [0, 3, 14])
+        with self.assertRaisesRegex(ValueError, "given"):
+            puz.set_value(0, 1)
+        with self.assertRaises(IndexError):
+            puz.candidates(81)
+
+    def test_solve(self):
+        puz = sudoku.Puzzle(EXAMPLE)
+        self.assertEqual(puz.solve(), [[int(c) for c in SOLUTION]])
+        self.assertEqual(len(sudoku.Puzzle("." * 81).solve(3)), 3)
+        # A wrong number that does not conflict yet
+        puz.set_value(2, 1)
+        self.assertEqual(puz.solve(), [])
+        self.assertIsNone(puz.hint())
+
+    def test_hint(self):
+        puz = sudoku.Puzzle(HARD)
+        techniques = set()
+        hint = puz.hint()
+        while hint is not None:
+            techniques.add(hint.technique)
+            puz.set_value(hint.index, hint.value)
+            hint = puz.hint()
+        self.assertTrue(puz.is_solved())
+        self.assertIn("brute force", techniques)
+        self.assertLessEqual(techniques, set(sudoku.TECHNIQUES))
+
+    def test_rate(self):
+        rating = sudoku.Puzzle(EXAMPLE).rate()
+        self.assertEqual(rating.solutions, 1)
+        self.assertFalse(rating.brute_force)
+        self.assertIn(rating.hardest, sudoku.TECHNIQUES)
+        self.assertGreater(rating.counts["naked single"], 0)
+        row = rating.to_dict()
+        self.assertEqual(row["solutions"], 1)
+        self.assertEqual(row["naked_single"], rating.counts["naked single"])
+        self.assertTrue(sudoku.Puzzle(HARD).rate().brute_force)
+
+
+class ParserTest(unittest.TestCase):
+    def test_sdm(self):
+        text = "#AJane Doe\n%s\n\n%s\n" % (EXAMPLE, HARD)
+        puzzles = sudoku.read_sdm(text)
+        self.assertEqual([str(p) for p in puzzles], [EXAMPLE, HARD])
+        self.assertEqual(puzzles[0].metadata, {"author": "Jane Doe"})
+        self.assertEqual(sudoku.read_sdm(sudoku.write_sdm(puzzles)), puzzles)
+        with self.assertRaisesRegex(ValueError, "line 2"):
+            sudoku.read_sdm(EXAMPLE + "\n53x\n")
+
+    def test_sdk(self):
+        puz = sudoku.Puzzle(EXAMPLE)
+        self.assertEqual(sudoku.read_sdk(puz.to_sdk()), puz)
+
+    def test_pencil_grid(self):
+        puz = sudoku.Puzzle(EXAMPLE)
+        self.assertEqual(sudoku.read_pencil_grid(puz.to_pencil_grid()).candidates(2), [1, 2, 4])
+
+
+class BatchTest(unittest.TestCase):
+    def test_batch(self):
+        puzzles = [EXAMPLE, sudoku.Puzzle(HARD), "not a puzzle"]
+        solutions = sudoku.solve_many(puzzles, 2)
+        self.assertEqual(solutions[0], sudoku.Puzzle(EXAMPLE).solve())
+        self.assertEqual(len(solutions[1]), 1)
+        self.assertIsNone(solutions[2])
+        ratings = sudoku.rate_many(puzzles)
+        self.assertEqual(ratings[0].hardest, sudoku.Puzzle(EXAMPLE).rate().hardest)
+        self.assertTrue(ratings[1].brute_force)
+        self.assertIsNone(ratings[2])
+
+    def test_releases_gil(self):
+        # While another thread rates puzzles, this thread keeps running Python.  If the GIL were
+        # held, nothing could run here between the start and end of the call.
+        window = {}
+
+        def work():
+            window["start"] = time.perf_counter()
+            sudoku.rate_many([HARD] * 2)
+            window["end"] = time.perf_counter()
+
+        worker = threading.Thread(target=work)
+        ticks = []
+        worker.start()
+        while worker.is_alive():
+            ticks.append(time.perf_counter())
+        worker.join()
+        middle = (window["start"] + window["end"]) / 2
+        quarter = (window["end"] - window["start"]) / 4
+        self.assertTrue(any(abs(t - middle) < quarter for t in ticks))
+
+
+if __name__ == "__main__":
+    unittest.main()
@@ -0,0 +1,154 @@
+//! A small HTTP service that solves, validates, hints, rates and generates puzzles, with JSON
+//! requests and responses.  It is a separate crate so that the library does not depend on an HTTP
+//! server.
+mod service;
+
+use crate::service::{Limits, Reply, Service};
+use std::io::{self, Read, Write};
+use std::process;
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+use tiny_http::{Header, Request, Response, Server};
+
+const USAGE: &str = "Usage: sudoku-server [OPTIONS]
+
+Serves JSON requests to solve, validate, hint, rate and generate puzzles.
+
+Endpoints:
+  GET  /health    {status, version, jobs}
+  POST /solve     {puzzle, max_solutions?}
+  POST /validate  {puzzle}
+  POST /hint      {puzzle}
+  POST /rate      {puzzle}
+  POST /generate  {seed?, count?, min_clues?, max_clues?, symmetry?, difficulty?, attempts?}
+
+Options:
+      --addr ADDR             Address to listen on (default 127.0.0.1:8080)
+      --threads N             Requests handled at once (default 4)
+      --max-solutions N       Most solutions a solve request may ask for (default 100)
+      --max-count N           Most puzzles a generate request may ask for (default 10)
+      --max-attempts N        Most attempts a generate request may allow (default 1000)
+      --max-body BYTES        Largest request body (default 65536)
+      --timeout SECONDS       Time a request may run (default 10)
+  -h, --help                  Print this message";
+
+/// Reads at most `limit` bytes of the body.  Returns `None` if there is more.
+fn read_body(request: &mut Request, limit: usize) -> io::Result<Option<String>> {
+    if request.body_length().is_some_and(|len| len > limit) {
+        return Ok(None);
+    }
+    let mut body = Vec::new();
+    request
+        .as_reader()
+        .take(limit as u64 + 1)
+        .read_to_end(&mut body)?;
+    if body.len() > limit {
+        return Ok(None);
+    }
+    Ok(Some(String::from_utf8_lossy(&body).into_owned()))
+}
+
+fn respond(service: &Service, mut request: Request) {
+    let reply = match read_body(&mut request, service.limits().max_body) {
+        Ok(Some(body)) => service.handle(request.method().as_str(), request.url(), &body),
+        Ok(None) => Reply::error(
+            413,
+            &format!(
+                "The body is larger than {} bytes",
+                service.limits().max_body
+            ),
+        ),
+        Err(e) => Reply::error(400, &e.to_string()),
+    };
+    let header = Header::from_bytes("Content-Type", "application/json").expect("Valid header");
+    let response = Response::from_string(reply.body.to_string())
+        .with_status_code(reply.status)
+        .with_header(header);
+    // The client may have gone away
+    let _ = request.respond(response);
+}
+
+/// Reads the value of an option.
+fn value<T: std::str::FromStr>(name: &str, arg: Option<String>) -> T {
+    match arg.map(|a| a.parse()) {
+        Some(Ok(value)) => value,
+        _ => {
+            eprintln!("{} needs a valid value\n\n{}", name, USAGE);
+            process::exit(1);
+        }
+    }
+}
+
+fn main() {
+    let mut addr = "127.0.0.1:8080".to_string();
+    let mut threads = 4;
+    let mut limits = Limits::default();
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-h" | "--help" => {
+                println!("{}", USAGE);
+                return;
+            }
+            "--addr" => addr = value(&arg, args.next()),
+            "--threads" => threads = value::<usize>(&arg, args.next()).max(1),
+            "--max-solutions" => limits.max_solutions = value(&arg, args.next()),
+            "--max-count" => limits.max_count = value(&arg, args.next()),
+            "--max-attempts" => limits.max_attempts = value(&arg, args.next()),
+            "--max-body" => limits.max_body = value(&arg, args.next()),
+            "--timeout" => {
+                let seconds: f64 = value(&arg, args.next());
+                limits.timeout = match Duration::try_from_secs_f64(seconds) {
+                    Ok(timeout)
+                        if !timeout.is_zero() && Instant::now().checked_add(timeout).is_some() =>
+                    {
+                        timeout
+                    }
+                    _ => {
+                        eprintln!(
+                            "--timeout must be more than 0 and not too long\n\n{}",
+                            USAGE
+                        );
+                        process::exit(1);
+                    }
+                };
+            }
+            _ => {
+                eprintln!("Unexpected argument '{}'\n\n{}", arg, USAGE);
+                process::exit(1);
+            }
+        }
+    }
+    // Each thread works on one request at a time, and stops working on it once it times out
+    limits.max_jobs = threads;
+
+    let server = match Server::http(&addr) {
+        Ok(server) => Arc::new(server),
+        Err(e) => {
+            eprintln!("Could not listen on {}: {}", addr, e);
+            process::exit(2);
+        }
+    };
+    match server.server_addr().to_ip() {
+        Some(local) => println!("Listening on http://{}", local),
+        None => println!("Listening on {}", addr),
+    }
+    let _ = io::stdout().flush();
+
+    let service = Arc::new(Service::new(limits));
+    let workers: Vec<_> = (0..threads)
+        .map(|_| {
+            let server = Arc::clone(&server);
+            let service = Arc::clone(&service);
+            thread::spawn(move || {
+                while let Ok(request) = server.recv() {
+                    respond(&service, request);
+                }
+            })
+        })
+        .collect();
+    for worker in workers {
+        let _ = worker.join();
+    }
+}
@@ -0,0 +1,524 @@
+//! The endpoints of the service.  They are kept apart from the HTTP server, so that they can be
+//! tested without a network.
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use serde_json::{json, Map, Value};
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use sudoku::{
+    Alphabet, Generator, ParseMode, PuzInput, PuzOutput, Puzzle, SudError, Symmetry, Technique,
+};
+
+/// The number of cells in a puzzle.
+const CELLS: usize = 81;
+
+/// Limits on what a request may ask for, so that one request cannot take over the service.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Limits {
+    /// The most solutions a solve request may ask for.
+    pub max_solutions: usize,
+    /// The most puzzles a generate request may ask for.
+    pub max_count: usize,
+    /// The most attempts a generate request may allow for each puzzle.
+    pub max_attempts: usize,
+    /// How long a request may run before the service gives up on it.
+    pub timeout: Duration,
+    /// The largest request body, in bytes.
+    pub max_body: usize,
+    /// How many requests may be worked on at once.  A request stops being worked on once it
+    /// has timed out.
+    pub max_jobs: usize,
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        Limits {
+            max_solutions: 100,
+            max_count: 10,
+            max_attempts: 1000,
+            timeout: Duration::from_secs(10),
+            max_body: 64 * 1024,
+            max_jobs: 8,
+        }
+    }
+}
+
+/// A response: the HTTP status and the JSON body.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Reply {
+    pub status: u16,
+    pub body: Value,
+}
+
+impl Reply {
+    fn ok(body: Value) -> Reply {
+        Reply { status: 200, body }
+    }
+
+    pub fn error(status: u16, message: &str) -> Reply {
+        Reply {
+            status,
+            body: json!({ "error": message }),
+        }
+    }
+}
+
+/// Counts a job as running until it is dropped, even if the job panics.
+struct Job(Arc<AtomicUsize>);
+
+impl Drop for Job {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct PuzzleRequest {
+    puzzle: String,
+}
+
+fn one() -> usize {
+    1
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct SolveRequest {
+    puzzle: String,
+    #[serde(default = "one")]
+    max_solutions: usize,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct GenerateRequest {
+    seed: Option<u64>,
+    #[serde(default = "one")]
+    count: usize,
+    min_clues: Option<usize>,
+    max_clues: Option<usize>,
+    symmetry: Option<String>,
+    difficulty: Option<String>,
+    attempts: Option<usize>,
+}
+
+fn parse<T: DeserializeOwned>(body: &str) -> Result<T, Reply> {
+    serde_json::from_str(body).map_err(|e| Reply::error(400, &format!("Invalid request: {}", e)))
+}
+
+fn puzzle(text: &str) -> Result<Puzzle, Reply> {
+    text.parse()
+        .map_err(|e: SudError| Reply::error(400, &e.to_string()))
+}
+
+/// The cells as 81 characters, with `.` for an empty cell.
+fn line(values: &[u8]) -> String {
+    values.to_vec().as_string().unwrap_or_default()
+}
+
+/// Cells whose numbers are repeated in their row, column, or box.
+fn conflicts(puz: &Puzzle) -> Vec<usize> {
+    (0..CELLS)
+        .filter(|i| puz.cells[*i].num() != 0 && !puz.valid_entry(*i))
+        .collect()
+}
+
+/// The key for a technique in JSON, such as `naked_single`.
+fn key(technique: Technique) -> String {
+    technique.name().replace(' ', "_")
+}
+
+pub struct Service {
+    limits: Limits,
+    jobs: Arc<AtomicUsize>,
+}
+
+impl Service {
+    pub fn new(limits: Limits) -> Service {
+        Service {
+            limits,
+            jobs: Arc::new(AtomicUsize::new(0)),
+        }
+    }
+
+    pub fn limits(&self) -> &Limits {
+        &self.limits
+    }
+
+    /// Answers a request.  `path` may include a query string, which is ignored.
+    pub fn handle(&self, method: &str, path: &str, body: &str) -> Reply {
+        let path = path.split('?').next().unwrap_or_default();
+        let result = match (method, path) {
+            ("GET", "/health") => Ok(self.health()),
+            ("POST", "/solve") => self.solve(body),
+            ("POST", "/validate") => self.validate(body),
+            ("POST", "/hint") => self.hint(body),
+            ("POST", "/rate") => self.rate(body),
+            ("POST", "/generate") => self.generate(body),
+            (_, "/health") => Err(Reply::error(405, "Use GET for /health")),
+            (_, "/solve") | (_, "/validate") | (_, "/hint") | (_, "/rate") | (_, "/generate") => {
+                Err(Reply::error(405, &format!("Use POST for {}", path)))
+            }
+            _ => Err(Reply::error(404, &format!("There is no {}", path))),
+        };
+        result.unwrap_or_else(|reply| reply)
+    }
+
+    /// Runs `f` with the time at which it must give up, which is the timeout from now.  `f`
+    /// returns `SudError::TimedOut` once that time has passed, and any other error is the
+    /// puzzle's fault.
+    fn run<T, F>(&self, f: F) -> Result<T, Reply>
+    where
+        F: FnOnce(Instant) -> Result<T, SudError>,
+    {
+        if self.jobs.fetch_add(1, Ordering::SeqCst) >= self.limits.max_jobs {
+            self.jobs.fetch_sub(1, Ordering::SeqCst);
+            return Err(Reply::error(503, "The service is busy"));
+        }
+        let _job = Job(Arc::clone(&self.jobs));
+        let deadline = Instant::now()
+            .checked_add(self.limits.timeout)
+            .ok_or_else(|| Reply::error(500, "The timeout is too long"))?;
+        match panic::catch_unwind(AssertUnwindSafe(|| f(deadline))) {
+            Ok(Ok(value)) => Ok(value),
+            Ok(Err(SudError::TimedOut)) => Err(Reply::error(
+                503,
+                &format!(
+                    "Timed out after {} seconds",
+                    self.limits.timeout.as_secs_f64()
+                ),
+            )),
+            Ok(Err(e)) => Err(Reply::error(422, &e.to_string())),
+            Err(_) => Err(Reply::error(500, "Internal error")),
+        }
+    }
+
+    fn health(&self) -> Reply {
+        Reply::ok(json!({
+            "status": "ok",
+            "version": env!("CARGO_PKG_VERSION"),
+            "jobs": self.jobs.load(Ordering::SeqCst),
+        }))
+    }
+
+    fn solve(&self, body: &str) -> Result<Reply, Reply> {
+        let request: SolveRequest = parse(body)?;
+        let max = request.max_solutions;
+        if max == 0 || max > self.limits.max_solutions {
+            return Err(Reply::error(
+                400,
+                &format!(
+                    "max_solutions must be from 1 to {}",
+                    self.limits.max_solutions
+                ),
+            ));
+        }
+        let mut puz = puzzle(&request.puzzle)?;
+        let solutions = self.run(|deadline| puz.brute_force_solve_until(max, deadline))?;
+        Ok(Reply::ok(json!({
+            "count": solutions.len(),
+            "solutions": solutions.iter().map(|s| line(s)).collect::<Vec<String>>(),
+        })))
+    }
+
+    /// Unlike the other endpoints, a puzzle that cannot be read is not an error, but a puzzle that
+    /// is not valid.  A valid puzzle has no conflicting givens and exactly one solution.
+    fn validate(&self, body: &str) -> Result<Reply, Reply> {
+        let request: PuzzleRequest = parse(body)?;
+        let input = match Alphabet::default().parse(&request.puzzle, ParseMode::Strict) {
+            Ok(input) => input,
+            // Read the givens again to find every conflict, not just the first
+            Err(SudError::ConflictingGivens { .. }) => {
+                request.puzzle.as_str().as_input().unwrap_or_default()
+            }
+            Err(e) => {
+                return Ok(Reply::ok(json!({
+                    "valid": false,
+                    "error": e.to_string(),
+                    "conflicts": [],
+                    "solutions": 0,
+                })))
+            }
+        };
+        let mut puz = Puzzle::new();
+        puz.set_initial(input);
+        let conflicts = conflicts(&puz);
+        let solutions = if conflicts.is_empty() {
+            self.run(|deadline| puz.brute_force_solve_until(2, deadline))?
+                .len()
+        } else {
+            0
+        };
+        Ok(Reply::ok(json!({
+            "valid": solutions == 1,
+            "error": null,
+            "conflicts": conflicts,
+            "solutions": solutions,
+        })))
+    }
+
+    fn hint(&self, body: &str) -> Result<Reply, Reply> {
+        let request: PuzzleRequest = parse(body)?;
+        let puz = puzzle(&request.puzzle)?;
+        if puz.is_solved() {
+            return Ok(Reply::ok(json!({ "solved": true, "hint": null })));
+        }
+        let step = self
+            .run(|deadline| puz.hint_until(deadline))?
+            .ok_or_else(|| Reply::error(422, "The puzzle cannot be solved"))?;
+        let (index, value) = step.placements[0];
+        Ok(Reply::ok(json!({
+            "solved": false,
+            "hint": {
+                "index": index,
+                "cell": format!("r{}c{}", index / 9 + 1, index % 9 + 1),
+                "value": value,
+                "technique": step.technique.name(),
+                "eliminations": step.eliminations.len(),
+            },
+        })))
+    }
+
+    fn rate(&self, body: &str) -> Result<Reply, Reply> {
+        let request: PuzzleRequest = parse(body)?;
+        let puz = puzzle(&request.puzzle)?;
+        let rating = self.run(|deadline| puz.rate_until(deadline))?;
+        let techniques: Map<String, Value> = Technique::HUMAN
+            .iter()
+            .map(|t| (key(*t), json!(rating.count(*t))))
+            .collect();
+        Ok(Reply::ok(json!({
+            "hardest": rating.hardest().map(|t| t.name()),
+            "techniques": techniques,
+            "brute_force": rating.brute_force,
+            "solutions": rating.solutions,
+        })))
+    }
+
+    fn generate(&self, body: &str) -> Result<Reply, Reply> {
+        let request: GenerateRequest = parse(body)?;
+        if request.count == 0 || request.count > self.limits.max_count {
+            return Err(Reply::error(
+                400,
+                &format!("count must be from 1 to {}", self.limits.max_count),
+            ));
+        }
+        let seed = request.seed.unwrap_or_else(|| {
+            SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_nanos() as u64)
+                .unwrap_or_default()
+        });
+        let mut generator = Generator::new(seed);
+        // When only one end of the range is given, the other is moved to include it
+        let (default_min, default_max) = Generator::DEFAULT_CLUES;
+        let min = request
+            .min_clues
+            .unwrap_or_else(|| default_min.min(request.max_clues.unwrap_or(CELLS)));
+        let max = request.max_clues.unwrap_or_else(|| default_max.max(min));
+        if min > max || max > CELLS {
+            return Err(Reply::error(
+                400,
+                &format!("Clues {}-{} must be within 0-{}", min, max, CELLS),
+            ));
+        }
+        generator.clues(min, max);
+        if let Some(name) = request.symmetry {
+            let symmetry: Symmetry = name
+                .parse()
+                .map_err(|e: SudError| Reply::error(400, &e.to_string()))?;
+            generator.symmetry(symmetry);
+        }
+        if let Some(name) = request.difficulty {
+            let technique: Technique = name
+                .parse()
+                .map_err(|e: SudError| Reply::error(400, &e.to_string()))?;
+            generator.difficulty(technique);
+        }
+        if let Some(attempts) = request.attempts {
+            if attempts == 0 || attempts > self.limits.max_attempts {
+                return Err(Reply::error(
+                    400,
+                    &format!("attempts must be from 1 to {}", self.limits.max_attempts),
+                ));
+            }
+            generator.attempts(attempts);
+        }
+        let count = request.count;
+        let puzzles = self.run(|deadline| {
+            generator.deadline(deadline);
+            (0..count)
+                .map(|_| generator.generate())
+                .collect::<Result<Vec<Puzzle>, SudError>>()
+        })?;
+        Ok(Reply::ok(json!({
+            "seed": seed,
+            "puzzles": puzzles
+                .iter()
+                .map(|p| line(&p.cells.iter().map(|c| c.num()).collect::<Vec<u8>>()))
+                .collect::<Vec<String>>(),
+        })))
+    }
+}
+
+#[cfg(test)]
+mod service_tests {
+    use super::*;
+
+    const EXAMPLE: &str =
+        "53..7....6..195....98....6.8...6...34..8.3..17...2...6.6....28....419..5....8..79";
+    const SOLUTION: &str =
+        "534678912672195348198342567859761423426853791713924856961537284287419635345286179";
+
+    fn post(service: &Service, path: &str, body: Value) -> Reply {
+        service.handle("POST", path, &body.to_string())
+    }
+
+    #[test]
+    fn solve_test() {
+        let service = Service::new(Limits::default());
+        let reply = post(&service, "/solve", json!({ "puzzle": EXAMPLE }));
+        assert_eq!(reply.status, 200);
+        assert_eq!(reply.body, json!({ "count": 1, "solutions": [SOLUTION] }));
+
+        let blank = ".".repeat(81);
+        let reply = post(
+            &service,
+            "/solve",
+            json!({ "puzzle": blank, "max_solutions": 3 }),
+        );
+        assert_eq!(reply.body["count"], 3);
+        let reply = post(
+            &service,
+            "/solve",
+            json!({ "puzzle": blank, "max_solutions": 101 }),
+        );
+        assert_eq!(reply.status, 400);
+        assert_eq!(
+            post(&service, "/solve", json!({ "puzzle": "53x" })).status,
+            400
+        );
+        assert_eq!(
+            post(&service, "/solve", json!({ "grid": EXAMPLE })).status,
+            400
+        );
+    }
+
+    #[test]
+    fn validate_test() {
+        let service = Service::new(Limits::default());
+        let reply = post(&service, "/validate", json!({ "puzzle": EXAMPLE }));
+        assert_eq!(
+            reply.body,
+            json!({ "valid": true, "error": null, "conflicts": [], "solutions": 1 })
+        );
+        let repeated = format!("55{}", &EXAMPLE[2..]);
+        let reply = post(&service, "/validate", json!({ "puzzle": repeated }));
+        assert_eq!(reply.body["conflicts"], json!([0, 1]));
+        assert_eq!(reply.body["valid"], false);
+        let reply = post(&service, "/validate", json!({ "puzzle": "53x" }));
+        assert_eq!(reply.status, 200);
+        assert!(reply.body["error"].is_string());
+    }
+
+    #[test]
+    fn hint_rate_test() {
+        let service = Service::new(Limits::default());
+        let reply = post(&service, "/hint", json!({ "puzzle": EXAMPLE }));
+        let hint = &reply.body["hint"];
+        assert_eq!(hint["technique"], "naked single");
+        let index = hint["index"].as_u64().unwrap() as usize;
+        assert_eq!(hint["value"], (SOLUTION.as_bytes()[index] - b'0') as u64);
+        let reply = post(&service, "/hint", json!({ "puzzle": SOLUTION }));
+        assert_eq!(reply.body, json!({ "solved": true, "hint": null }));
+        let wrong = format!("531{}", &EXAMPLE[3..]);
+        assert_eq!(
+            post(&service, "/hint", json!({ "puzzle": wrong })).status,
+            422
+        );
+
+        let reply = post(&service, "/rate", json!({ "puzzle": EXAMPLE }));
+        assert_eq!(reply.body["solutions"], 1);
+        assert_eq!(reply.body["brute_force"], false);
+        assert!(reply.body["techniques"]["naked_single"].as_u64().unwrap() > 0);
+    }
+
+    #[test]
+    fn generate_test() {
+        let service = Service::new(Limits::default());
+        let request = json!({ "seed": 7, "count": 2, "min_clues": 30, "max_clues": 34 });
+        let reply = post(&service, "/generate", request.clone());
+        assert_eq!(reply.status, 200);
+        assert_eq!(reply.body["seed"], 7);
+        assert_eq!(reply.body["puzzles"].as_array().unwrap().len(), 2);
+        assert_eq!(post(&service, "/generate", request).body, reply.body);
+
+        let reply = post(&service, "/generate", json!({ "symmetry": "spiral" }));
+        assert_eq!(reply.status, 400);
+        assert_eq!(
+            post(&service, "/generate", json!({ "count": 11 })).status,
+            400
+        );
+        assert_eq!(
+            post(&service, "/generate", json!({ "attempts": 0 })).status,
+            400
+        );
+        let reply = post(&service, "/generate", json!({ "attempts": 1001 }));
+        assert_eq!(
+            reply.body,
+            json!({ "error": "attempts must be from 1 to 1000" })
+        );
+    }
+
+    #[test]
+    fn limits_test() {
+        let service = Service::new(Limits {
+            timeout: Duration::from_nanos(1),
+            ..Limits::default()
+        });
+        let reply = post(&service, "/rate", json!({ "puzzle": EXAMPLE }));
+        assert_eq!(reply.status, 503);
+
+        let service = Service::new(Limits {
+            max_jobs: 0,
+            ..Limits::default()
+        });
+        let reply = post(&service, "/solve", json!({ "puzzle": EXAMPLE }));
+        assert_eq!(reply.body, json!({ "error": "The service is busy" }));
+
+        // A request that timed out no longer holds its job
+        let service = Service::new(Limits {
+            timeout: Duration::from_millis(50),
+            max_jobs: 1,
+            max_attempts: 1_000_000,
+            ..Limits::default()
+        });
+        let request = json!({
+            "attempts": 1_000_000,
+            "difficulty": "naked-single",
+            "min_clues": 17,
+            "max_clues": 17,
+        });
+        for _ in 0..2 {
+            let reply = post(&service, "/generate", request.clone());
+            assert_eq!(reply.status, 503);
+            assert_eq!(service.handle("GET", "/health", "").body["jobs"], 0);
+        }
+        let reply = post(&service, "/solve", json!({ "puzzle": EXAMPLE }));
+        assert_eq!(reply.status, 200);
+    }
+
+    #[test]
+    fn routes_test() {
+        let service = Service::new(Limits::default());
+        let reply = service.handle("GET", "/health?verbose", "");
+        assert_eq!(reply.status, 200);
+        assert_eq!(reply.body["status"], "ok");
+        assert_eq!(service.handle("GET", "/solve", "").status, 405);
+        assert_eq!(service.handle("POST", "/health", "").status, 405);
+        assert_eq!(service.handle("GET", "/", "").status, 404);
+    }
+}
@@ -0,0 +1,114 @@
+//! Runs the server on a free port and sends it requests.
+use serde_json::{json, Value};
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::TcpStream;
+use std::process::{Child, Command, Stdio};
+
+const EXAMPLE: &str =
+    "53..7....6..195....98....6.8...6...34..8.3..17...2...6.6....28....419..5....8..79";
+const SOLUTION: &str =
+    "534678912672195348198342567859761423426853791713924856961537284287419635345286179";
+
+/// Stops the server when the test ends, even if it fails.
+struct Server {
+    child: Child,
+    addr: String,
+}
+
+impl Server {
+    fn start(args: &[&str]) -> Server {
+        let mut child = Command::new(env!("CARGO_BIN_EXE_sudoku-server"))
+            .args(["--addr", "127.0.0.1:0"])
+            .args(args)
+            .stdout(Stdio::piped())
+            .spawn()
+            .expect("Could not start the server");
+        let mut line = String::new();
+        BufReader::new(child.stdout.take().unwrap())
+            .read_line(&mut line)
+            .unwrap();
+        let addr = line
+            .trim()
+            .trim_start_matches("Listening on http://")
+            .to_string();
+        Server { child, addr }
+    }
+
+    /// Sends a request and returns the status and the JSON body.
+    fn request(&self, method: &str, path: &str, body: &str) -> (u16, Value) {
+        let mut stream = TcpStream::connect(&self.addr).unwrap();
+        write!(
+            stream,
+            "{} {} HTTP/1.1\r\nHost: {}\r\nContent-Type: application/json\r\n\
+             Content-Length: {}\r\nConnection: close\r\n\r\n{}",
+            method,
+            path,
+            self.addr,
+            body.len(),
+            body
+        )
+        .unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        let (head, body) = response.split_at(response.find("\r\n\r\n").unwrap());
+        assert!(head
+            .to_ascii_lowercase()
+            .contains("content-type: application/json"));
+        let status = head.split(' ').nth(1).unwrap().parse().unwrap();
+        (status, serde_json::from_str(body.trim()).unwrap())
+    }
+
+    fn post(&self, path: &str, body: Value) -> (u16, Value) {
+        self.request("POST", path, &body.to_string())
+    }
+}
+
+impl Drop for Server {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+#[test]
+fn endpoints_test() {
+    let server = Server::start(&[]);
+    let (status, body) = server.request("GET", "/health", "");
+    assert_eq!(status, 200);
+    assert_eq!(body["status"], "ok");
+
+    let (status, body) = server.post("/solve", json!({ "puzzle": EXAMPLE }));
+    assert_eq!(status, 200);
+    assert_eq!(body["solutions"], json!([SOLUTION]));
+
+    let (_, body) = server.post("/validate", json!({ "puzzle": EXAMPLE }));
+    assert_eq!(body["valid"], true);
+
+    let (_, body) = server.post("/hint", json!({ "puzzle": EXAMPLE }));
+    assert_eq!(body["hint"]["technique"], "naked single");
+
+    let (_, body) = server.post("/rate", json!({ "puzzle": EXAMPLE }));
+    assert_eq!(body["hardest"], "naked single");
+
+    let (status, body) = server.post("/generate", json!({ "seed": 1, "min_clues": 30 }));
+    assert_eq!(status, 200);
+    assert_eq!(body["puzzles"].as_array().unwrap().len(), 1);
+
+    assert_eq!(server.request("POST", "/solve", "{").0, 400);
+    assert_eq!(server.request("GET", "/nothing", "").0, 404);
+}
+
+#[test]
+fn limits_test() {
+    let server = Server::start(&["--max-body", "64", "--max-solutions", "2"]);
+    let (status, _) = server.post("/solve", json!({ "puzzle": EXAMPLE }));
+    assert_eq!(status, 413);
+    let (status, body) = server.post("/solve", json!({ "puzzle": "", "max_solutions": 3 }));
+    assert_eq!(status, 400);
+    assert_eq!(body["error"], "max_solutions must be from 1 to 2");
+
+    let server = Server::start(&["--timeout", "0.000001"]);
+    let (status, body) = server.post("/rate", json!({ "puzzle": EXAMPLE }));
+    assert_eq!(status, 503);
+    assert!(body["error"].as_str().unwrap().starts_with("Timed out"));
+}
@@ -0,0 +1,242 @@
+//! The symbols used to read and write numbers, so that puzzles can be written with letters or
+//! other characters.  Only `Alphabet::parse`, `Alphabet::format` and `PuzOutput::as_string_with`
+//! use an alphabet.  The `Display` grids, the `.sdm`, `.sdk` and pencil grid formats, SVG images
+//! and booklets always write the numbers 1 to 9.  The crate only handles 9x9 grids, so the
+//! symbols of `base36` and `letters` past the ninth are not used yet.
+use crate::*;
+
+const BASE_36: &str = "123456789ABCDEFGHIJKLMNOPQRSTUVWXYZ";
+const LETTERS: &str = "ABCDEFGHIJKLMNOPQRSTUVWXYZ";
+
+/// The symbol for each number, starting with the symbol for 1.  Empty cells are written with the
+/// blank symbol, which is `.` unless changed.  When parsing, `0`, `.` and `_` are also read as
+/// empty cells unless they are one of the symbols.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Alphabet {
+    symbols: Vec<char>,
+    blank: char,
+}
+
+/// The first `MAX_NUM` symbols of `Alphabet::base36`, which are the digits 1 to 9 for a 9x9 grid.
+impl Default for Alphabet {
+    fn default() -> Self {
+        Alphabet::new(&BASE_36[..MAX_NUM.min(BASE_36.len())])
+    }
+}
+
+impl Alphabet {
+    /// Creates an alphabet from the symbols for 1, 2, 3, and so on.  Panics if a symbol is
+    /// repeated.
+    pub fn new(symbols: &str) -> Alphabet {
+        let symbols = symbols.chars().collect::<Vec<char>>();
+        for (i, c) in symbols.iter().enumerate() {
+            assert!(
+                !symbols[..i].contains(c),
+                "Symbol '{}' is repeated in the alphabet",
+                c
+            );
+        }
+        Alphabet {
+            symbols,
+            blank: '.',
+        }
+    }
+
+    /// 1 to 9 followed by A to Z, so 16x16 grids use 1-9A-G.
+    pub fn base36() -> Alphabet {
+        Alphabet::new(BASE_36)
+    }
+
+    /// A to Z, so 25x25 grids use A-Y.
+    pub fn letters() -> Alphabet {
+        Alphabet::new(LETTERS)
+    }
+
+    /// The symbol used to write an empty cell.
+    pub fn blank(&mut self, blank: char) -> &mut Self {
+        assert!(
+            !self.symbols.contains(&blank),
+            "The blank symbol cannot be a number"
+        );
+        self.blank = blank;
+        self
+    }
+
+    pub fn symbols(&self) -> &[char] {
+        &self.symbols
+    }
+
+    /// The number that the symbol stands for.  Letters match either case unless both cases are
+    /// in the alphabet.
+    pub fn value(&self, c: char) -> Option<Element> {
+        self.symbols
+            .iter()
+            .position(|s| *s == c)
+            .or_else(|| self.symbols.iter().position(|s| s.eq_ignore_ascii_case(&c)))
+            .map(|i| (i + 1) as Element)
+    }
+
+    /// The symbol for the number, or the blank symbol for 0.
+    pub fn symbol(&self, val: Element) -> Option<char> {
+        match val {
+            0 => Some(self.blank),
+            v => self.symbols.get(v as usize - 1).cloned(),
+        }
+    }
+
+    fn is_blank(&self, c: char) -> bool {
+        c == self.blank || c == '0' || c == '.' || c == '_'
+    }
+
+    /// Reads a puzzle written with this alphabet.  Works the same as `PuzInput` for `&str`: in
+    /// `ParseMode::Lenient` any character that is not a symbol is an empty cell, and in
+    /// `ParseMode::Strict` only blanks are empty cells, grid lines and whitespace are skipped, and
+    /// anything else is an error.
+    pub fn parse(&self, text: &str, mode: ParseMode) -> Result<Vec<Element>, SudError> {
+        let mut v: Vec<Element> = Vec::new();
+        for (line, row) in text.lines().enumerate() {
+            for (col, c) in row.chars().enumerate() {
+                let (line, column) = (line + 1, col + 1);
+                match self.value(c) {
+                    Some(n) if n as usize > MAX_NUM => {
+                        return Err(SudError::OutOfRange {
+                            line,
+                            column,
+                            value: n as usize,
+                        })
+                    }
+                    Some(n) => v.push(n),
+                    None if mode == ParseMode::Lenient || self.is_blank(c) => v.push(0),
+                    None if is_decoration(c) => (),
+                    None => {
+                        return Err(SudError::InvalidChar {
+                            line,
+                            column,
+                            found: c,
+                        })
+                    }
+                }
+            }
+        }
+
+        if v.len() != NUM_CELLS {
+            return Err(SudError::WrongLength {
+                expected: NUM_CELLS,
+                found: v.len(),
+            });
+        }
+        if mode == ParseMode::Strict {
+            check_givens(&v)?;
+        }
+        Ok(v)
+    }
+
+    /// Writes the numbers as a single line of symbols.
+    pub fn format(&self, values: &[Element]) -> Result<String, SudError> {
+        if values.len() != NUM_CELLS {
+            return Err(SudError::OutputParse);
+        }
+        values
+            .iter()
+            .map(|v| self.symbol(*v).ok_or(SudError::OutputParse))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod alphabet_tests {
+    use super::*;
+
+    const EXAMPLE: &str =
+        "53..7....6..195....98....6.8...6...34..8.3..17...2...6.6....28....419..5....8..79";
+
+    #[test]
+    fn default_test() {
+        let alphabet = Alphabet::default();
+        assert_eq!(alphabet.symbols().len(), MAX_NUM);
+        let values = alphabet.parse(EXAMPLE, ParseMode::Strict).unwrap();
+        assert_eq!(values, EXAMPLE.as_input().unwrap());
+        assert_eq!(alphabet.format(&values).unwrap(), EXAMPLE);
+        assert_eq!(alphabet.symbol(10), None);
+        assert_eq!(alphabet.format(&values[1..]), Err(SudError::OutputParse));
+    }
+
+    #[test]
+    fn letters_test() {
+        let mut alphabet = Alphabet::letters();
+        alphabet.blank('-');
+        let letters = EXAMPLE
+            .chars()
+            .map(|c| match c.to_digit(10) {
+                Some(d) => (b'A' + d as u8 - 1) as char,
+                None => '-',
+            })
+            .collect::<String>();
+        assert!(letters.starts_with("EC--G----F--AIE---"));
+
+        // The blank is written, but not skipped as a grid line, when parsing
+        let values = alphabet.parse(&letters, ParseMode::Lenient).unwrap();
+        assert_eq!(values, EXAMPLE.as_input().unwrap());
+        assert_eq!(alphabet.format(&values).unwrap(), letters);
+        assert_eq!(
+            alphabet
+                .parse(&letters.to_lowercase(), ParseMode::Strict)
+                .unwrap(),
+            values
+        );
+
+        // J is 10, which is too large for a 9x9 grid
+        assert!(matches!(
+            alphabet.parse(&letters.replacen("E", "J", 1), ParseMode::Lenient),
+            Err(SudError::OutOfRange {
+                line: 1,
+                column: 1,
+                value: 10
+            })
+        ));
+        assert_eq!(Alphabet::base36().value('G'), Some(16));
+        assert_eq!(Alphabet::letters().value('Y'), Some(25));
+    }
+
+    #[test]
+    fn output_test() {
+        // Other types get `as_string_with` from their 2-D vector
+        struct Grid(Vec<Element>);
+        impl PuzOutput for Grid {
+            fn as_string(&self) -> Result<String, SudError> {
+                self.0.as_string()
+            }
+
+            fn as_2d_vec(&self) -> Result<Vec<Vec<Element>>, SudError> {
+                self.0.as_2d_vec()
+            }
+        }
+        let letters = EXAMPLE
+            .chars()
+            .map(|c| c.to_digit(10).map_or('.', |d| (b'A' + d as u8 - 1) as char))
+            .collect::<String>();
+        let grid = Grid(EXAMPLE.as_input().unwrap());
+        assert_eq!(grid.as_string_with(&Alphabet::letters()), Ok(letters));
+        assert_eq!(grid.as_string_with(&Alphabet::default()), grid.as_string());
+    }
+
+    #[test]
+    fn custom_test() {
+        let alphabet = Alphabet::new("abcdefghi");
+        assert_eq!(alphabet.value('A'), Some(1));
+        let both = Alphabet::new("aAbBcCdDe");
+        assert_eq!(both.value('A'), Some(2));
+        assert_eq!(both.value('e'), Some(9));
+        assert_eq!(both.value('E'), Some(9));
+        assert!(matches!(
+            alphabet.parse(&"x".repeat(81), ParseMode::Strict),
+            Err(SudError::InvalidChar { found: 'x', .. })
+        ));
+    }
+
+    #[test]
+    #[should_panic]
+    fn repeated_symbol_test() {
+        Alphabet::new("123456781");
+    }
+}
@@ -0,0 +1,114 @@
+//! A small command line parser, so that the crate does not need any dependencies.
+use std::str::FromStr;
+
+/// Walks through the arguments of a command.  Options may be written as `--name value`,
+/// `--name=value` or `-n value`.  Anything that does not start with `-` is a positional argument,
+/// as is everything after `--`.
+pub struct Args {
+    args: Vec<String>,
+    position: usize,
+    /// The value written after `=` in the last option
+    pending: Option<String>,
+    positional: Vec<String>,
+}
+
+impl Args {
+    pub fn new(args: &[String]) -> Args {
+        Args {
+            args: args.to_vec(),
+            position: 0,
+            pending: None,
+            positional: Vec::new(),
+        }
+    }
+
+    /// The next option, such as `--max-solutions`.  Positional arguments are collected along the
+    /// way.  Returns an error if the last option had a value that was not used.
+    pub fn next_option(&mut self) -> Result<Option<String>, String> {
+        if let Some(value) = self.pending.take() {
+            return Err(format!("Unexpected value '{}'", value));
+        }
+        while let Some(arg) = self.args.get(self.position).cloned() {
+            self.position += 1;
+            if arg == "--" {
+                self.positional
+                    .extend(self.args[self.position..].iter().cloned());
+                self.position = self.args.len();
+            } else if arg.starts_with('-') && arg.len() > 1 {
+                return Ok(Some(match arg.find('=') {
+                    Some(i) if arg.starts_with("--") => {
+                        self.pending = Some(arg[i + 1..].to_string());
+                        arg[..i].to_string()
+                    }
+                    _ => arg,
+                }));
+            } else {
+                self.positional.push(arg);
+            }
+        }
+        Ok(None)
+    }
+
+    /// The value of the option that was just returned by `next_option`.
+    pub fn value(&mut self, name: &str) -> Result<String, String> {
+        if let Some(value) = self.pending.take() {
+            return Ok(value);
+        }
+        match self.args.get(self.position) {
+            Some(value) => {
+                self.position += 1;
+                Ok(value.clone())
+            }
+            None => Err(format!("{} needs a value", name)),
+        }
+    }
+
+    pub fn parse<T: FromStr>(&mut self, name: &str) -> Result<T, String> {
+        let value = self.value(name)?;
+        value
+            .parse()
+            .map_err(|_| format!("Invalid value '{}' for {}", value, name))
+    }
+
+    /// Arguments that are not options.  Only complete once `next_option` returns `None`.
+    pub fn positional(&self) -> &[String] {
+        &self.positional
+    }
+}
+
+#[cfg(test)]
+mod args_tests {
+    use super::*;
+
+    fn args(text: &str) -> Args {
+        Args::new(
+            &text
+                .split_whitespace()
+                .map(|s| s.to_string())
+                .collect::<Vec<String>>(),
+        )
+    }
+
+    #[test]
+    fn options_test() {
+        let mut a = args("one --max 3 two -t=4 --timeout=1.5 - -- --three");
+        assert_eq!(a.next_option(), Ok(Some("--max".to_string())));
+        assert_eq!(a.parse::<usize>("--max"), Ok(3));
+        assert_eq!(a.next_option(), Ok(Some("-t=4".to_string())));
+        assert_eq!(a.next_option(), Ok(Some("--timeout".to_string())));
+        assert_eq!(a.parse::<f64>("--timeout"), Ok(1.5));
+        assert_eq!(a.next_option(), Ok(None));
+        assert_eq!(a.positional(), &["one", "two", "-", "--three"]);
+    }
+
+    #[test]
+    fn errors_test() {
+        let mut a = args("--max=x --pretty=yes --count");
+        a.next_option().unwrap();
+        assert!(a.parse::<usize>("--max").is_err());
+        assert_eq!(a.next_option(), Ok(Some("--pretty".to_string())));
+        assert!(a.next_option().is_err());
+        assert_eq!(a.next_option(), Ok(Some("--count".to_string())));
+        assert!(a.value("--count").is_err());
+    }
+}
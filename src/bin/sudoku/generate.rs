@@ -0,0 +1,183 @@
+//! `sudoku generate`: generates puzzles with a unique solution.
+use crate::args::Args;
+use crate::{EXIT_FAILED, EXIT_OK, EXIT_USAGE};
+use std::time::{SystemTime, UNIX_EPOCH};
+use sudoku::{Alphabet, Generator, PuzOutput, Symmetry, Technique};
+
+pub const USAGE: &str = "Usage: sudoku generate [OPTIONS]
+
+Generates puzzles with a unique solution and prints each as an .sdm line: 81 digits, with 0 for
+an empty cell.
+
+Options:
+  -n, --count N            How many puzzles to generate (default 1)
+  -s, --seed N             Seed for the random numbers.  The same seed and options generate the
+                           same puzzles.  A seed is picked and printed to standard error if not
+                           given.
+  -d, --difficulty NAME    Only keep puzzles whose hardest technique is NAME: naked-single,
+                           hidden-single, naked-tuple, locked-candidates or brute-force
+  -c, --clues MIN[-MAX]    How many givens each puzzle has (default 22-30)
+  -y, --symmetry NAME      none, rotational, quarter-turn, horizontal, vertical or diagonal
+                           (default none)
+      --size N             Width of the grid.  Only 9 is supported.
+  -a, --attempts N         Grids to try for each puzzle before giving up (default 100)
+  -h, --help               Print this message
+
+Exit codes:
+  0  Every puzzle was generated
+  1  Invalid arguments
+  2  No puzzle matching the options was found";
+
+struct Options {
+    count: usize,
+    seed: Option<u64>,
+    difficulty: Option<Technique>,
+    clues: (usize, usize),
+    symmetry: Symmetry,
+    attempts: usize,
+}
+
+/// Reads `MIN-MAX`, or `N` for exactly N.
+fn parse_clues(text: &str) -> Result<(usize, usize), String> {
+    let invalid = || format!("Invalid clue range '{}'", text);
+    let (min, max) = match text.find('-') {
+        Some(i) => (&text[..i], &text[i + 1..]),
+        None => (text, text),
+    };
+    let min = min.trim().parse::<usize>().map_err(|_| invalid())?;
+    let max = max.trim().parse::<usize>().map_err(|_| invalid())?;
+    if min > max || max > 81 {
+        return Err(format!("Clue range '{}' must be within 0-81", text));
+    }
+    Ok((min, max))
+}
+
+fn parse_args(args: &[String]) -> Result<Option<Options>, String> {
+    let mut options = Options {
+        count: 1,
+        seed: None,
+        difficulty: None,
+        clues: Generator::DEFAULT_CLUES,
+        symmetry: Symmetry::None,
+        attempts: 100,
+    };
+    let mut args = Args::new(args);
+    while let Some(option) = args.next_option()? {
+        match option.as_str() {
+            "-n" | "--count" => options.count = args.parse(&option)?,
+            "-s" | "--seed" => options.seed = Some(args.parse(&option)?),
+            "-d" | "--difficulty" => {
+                let name = args.value(&option)?;
+                options.difficulty = Some(name.parse().map_err(|e| format!("{}", e))?);
+            }
+            "-c" | "--clues" => options.clues = parse_clues(&args.value(&option)?)?,
+            "-y" | "--symmetry" => {
+                let name = args.value(&option)?;
+                options.symmetry = name.parse().map_err(|e| format!("{}", e))?;
+            }
+            "--size" => {
+                let size: usize = args.parse(&option)?;
+                if size != 9 {
+                    return Err(format!("Only 9x9 grids are supported, not {0}x{0}", size));
+                }
+            }
+            "-a" | "--attempts" => {
+                options.attempts = args.parse(&option)?;
+                if options.attempts == 0 {
+                    return Err(format!("{} must be at least 1", option));
+                }
+            }
+            "-h" | "--help" => return Ok(None),
+            _ => return Err(format!("Unknown option '{}'", option)),
+        }
+    }
+    if let Some(file) = args.positional().first() {
+        return Err(format!("Unexpected argument '{}'", file));
+    }
+    Ok(Some(options))
+}
+
+pub fn run(args: &[String]) -> i32 {
+    let options = match parse_args(args) {
+        Ok(Some(options)) => options,
+        Ok(None) => {
+            println!("{}", USAGE);
+            return EXIT_OK;
+        }
+        Err(e) => {
+            eprintln!("{}\n\n{}", e, USAGE);
+            return EXIT_USAGE;
+        }
+    };
+    let seed = options.seed.unwrap_or_else(|| {
+        let seed = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_nanos() as u64)
+            .unwrap_or_default();
+        eprintln!("seed: {}", seed);
+        seed
+    });
+
+    let mut generator = Generator::new(seed);
+    generator
+        .clues(options.clues.0, options.clues.1)
+        .symmetry(options.symmetry)
+        .attempts(options.attempts);
+    if let Some(difficulty) = options.difficulty {
+        generator.difficulty(difficulty);
+    }
+    let mut sdm = Alphabet::default();
+    sdm.blank('0');
+
+    for _ in 0..options.count {
+        match generator.generate() {
+            Ok(puz) => {
+                let givens = puz.cells.iter().map(|c| c.num()).collect::<Vec<u8>>();
+                println!("{}", givens.as_string_with(&sdm).unwrap_or_default());
+            }
+            Err(e) => {
+                eprintln!("{}", e);
+                return EXIT_FAILED;
+            }
+        }
+    }
+    EXIT_OK
+}
+
+#[cfg(test)]
+mod generate_tests {
+    use super::*;
+
+    fn args(text: &str) -> Vec<String> {
+        text.split_whitespace().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn parse_args_test() {
+        let options = parse_args(&args(
+            "-n 3 --seed 7 -d hidden-single --clues 24-30 -y rotational --size 9",
+        ))
+        .unwrap()
+        .unwrap();
+        assert_eq!(options.count, 3);
+        assert_eq!(options.seed, Some(7));
+        assert_eq!(options.difficulty, Some(Technique::HiddenSingle));
+        assert_eq!(options.clues, (24, 30));
+        assert_eq!(options.symmetry, Symmetry::Rotational);
+
+        assert!(parse_args(&args("--help")).unwrap().is_none());
+        assert!(parse_args(&args("--size 16")).is_err());
+        assert!(parse_args(&args("-d x-wing")).is_err());
+        assert!(parse_args(&args("-y spiral")).is_err());
+        assert!(parse_args(&args("extra")).is_err());
+    }
+
+    #[test]
+    fn parse_clues_test() {
+        assert_eq!(parse_clues("28"), Ok((28, 28)));
+        assert_eq!(parse_clues("22 - 26"), Ok((22, 26)));
+        assert!(parse_clues("30-20").is_err());
+        assert!(parse_clues("20-90").is_err());
+        assert!(parse_clues("many").is_err());
+    }
+}
@@ -0,0 +1,97 @@
+//! Reading puzzles, one per line, from files or standard input.
+use std::fs::File;
+use std::io::{self, BufRead, BufReader};
+
+/// A line of input and where it came from.
+pub struct Line {
+    pub source: String,
+    pub number: usize,
+    pub text: String,
+}
+
+impl Line {
+    /// Where the line came from, written as `file:line`.
+    pub fn location(&self) -> String {
+        format!("{}:{}", self.source, self.number)
+    }
+}
+
+/// The lines of the file being read, with its name and the number of lines read so far.
+struct Open {
+    source: String,
+    lines: io::Lines<Box<dyn BufRead>>,
+    number: usize,
+}
+
+/// Reads the lines of each file in turn, as they are needed.  Each file is opened once the
+/// previous one has been read.
+pub struct Lines {
+    files: std::vec::IntoIter<String>,
+    open: Option<Open>,
+}
+
+impl Iterator for Lines {
+    type Item = Result<Line, String>;
+
+    fn next(&mut self) -> Option<Result<Line, String>> {
+        loop {
+            let open = match self.open.as_mut() {
+                Some(open) => open,
+                None => {
+                    let file = self.files.next()?;
+                    let reader: Box<dyn BufRead> = if file == "-" {
+                        Box::new(BufReader::new(io::stdin()))
+                    } else {
+                        match File::open(&file) {
+                            Ok(f) => Box::new(BufReader::new(f)),
+                            Err(e) => return Some(Err(format!("{}: {}", file, e))),
+                        }
+                    };
+                    let source = if file == "-" {
+                        "<stdin>".to_string()
+                    } else {
+                        file
+                    };
+                    self.open.insert(Open {
+                        source,
+                        lines: reader.lines(),
+                        number: 0,
+                    })
+                }
+            };
+            let text = match open.lines.next() {
+                Some(Ok(text)) => text,
+                Some(Err(e)) => return Some(Err(format!("{}: {}", open.source, e))),
+                None => {
+                    self.open = None;
+                    continue;
+                }
+            };
+            open.number += 1;
+            let trimmed = text.trim();
+            if trimmed.is_empty() || trimmed.starts_with('#') {
+                continue;
+            }
+            return Some(Ok(Line {
+                source: open.source.clone(),
+                number: open.number,
+                text: trimmed.to_string(),
+            }));
+        }
+    }
+}
+
+/// Reads the lines of each file in turn, or standard input if there are no files or the file is
+/// `-`.  Blank lines and lines starting with `#` are skipped.  Nothing is read until it is needed,
+/// so each puzzle can be handled as soon as its line arrives.
+pub fn read_lines(files: &[String]) -> Lines {
+    let files = if files.is_empty() {
+        vec!["-".to_string()]
+    } else {
+        files.to_vec()
+    };
+    Lines {
+        files: files.into_iter(),
+        open: None,
+    }
+}
@@ -0,0 +1,47 @@
+//! Command line front end for the sudoku library.
+mod args;
+mod generate;
+mod input;
+mod rate;
+mod repl;
+mod solve;
+
+use std::process;
+
+pub const EXIT_OK: i32 = 0;
+pub const EXIT_USAGE: i32 = 1;
+pub const EXIT_FAILED: i32 = 2;
+pub const EXIT_TIMEOUT: i32 = 3;
+
+const USAGE: &str = "Usage: sudoku <COMMAND> [OPTIONS]
+
+Commands:
+  solve     Solve puzzles from files or standard input
+  rate      Rate puzzles by the techniques needed to solve them
+  generate  Generate puzzles with a unique solution
+  repl      Apply solving techniques to a puzzle one at a time
+
+Run 'sudoku <COMMAND> --help' for the options of a command.";
+
+fn main() {
+    let args = std::env::args().skip(1).collect::<Vec<String>>();
+    let code = match args.first().map(|a| a.as_str()) {
+        Some("solve") => solve::run(&args[1..]),
+        Some("rate") => rate::run(&args[1..]),
+        Some("generate") => generate::run(&args[1..]),
+        Some("repl") => repl::run(&args[1..]),
+        Some("-h") | Some("--help") | Some("help") => {
+            println!("{}", USAGE);
+            EXIT_OK
+        }
+        Some(command) => {
+            eprintln!("Unknown command '{}'\n\n{}", command, USAGE);
+            EXIT_USAGE
+        }
+        None => {
+            eprintln!("{}", USAGE);
+            EXIT_USAGE
+        }
+    };
+    process::exit(code);
+}
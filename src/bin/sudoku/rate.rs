@@ -0,0 +1,394 @@
+//! `sudoku rate`: rates each puzzle by the human techniques needed to solve it.
+use crate::args::Args;
+use crate::input::read_lines;
+use crate::{EXIT_FAILED, EXIT_OK, EXIT_USAGE};
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+use sudoku::{Puzzle, Rating, Technique};
+
+pub const USAGE: &str = "Usage: sudoku rate [OPTIONS] [FILE...]
+
+Solves each puzzle, one per line, from the files or standard input with the human techniques,
+trying the easiest first, and brute force once they are stuck.  Blank lines and lines starting
+with '#' are skipped.
+
+For each puzzle, prints the hardest technique used, how many times each technique was used,
+whether brute force was needed, the number of solutions (0, 1, or 2 for two or more) and the
+time taken in milliseconds.  A summary of all the puzzles follows.  CSV output is written to
+standard output and the summary to standard error.
+
+Options:
+  -f, --format FORMAT  csv or json (default csv)
+  -h, --help           Print this message
+
+Exit codes:
+  0  Every puzzle was rated
+  1  Invalid arguments or a file could not be read
+  2  A puzzle could not be read";
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Format {
+    Csv,
+    Json,
+}
+
+impl FromStr for Format {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Format, ()> {
+        match s.to_ascii_lowercase().as_str() {
+            "csv" => Ok(Format::Csv),
+            "json" => Ok(Format::Json),
+            _ => Err(()),
+        }
+    }
+}
+
+struct Options {
+    format: Format,
+    files: Vec<String>,
+}
+
+fn parse_args(args: &[String]) -> Result<Option<Options>, String> {
+    let mut options = Options {
+        format: Format::Csv,
+        files: Vec::new(),
+    };
+    let mut args = Args::new(args);
+    while let Some(option) = args.next_option()? {
+        match option.as_str() {
+            "-f" | "--format" => options.format = args.parse(&option)?,
+            "-h" | "--help" => return Ok(None),
+            _ => return Err(format!("Unknown option '{}'", option)),
+        }
+    }
+    options.files = args.positional().to_vec();
+    Ok(Some(options))
+}
+
+/// The rating of one puzzle and where it came from.
+struct Report {
+    source: String,
+    line: usize,
+    puzzle: String,
+    rating: Rating,
+    time: Duration,
+}
+
+/// Totals over every puzzle that was rated.
+#[derive(Default)]
+struct Summary {
+    puzzles: usize,
+    invalid: usize,
+    no_solution: usize,
+    unique: usize,
+    multiple: usize,
+    brute_force: usize,
+    /// The number of puzzles whose hardest technique was each of `Technique::ALL`
+    hardest: Vec<usize>,
+    /// The number of times each of `Technique::ALL` was used
+    uses: Vec<usize>,
+    total_time: Duration,
+    max_time: Duration,
+}
+
+impl Summary {
+    fn new() -> Summary {
+        Summary {
+            hardest: vec![0; Technique::ALL.len()],
+            uses: vec![0; Technique::ALL.len()],
+            ..Summary::default()
+        }
+    }
+
+    fn add(&mut self, report: &Report) {
+        let rating = &report.rating;
+        self.puzzles += 1;
+        match rating.solutions {
+            0 => self.no_solution += 1,
+            1 => self.unique += 1,
+            _ => self.multiple += 1,
+        }
+        self.brute_force += rating.brute_force as usize;
+        for (i, technique) in Technique::ALL.iter().enumerate() {
+            self.uses[i] += rating.count(*technique);
+            if rating.hardest() == Some(*technique) {
+                self.hardest[i] += 1;
+            }
+        }
+        self.total_time += report.time;
+        self.max_time = self.max_time.max(report.time);
+    }
+
+    fn mean_time(&self) -> Duration {
+        if self.puzzles == 0 {
+            Duration::default()
+        } else {
+            self.total_time / self.puzzles as u32
+        }
+    }
+}
+
+fn millis(time: Duration) -> String {
+    format!("{:.3}", time.as_secs_f64() * 1000.0)
+}
+
+/// The technique's name as a CSV column or JSON key.
+fn key(technique: Technique) -> String {
+    technique.name().replace(' ', "_")
+}
+
+fn hardest_name(rating: &Rating) -> &'static str {
+    rating.hardest().map(|t| t.name()).unwrap_or("none")
+}
+
+fn csv_field(text: &str) -> String {
+    if text.contains([',', '"', '\n']) {
+        format!("\"{}\"", text.replace('"', "\"\""))
+    } else {
+        text.to_string()
+    }
+}
+
+fn json_string(text: &str) -> String {
+    let mut out = String::from("\"");
+    for c in text.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+fn csv_header() -> String {
+    let mut columns = vec!["source", "line", "puzzle", "hardest"]
+        .into_iter()
+        .map(|s| s.to_string())
+        .collect::<Vec<String>>();
+    columns.extend(Technique::HUMAN.iter().map(|t| key(*t)));
+    columns.extend(
+        vec!["brute_force", "solutions", "milliseconds"]
+            .into_iter()
+            .map(|s| s.to_string()),
+    );
+    columns.join(",")
+}
+
+fn csv_row(report: &Report) -> String {
+    let rating = &report.rating;
+    let mut fields = vec![
+        csv_field(&report.source),
+        report.line.to_string(),
+        csv_field(&report.puzzle),
+        hardest_name(rating).to_string(),
+    ];
+    fields.extend(
+        Technique::HUMAN
+            .iter()
+            .map(|t| rating.count(*t).to_string()),
+    );
+    fields.push(rating.brute_force.to_string());
+    fields.push(rating.solutions.to_string());
+    fields.push(millis(report.time));
+    fields.join(",")
+}
+
+fn json_report(report: &Report) -> String {
+    let rating = &report.rating;
+    let hardest = match rating.hardest() {
+        Some(t) => json_string(t.name()),
+        None => "null".to_string(),
+    };
+    let counts = Technique::HUMAN
+        .iter()
+        .map(|t| format!("\"{}\": {}", key(*t), rating.count(*t)))
+        .collect::<Vec<String>>();
+    format!(
+        "{{\"source\": {}, \"line\": {}, \"puzzle\": {}, \"hardest\": {}, \"techniques\": {{{}}}, \
+         \"brute_force\": {}, \"solutions\": {}, \"milliseconds\": {}}}",
+        json_string(&report.source),
+        report.line,
+        json_string(&report.puzzle),
+        hardest,
+        counts.join(", "),
+        rating.brute_force,
+        rating.solutions,
+        millis(report.time)
+    )
+}
+
+fn json_counts(counts: &[usize]) -> String {
+    Technique::ALL
+        .iter()
+        .zip(counts.iter())
+        .map(|(t, n)| format!("\"{}\": {}", key(*t), n))
+        .collect::<Vec<String>>()
+        .join(", ")
+}
+
+fn json_summary(summary: &Summary) -> String {
+    format!(
+        "{{\"puzzles\": {}, \"invalid\": {}, \"no_solution\": {}, \"unique\": {}, \
+         \"multiple_solutions\": {}, \"brute_force\": {}, \"hardest\": {{{}}}, \
+         \"techniques\": {{{}}}, \"total_milliseconds\": {}, \"mean_milliseconds\": {}, \
+         \"max_milliseconds\": {}}}",
+        summary.puzzles,
+        summary.invalid,
+        summary.no_solution,
+        summary.unique,
+        summary.multiple,
+        summary.brute_force,
+        json_counts(&summary.hardest),
+        json_counts(&summary.uses),
+        millis(summary.total_time),
+        millis(summary.mean_time()),
+        millis(summary.max_time)
+    )
+}
+
+fn text_summary(summary: &Summary) -> String {
+    let mut lines = vec![
+        format!("Puzzles: {} ({} invalid)", summary.puzzles, summary.invalid),
+        format!(
+            "Solutions: {} unique, {} multiple, {} none",
+            summary.unique, summary.multiple, summary.no_solution
+        ),
+        format!("Brute force needed: {}", summary.brute_force),
+        "Hardest technique:".to_string(),
+    ];
+    for (technique, n) in Technique::ALL.iter().zip(summary.hardest.iter()) {
+        lines.push(format!("  {:<18} {}", technique.name(), n));
+    }
+    lines.push("Technique uses:".to_string());
+    for (technique, n) in Technique::ALL.iter().zip(summary.uses.iter()) {
+        lines.push(format!("  {:<18} {}", technique.name(), n));
+    }
+    lines.push(format!(
+        "Time (ms): {} total, {} mean, {} max",
+        millis(summary.total_time),
+        millis(summary.mean_time()),
+        millis(summary.max_time)
+    ));
+    lines.join("\n")
+}
+
+pub fn run(args: &[String]) -> i32 {
+    let options = match parse_args(args) {
+        Ok(Some(options)) => options,
+        Ok(None) => {
+            println!("{}", USAGE);
+            return EXIT_OK;
+        }
+        Err(e) => {
+            eprintln!("{}\n\n{}", e, USAGE);
+            return EXIT_USAGE;
+        }
+    };
+
+    let mut code = EXIT_OK;
+    let mut summary = Summary::new();
+    let mut reports = Vec::new();
+    if options.format == Format::Csv {
+        println!("{}", csv_header());
+    }
+    for line in read_lines(&options.files) {
+        let line = match line {
+            Ok(line) => line,
+            Err(e) => {
+                eprintln!("{}", e);
+                return EXIT_USAGE;
+            }
+        };
+        let puz = match line.text.parse::<Puzzle>() {
+            Ok(puz) => puz,
+            Err(e) => {
+                eprintln!("{}: {}", line.location(), e);
+                summary.invalid += 1;
+                code = EXIT_FAILED;
+                continue;
+            }
+        };
+        let start = Instant::now();
+        let rating = puz.rate();
+        let report = Report {
+            source: line.source.clone(),
+            line: line.number,
+            puzzle: line.text.clone(),
+            rating,
+            time: start.elapsed(),
+        };
+        summary.add(&report);
+        match options.format {
+            // Print as we go, since rating thousands of puzzles takes a while
+            Format::Csv => println!("{}", csv_row(&report)),
+            Format::Json => reports.push(json_report(&report)),
+        }
+    }
+
+    match options.format {
+        Format::Csv => eprintln!("{}", text_summary(&summary)),
+        Format::Json => {
+            println!("{{\n  \"puzzles\": [");
+            for (i, report) in reports.iter().enumerate() {
+                let comma = if i + 1 < reports.len() { "," } else { "" };
+                println!("    {}{}", report, comma);
+            }
+            println!("  ],\n  \"summary\": {}\n}}", json_summary(&summary));
+        }
+    }
+    code
+}
+
+#[cfg(test)]
+mod rate_tests {
+    use super::*;
+
+    fn report(puzzle: &str) -> Report {
+        Report {
+            source: "a,b.txt".to_string(),
+            line: 3,
+            puzzle: puzzle.to_string(),
+            rating: puzzle.parse::<Puzzle>().unwrap().rate(),
+            time: Duration::from_micros(1500),
+        }
+    }
+
+    #[test]
+    fn parse_args_test() {
+        let args = ["--format=JSON", "a.txt"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect::<Vec<String>>();
+        let options = parse_args(&args).unwrap().unwrap();
+        assert_eq!(options.format, Format::Json);
+        assert_eq!(options.files, vec!["a.txt"]);
+
+        assert!(parse_args(&["-f".to_string(), "xml".to_string()]).is_err());
+        assert!(parse_args(&["-h".to_string()]).unwrap().is_none());
+    }
+
+    #[test]
+    fn output_test() {
+        let solved =
+            "534678912672195348198342567859761423426853791713924856961537284287419635345286179";
+        let report = report(solved);
+        assert_eq!(
+            csv_row(&report),
+            format!("\"a,b.txt\",3,{},none,0,0,0,0,false,1,1.500", solved)
+        );
+        assert!(json_report(&report)
+            .contains("\"hardest\": null, \"techniques\": {\"naked_single\": 0"));
+
+        let mut summary = Summary::new();
+        summary.add(&report);
+        summary.add(&report);
+        assert_eq!(summary.unique, 2);
+        assert_eq!(millis(summary.mean_time()), "1.500");
+        assert!(json_summary(&summary).contains("\"total_milliseconds\": 3.000"));
+        assert_eq!(json_string("a\"\\\n"), "\"a\\\"\\\\\\u000a\"");
+    }
+}
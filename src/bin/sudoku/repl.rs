@@ -0,0 +1,408 @@
+//! `sudoku repl`: applies solving techniques to a puzzle one at a time.
+use crate::{EXIT_FAILED, EXIT_OK, EXIT_USAGE};
+use std::collections::BTreeSet;
+use std::fs;
+use std::io::{self, BufRead, IsTerminal, Write};
+use sudoku::{
+    index_to_col, index_to_row, update_pencil_after_add, update_pencil_after_remove,
+    write_pencil_grid, BasicHumanMethods, PuzOutput, Puzzle, ShareCode, Technique,
+};
+
+pub const USAGE: &str = "Usage: sudoku repl [PUZZLE | FILE]
+
+Reads commands from standard input and applies solving techniques to a puzzle one at a time.
+Type 'help' for the commands.";
+
+const HELP: &str = "Commands:
+  load PUZZLE           Load a puzzle, or a code written by 'dump'
+  show                  Print the grid with its pencil marks
+  step                  Apply the easiest technique that makes progress
+  TECHNIQUE             Apply a technique: single_candidate, single_possibility, naked_tuple,
+                        locked_candidates_pointing, locked_candidates_claiming or brute_force
+  set CELL NUMBER       Fill in a cell, such as 'set r3c4 7', and remove the number from the
+                        pencil marks of the cells it sees
+  clear CELL            Empty a cell, and put the number back into the pencil marks of the
+                        cells it sees
+  mark CELL NUMBERS     Add pencil marks, such as 'mark r3c4 27'
+  unmark CELL NUMBERS   Remove pencil marks
+  reset                 Work out the pencil marks of every empty cell again
+  undo                  Undo the last change
+  dump                  Print the numbers and a code that 'load' reads back with pencil marks
+  help                  Print this message
+  quit                  Leave
+
+Each change is printed as a list of cells: 'r3c4 = 7' when a cell is filled in, and
+'r3c4 -2 +5' when pencil marks are removed or added.";
+
+const TECHNIQUES: [&str; 6] = [
+    "single_candidate",
+    "single_possibility",
+    "naked_tuple",
+    "locked_candidates_pointing",
+    "locked_candidates_claiming",
+    "brute_force",
+];
+
+/// A cell's name, such as `r3c4`.
+fn cell_name(index: usize) -> String {
+    format!("r{}c{}", index_to_row(index) + 1, index_to_col(index) + 1)
+}
+
+fn parse_cell(text: &str) -> Result<usize, String> {
+    let invalid = || format!("Invalid cell '{}', expected one like r3c4", text);
+    let lower = text.to_ascii_lowercase();
+    let digits = lower
+        .strip_prefix('r')
+        .and_then(|rest| {
+            let mut parts = rest.splitn(2, 'c');
+            Some((
+                parts.next()?.parse::<usize>().ok()?,
+                parts.next()?.parse::<usize>().ok()?,
+            ))
+        })
+        .ok_or_else(invalid)?;
+    match digits {
+        (row, col) if (1..=9).contains(&row) && (1..=9).contains(&col) => {
+            Ok((row - 1) * 9 + col - 1)
+        }
+        _ => Err(invalid()),
+    }
+}
+
+/// Reads numbers written together, such as `27`, or apart.
+fn parse_numbers(words: &[&str]) -> Result<Vec<u8>, String> {
+    let mut nums = Vec::new();
+    for c in words.concat().chars() {
+        match c.to_digit(10) {
+            Some(n) if n >= 1 => nums.push(n as u8),
+            _ => return Err(format!("Invalid number '{}'", c)),
+        }
+    }
+    if nums.is_empty() {
+        return Err("Expected at least one number".to_string());
+    }
+    Ok(nums)
+}
+
+/// Describes every cell that is different between the two puzzles.
+fn diff(before: &Puzzle, after: &Puzzle) -> String {
+    let mut lines = Vec::new();
+    for (index, (old, new)) in before.cells.iter().zip(after.cells.iter()).enumerate() {
+        let name = cell_name(index);
+        if old.num() != new.num() {
+            lines.push(match (old.num(), new.num()) {
+                (0, val) => format!("{} = {}", name, val),
+                (was, 0) => format!("{} cleared (was {})", name, was),
+                (was, val) => format!("{} = {} (was {})", name, val, was),
+            });
+        } else if new.num() == 0 && old.penciled() != new.penciled() {
+            let removed = old.penciled().difference(new.penciled());
+            let added = new.penciled().difference(old.penciled());
+            let marks = removed
+                .map(|v| format!("-{}", v))
+                .chain(added.map(|v| format!("+{}", v)))
+                .collect::<Vec<String>>();
+            lines.push(format!("{} {}", name, marks.join(" ")));
+        }
+    }
+    if lines.is_empty() {
+        "No change".to_string()
+    } else {
+        lines.join("\n")
+    }
+}
+
+fn cells(list: &[(usize, u8)]) -> String {
+    list.iter()
+        .map(|(i, v)| format!("{}={}", cell_name(*i), v))
+        .collect::<Vec<String>>()
+        .join(" ")
+}
+
+/// Reads a puzzle in any format the library can parse, or a share code.
+fn load(text: &str) -> Result<Puzzle, String> {
+    text.parse::<Puzzle>().or_else(|e| {
+        ShareCode(text.trim())
+            .to_puzzle()
+            .map_err(|_| e.to_string())
+    })
+}
+
+/// Runs the technique on the puzzle, and describes what the technique found.
+fn apply(puz: &mut Puzzle, name: &str) -> String {
+    match name {
+        "single_candidate" => format!("Found {}", cells(&puz.single_candidate(true))),
+        "single_possibility" => format!("Found {}", cells(&puz.single_possibility(true))),
+        "naked_tuple" => {
+            let found: BTreeSet<usize> = puz.naked_tuple();
+            let names = found.into_iter().map(cell_name).collect::<Vec<String>>();
+            format!("Tuple cells {}", names.join(" "))
+        }
+        "locked_candidates_pointing" => {
+            format!("Found {}", cells(&puz.locked_candidates_pointing(true)))
+        }
+        "locked_candidates_claiming" => {
+            format!("Found {}", cells(&puz.locked_candidates_claiming(true)))
+        }
+        _ if puz.is_solved() => "The puzzle is already solved".to_string(),
+        _ => {
+            let step = puz.apply_technique(Technique::BruteForce);
+            if step.placements.is_empty() {
+                "No solution".to_string()
+            } else {
+                "Filled in the solution".to_string()
+            }
+        }
+    }
+}
+
+#[derive(Default)]
+struct Repl {
+    puzzle: Option<Puzzle>,
+    history: Vec<Puzzle>,
+}
+
+impl Repl {
+    fn puzzle(&mut self) -> Result<&mut Puzzle, String> {
+        self.puzzle
+            .as_mut()
+            .ok_or_else(|| "No puzzle, use 'load' first".to_string())
+    }
+
+    /// Runs one command.  Returns `None` for `quit`.
+    fn execute(&mut self, line: &str) -> Result<Option<String>, String> {
+        let words = line.split_whitespace().collect::<Vec<&str>>();
+        let command = match words.first() {
+            Some(command) => command.to_ascii_lowercase(),
+            None => return Ok(Some(String::new())),
+        };
+        let args = &words[1..];
+        let before = self.puzzle.clone();
+
+        let found = match command.as_str() {
+            "quit" | "exit" => return Ok(None),
+            "help" => return Ok(Some(HELP.to_string())),
+            "load" => {
+                let puz = load(&args.join(" "))?;
+                let grid = write_pencil_grid(&puz);
+                if let Some(old) = self.puzzle.replace(puz) {
+                    self.history.push(old);
+                }
+                return Ok(Some(grid));
+            }
+            "show" => return Ok(Some(write_pencil_grid(self.puzzle()?))),
+            "dump" => {
+                let puz = self.puzzle()?;
+                let values = puz.cells.iter().map(|c| c.num()).collect::<Vec<u8>>();
+                return Ok(Some(format!(
+                    "values {}\ncode {}",
+                    values.as_string().unwrap_or_default(),
+                    puz.share_code(true, true)
+                )));
+            }
+            "undo" => {
+                let puz = self.history.pop().ok_or("Nothing to undo")?;
+                let text = diff(self.puzzle()?, &puz);
+                self.puzzle = Some(puz);
+                return Ok(Some(text));
+            }
+            "step" => match self.puzzle()?.next_step() {
+                Some(step) => step.technique.to_string(),
+                None => return Ok(Some("No technique makes progress".to_string())),
+            },
+            name if TECHNIQUES.contains(&name) => apply(self.puzzle()?, name),
+            "set" => {
+                let (index, nums) = match args {
+                    [cell, num] => (parse_cell(cell)?, parse_numbers(&[num])?),
+                    _ => return Err("Usage: set CELL NUMBER".to_string()),
+                };
+                let puz = self.puzzle()?;
+                if nums.len() != 1 || !puz.set_entry(index, nums[0]) {
+                    return Err(format!("Cannot set {} to {}", cell_name(index), args[1]));
+                }
+                update_pencil_after_add(puz, index);
+                String::new()
+            }
+            "clear" => {
+                let index = match args {
+                    [cell] => parse_cell(cell)?,
+                    _ => return Err("Usage: clear CELL".to_string()),
+                };
+                let puz = self.puzzle()?;
+                let val = puz.cells[index].num();
+                if !puz.set_entry(index, 0) {
+                    return Err(format!("{} is a given", cell_name(index)));
+                }
+                update_pencil_after_remove(puz, index, val);
+                String::new()
+            }
+            "mark" | "unmark" => {
+                if args.len() < 2 {
+                    return Err(format!("Usage: {} CELL NUMBERS", command));
+                }
+                let index = parse_cell(args[0])?;
+                let nums = parse_numbers(&args[1..])?;
+                let cell = &mut self.puzzle()?.cells[index];
+                if cell.num() != 0 {
+                    return Err(format!("{} is filled in", cell_name(index)));
+                }
+                for num in nums {
+                    if command == "mark" {
+                        cell.mark_possible(num);
+                    } else {
+                        cell.remove_possible(num);
+                    }
+                }
+                String::new()
+            }
+            "reset" => {
+                self.puzzle()?.reset_pencil_marks();
+                String::new()
+            }
+            _ => return Err(format!("Unknown command '{}', try 'help'", command)),
+        };
+
+        // Every command that gets this far may have changed the puzzle
+        let before = before.expect("The command needed a puzzle");
+        let after = self.puzzle()?;
+        let text = diff(&before, after);
+        if before.cells != after.cells {
+            self.history.push(before);
+        }
+        Ok(Some(if found.is_empty() {
+            text
+        } else {
+            format!("{}\n{}", found, text)
+        }))
+    }
+}
+
+pub fn run(args: &[String]) -> i32 {
+    let mut repl = Repl::default();
+    match args {
+        [] => (),
+        [arg] if arg == "-h" || arg == "--help" => {
+            println!("{}\n\n{}", USAGE, HELP);
+            return EXIT_OK;
+        }
+        [arg] => {
+            let text = fs::read_to_string(arg).unwrap_or_else(|_| arg.clone());
+            match load(&text) {
+                Ok(puz) => repl.puzzle = Some(puz),
+                Err(e) => {
+                    eprintln!("{}", e);
+                    return EXIT_FAILED;
+                }
+            }
+        }
+        _ => {
+            eprintln!("{}", USAGE);
+            return EXIT_USAGE;
+        }
+    }
+
+    let interactive = io::stdin().is_terminal();
+    let stdin = io::stdin();
+    let mut lines = stdin.lock().lines();
+    loop {
+        if interactive {
+            print!("> ");
+            let _ = io::stdout().flush();
+        }
+        let line = match lines.next() {
+            Some(Ok(line)) => line,
+            _ => break,
+        };
+        match repl.execute(&line) {
+            Ok(Some(text)) if text.is_empty() => (),
+            Ok(Some(text)) => println!("{}", text.trim_end()),
+            Ok(None) => break,
+            Err(e) => println!("Error: {}", e),
+        }
+    }
+    EXIT_OK
+}
+
+#[cfg(test)]
+mod repl_tests {
+    use super::*;
+
+    const EXAMPLE: &str =
+        "53..7....6..195....98....6.8...6...34..8.3..17...2...6.6....28....419..5....8..79";
+
+    fn run(repl: &mut Repl, line: &str) -> String {
+        repl.execute(line).unwrap().unwrap()
+    }
+
+    #[test]
+    fn parse_test() {
+        assert_eq!(parse_cell("r1c1"), Ok(0));
+        assert_eq!(parse_cell("R3C4"), Ok(21));
+        assert!(parse_cell("r0c1").is_err());
+        assert!(parse_cell("a1").is_err());
+        assert_eq!(parse_numbers(&["27", "3"]), Ok(vec![2, 7, 3]));
+        assert!(parse_numbers(&["20"]).is_err());
+    }
+
+    #[test]
+    fn technique_test() {
+        let mut repl = Repl::default();
+        assert!(repl.execute("show").is_err());
+        assert!(run(&mut repl, &format!("load {}", EXAMPLE)).contains("5"));
+
+        let text = run(&mut repl, "single_candidate");
+        assert!(text.starts_with("Found r5c5=5"));
+        assert!(text.contains("\nr5c5 = 5\n"));
+
+        let text = run(&mut repl, "undo");
+        assert!(text.contains("r5c5 cleared (was 5)"));
+        assert!(repl.execute("undo").is_err());
+
+        assert!(run(&mut repl, "step").starts_with("naked single\n"));
+        assert_eq!(
+            run(&mut repl, "naked_tuple").lines().nth(1),
+            Some("No change")
+        );
+        // The naked singles solved the puzzle
+        assert_eq!(
+            run(&mut repl, "brute_force"),
+            "The puzzle is already solved\nNo change"
+        );
+        run(&mut repl, "undo");
+        assert!(run(&mut repl, "brute_force").starts_with("Filled in the solution\n"));
+        assert_eq!(run(&mut repl, "step"), "No technique makes progress");
+        assert!(repl.execute("hidden_tuple").is_err());
+
+        // r1c9 must be 9, but there is already a 9 in its box
+        run(&mut repl, &format!("load {:.<81}", "12345678.........9"));
+        assert_eq!(run(&mut repl, "brute_force"), "No solution\nNo change");
+    }
+
+    #[test]
+    fn edit_test() {
+        let mut repl = Repl::default();
+        run(&mut repl, &format!("load {}", EXAMPLE));
+        // r1c3 can be 1, 2 or 4, and r1c4 can be 2 or 6
+        assert_eq!(run(&mut repl, "unmark r1c3 1 2"), "r1c3 -1 -2");
+        assert_eq!(run(&mut repl, "mark r1c3 9"), "r1c3 +9");
+        assert_eq!(run(&mut repl, "set r1c4 6"), "r1c4 = 6\nr1c6 -6\nr9c4 -6");
+        assert!(repl.execute("set r1c1 6").is_err());
+        assert!(repl.execute("mark r1c4 1").is_err());
+        // Clearing a cell gives the number back to the cells it sees
+        assert_eq!(
+            run(&mut repl, "clear r1c4"),
+            "r1c4 cleared (was 6)\nr1c6 +6\nr9c4 +6"
+        );
+        let cell = &repl.puzzle.as_ref().unwrap().cells[3];
+        assert_eq!(cell.poss_iter().cloned().collect::<Vec<u8>>(), vec![2, 6]);
+
+        let dump = run(&mut repl, "dump");
+        let code = dump.lines().nth(1).unwrap().trim_start_matches("code ");
+        let mut other = Repl::default();
+        run(&mut other, &format!("load {}", code));
+        assert_eq!(other.puzzle, repl.puzzle);
+
+        assert_eq!(run(&mut repl, "reset"), "r1c3 -9 +1 +2");
+        assert!(repl.execute("quit").unwrap().is_none());
+    }
+}
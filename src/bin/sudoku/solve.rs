@@ -0,0 +1,172 @@
+//! `sudoku solve`: solves each puzzle with the brute force solver.
+use crate::args::Args;
+use crate::input::read_lines;
+use crate::{EXIT_FAILED, EXIT_OK, EXIT_TIMEOUT, EXIT_USAGE};
+use std::time::{Duration, Instant};
+use sudoku::{BruteForce, PuzOutput, Puzzle};
+
+pub const USAGE: &str = "Usage: sudoku solve [OPTIONS] [FILE...]
+
+Solves each puzzle, one per line, from the files or standard input.  Blank lines and lines
+starting with '#' are skipped.  Each solution is printed on its own line.
+
+Options:
+  -n, --max-solutions N  Stop after N solutions of each puzzle (default 1)
+  -t, --timeout SECONDS  Give up on a puzzle after this many seconds
+  -p, --pretty           Print each solution as a grid
+  -h, --help             Print this message
+
+Exit codes:
+  0  Every puzzle was solved
+  1  Invalid arguments or a file could not be read
+  2  A puzzle could not be read or has no solution
+  3  A puzzle timed out";
+
+struct Options {
+    max_solutions: usize,
+    timeout: Option<Duration>,
+    pretty: bool,
+    files: Vec<String>,
+}
+
+fn parse_args(args: &[String]) -> Result<Option<Options>, String> {
+    let mut options = Options {
+        max_solutions: 1,
+        timeout: None,
+        pretty: false,
+        files: Vec::new(),
+    };
+    let mut args = Args::new(args);
+    while let Some(option) = args.next_option()? {
+        match option.as_str() {
+            "-n" | "--max-solutions" => {
+                options.max_solutions = args.parse(&option)?;
+                if options.max_solutions == 0 {
+                    return Err(format!("{} must be at least 1", option));
+                }
+            }
+            "-t" | "--timeout" => {
+                let seconds: f64 = args.parse(&option)?;
+                options.timeout = match Duration::try_from_secs_f64(seconds) {
+                    Ok(timeout) if !timeout.is_zero() => Some(timeout),
+                    _ => return Err(format!("{} must be a positive number of seconds", option)),
+                };
+            }
+            "-p" | "--pretty" => options.pretty = true,
+            "-h" | "--help" => return Ok(None),
+            _ => return Err(format!("Unknown option '{}'", option)),
+        }
+    }
+    options.files = args.positional().to_vec();
+    Ok(Some(options))
+}
+
+/// Solves the puzzle, giving up once the timeout has passed.  Returns `None` if the puzzle timed
+/// out.
+fn solve(mut puz: Puzzle, max: usize, timeout: Option<Duration>) -> Option<Vec<Vec<u8>>> {
+    // A timeout too long to add to the clock is no timeout at all
+    match timeout.and_then(|timeout| Instant::now().checked_add(timeout)) {
+        None => Some(puz.brute_force_solve_max(max)),
+        Some(deadline) => puz.brute_force_solve_until(max, deadline).ok(),
+    }
+}
+
+pub fn run(args: &[String]) -> i32 {
+    let options = match parse_args(args) {
+        Ok(Some(options)) => options,
+        Ok(None) => {
+            println!("{}", USAGE);
+            return EXIT_OK;
+        }
+        Err(e) => {
+            eprintln!("{}\n\n{}", e, USAGE);
+            return EXIT_USAGE;
+        }
+    };
+
+    let mut code = EXIT_OK;
+    for line in read_lines(&options.files) {
+        let line = match line {
+            Ok(line) => line,
+            Err(e) => {
+                eprintln!("{}", e);
+                return EXIT_USAGE;
+            }
+        };
+        let puz = match line.text.parse::<Puzzle>() {
+            Ok(puz) => puz,
+            Err(e) => {
+                eprintln!("{}: {}", line.location(), e);
+                code = code.max(EXIT_FAILED);
+                continue;
+            }
+        };
+        let solutions = match solve(puz, options.max_solutions, options.timeout) {
+            Some(solutions) => solutions,
+            None => {
+                eprintln!("{}: timed out", line.location());
+                code = code.max(EXIT_TIMEOUT);
+                continue;
+            }
+        };
+        if solutions.is_empty() {
+            eprintln!("{}: no solution", line.location());
+            code = code.max(EXIT_FAILED);
+        }
+        for solution in solutions {
+            if options.pretty {
+                let mut grid = Puzzle::new();
+                grid.set_initial(solution);
+                println!("{}", grid);
+            } else {
+                println!("{}", solution.as_string().unwrap_or_default());
+            }
+        }
+    }
+    code
+}
+
+#[cfg(test)]
+mod solve_tests {
+    use super::*;
+
+    fn args(text: &str) -> Vec<String> {
+        text.split_whitespace().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn parse_args_test() {
+        let options = parse_args(&args("-n 2 --timeout=0.5 a.txt -p b.txt"))
+            .unwrap()
+            .unwrap();
+        assert_eq!(options.max_solutions, 2);
+        assert_eq!(options.timeout, Some(Duration::from_millis(500)));
+        assert!(options.pretty);
+        assert_eq!(options.files, vec!["a.txt", "b.txt"]);
+
+        assert!(parse_args(&args("--help")).unwrap().is_none());
+        assert!(parse_args(&args("-n 0")).is_err());
+        assert!(parse_args(&args("--timeout -1")).is_err());
+        assert!(parse_args(&args("--timeout 1e300")).is_err());
+        assert!(parse_args(&args("--timeout 1e-12")).is_err());
+        assert!(parse_args(&args("--unknown")).is_err());
+    }
+
+    #[test]
+    fn solve_test() {
+        let puz: Puzzle =
+            "295743861431865900876192543387459216612387495549216738763524189928671354154938600"
+                .parse()
+                .unwrap();
+        assert_eq!(solve(puz.clone(), 1, None).unwrap().len(), 1);
+        let solutions = solve(puz, 5, Some(Duration::from_secs(10))).unwrap();
+        assert_eq!(solutions.len(), 2);
+
+        let mut empty = Puzzle::new();
+        empty.set_initial(vec![0; 81]);
+        assert_eq!(
+            solve(empty, usize::MAX, Some(Duration::from_millis(10))),
+            None
+        );
+    }
+}
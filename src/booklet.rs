@@ -0,0 +1,294 @@
+//! Printable booklets of puzzles, written as PostScript, with the solutions at the back.
+use crate::*;
+use std::fmt::Write;
+
+const MARGIN: f64 = 36.0;
+const HEADER: f64 = 40.0;
+const LABEL: f64 = 16.0;
+
+/// A booklet of puzzles.  Puzzles are laid out several to a page, each labeled with its number
+/// and the difficulty from its metadata.  The solutions, found using `brute_force_solve`, follow
+/// the puzzles.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Booklet {
+    title: String,
+    per_page: usize,
+    solutions_per_page: usize,
+    page_size: (f64, f64),
+    puzzles: Vec<Puzzle>,
+}
+
+impl Booklet {
+    /// Creates an empty booklet, with 4 puzzles and 9 solutions to a US Letter page.
+    pub fn new(title: &str) -> Booklet {
+        Booklet {
+            title: title.to_string(),
+            per_page: 4,
+            solutions_per_page: 9,
+            page_size: (612.0, 792.0),
+            puzzles: Vec::new(),
+        }
+    }
+
+    pub fn per_page(&mut self, count: usize) -> &mut Self {
+        assert!(count > 0, "At least one puzzle per page");
+        self.per_page = count;
+        self
+    }
+
+    pub fn solutions_per_page(&mut self, count: usize) -> &mut Self {
+        assert!(count > 0, "At least one solution per page");
+        self.solutions_per_page = count;
+        self
+    }
+
+    /// Page width and height in points (1/72 inch).  A4 is 595 x 842.
+    pub fn page_size(&mut self, width: f64, height: f64) -> &mut Self {
+        self.page_size = (width, height);
+        self
+    }
+
+    pub fn add(&mut self, puz: &Puzzle) -> &mut Self {
+        self.puzzles.push(puz.clone());
+        self
+    }
+
+    pub fn puzzles(&self) -> &[Puzzle] {
+        &self.puzzles
+    }
+
+    /// Returns the booklet as a PostScript document.
+    pub fn to_postscript(&self) -> String {
+        let pages = |per_page: usize| self.puzzles.len().div_ceil(per_page);
+        let puzzle_pages = pages(self.per_page);
+        let total = puzzle_pages + pages(self.solutions_per_page);
+
+        let mut out = String::new();
+        let _ = writeln!(out, "%!PS-Adobe-3.0");
+        let _ = writeln!(out, "%%Title: ({})", escape(&self.title));
+        let _ = writeln!(out, "%%Creator: sudoku");
+        let _ = writeln!(out, "%%Pages: {}", total);
+        let _ = writeln!(
+            out,
+            "%%BoundingBox: 0 0 {:.0} {:.0}",
+            self.page_size.0, self.page_size.1
+        );
+        let _ = writeln!(out, "%%EndComments");
+        out.push_str(&prolog());
+
+        let mut page = 0;
+        for chunk in self.puzzles.chunks(self.per_page) {
+            page += 1;
+            self.begin_page(&mut out, page, total, &self.title);
+            for (slot, puz) in chunk.iter().enumerate() {
+                let number = (page - 1) * self.per_page + slot + 1;
+                let values = puz.cells.iter().map(|c| c.num()).collect::<Vec<Element>>();
+                let difficulty = puz.meta().difficulty.clone().unwrap_or_default();
+                let position = self.position(self.per_page, slot);
+                self.draw_puzzle(&mut out, position, number, &difficulty, &values, puz);
+            }
+            let _ = writeln!(out, "showpage");
+        }
+
+        let title = format!("{} - Solutions", self.title);
+        for (chunk_num, chunk) in self.puzzles.chunks(self.solutions_per_page).enumerate() {
+            page += 1;
+            self.begin_page(&mut out, page, total, &title);
+            for (slot, puz) in chunk.iter().enumerate() {
+                let number = chunk_num * self.solutions_per_page + slot + 1;
+                // Two solutions are enough to tell that the solution is not unique
+                let solutions = puz.clone().brute_force_solve_max(2);
+                let (label, values) = match solutions.len() {
+                    0 => ("No solution", puz.cells.iter().map(|c| c.num()).collect()),
+                    1 => ("", solutions[0].clone()),
+                    _ => ("Not unique", solutions[0].clone()),
+                };
+                let position = self.position(self.solutions_per_page, slot);
+                self.draw_puzzle(&mut out, position, number, label, &values, puz);
+            }
+            let _ = writeln!(out, "showpage");
+        }
+        let _ = writeln!(out, "%%EOF");
+        out
+    }
+
+    fn begin_page(&self, out: &mut String, page: usize, total: usize, title: &str) {
+        let (width, height) = self.page_size;
+        let _ = writeln!(out, "%%Page: {} {}", page, page);
+        let _ = writeln!(
+            out,
+            "/Helvetica-Bold 18 selectfont ({}) {:.1} {:.1} ctext",
+            escape(title),
+            width / 2.0,
+            height - MARGIN - 18.0
+        );
+        let _ = writeln!(
+            out,
+            "/Helvetica 10 selectfont (Page {} of {}) {:.1} {:.1} ctext",
+            page,
+            total,
+            width / 2.0,
+            MARGIN / 2.0
+        );
+    }
+
+    /// The lower left corner and size of the grid in a slot on the page, and the top of the slot.
+    fn position(&self, per_page: usize, slot: usize) -> (f64, f64, f64, f64) {
+        let (width, height) = self.page_size;
+        let cols = (1..).find(|c| c * c >= per_page).unwrap_or(1);
+        let rows = per_page.div_ceil(cols);
+        let (slot_w, slot_h) = (
+            (width - 2.0 * MARGIN) / cols as f64,
+            (height - 2.0 * MARGIN - HEADER) / rows as f64,
+        );
+        let size = (slot_w * 0.9).min(slot_h - 2.0 * LABEL);
+        let (col, row) = (slot % cols, slot / cols);
+        let x = MARGIN + col as f64 * slot_w + (slot_w - size) / 2.0;
+        let top = height - MARGIN - HEADER - row as f64 * slot_h;
+        let y = top - LABEL - size;
+        (x, y, size, top)
+    }
+
+    /// Draws one grid and its labels.  Numbers that are givens in `puz` are bold.
+    fn draw_puzzle(
+        &self,
+        out: &mut String,
+        (x, y, size, top): (f64, f64, f64, f64),
+        number: usize,
+        label: &str,
+        values: &[Element],
+        puz: &Puzzle,
+    ) {
+        let _ = writeln!(
+            out,
+            "/Helvetica-Bold 11 selectfont (#{}) {:.1} {:.1} moveto show",
+            number,
+            x,
+            top - LABEL + 4.0
+        );
+        if !label.is_empty() {
+            let _ = writeln!(
+                out,
+                "/Helvetica 11 selectfont ({}) {:.1} {:.1} rtext",
+                escape(label),
+                x + size,
+                top - LABEL + 4.0
+            );
+        }
+        let _ = writeln!(out, "{:.1} {:.1} {:.1} grid", x, y, size);
+
+        let cell = size / MAX_NUM as f64;
+        let font = cell * 0.65;
+        let _ = writeln!(out, "/Helvetica-Bold {:.1} selectfont", font);
+        let mut bold = true;
+        for (index, val) in values.iter().enumerate().filter(|(_, v)| **v != 0) {
+            let given = puz.cells[index].given();
+            if given != bold {
+                let font_name = if given { "Helvetica-Bold" } else { "Helvetica" };
+                let _ = writeln!(out, "/{} {:.1} selectfont", font_name, font);
+                bold = given;
+            }
+            let _ = writeln!(
+                out,
+                "({}) {:.1} {:.1} ctext",
+                symbol(*val),
+                x + (index_to_col(index) as f64 + 0.5) * cell,
+                y + size - (index_to_row(index) as f64 + 0.5) * cell - font * 0.35
+            );
+        }
+    }
+}
+
+/// Procedures used by the pages.  `grid` draws the lines of a puzzle from its lower left corner,
+/// and `ctext` / `rtext` show a string centered on / ending at a point.
+fn prolog() -> String {
+    format!(
+        "%%BeginProlog
+/grid {{
+  /gs exch def /gy exch def /gx exch def
+  /gc gs {n} div def
+  0 1 {n} {{
+    /gi exch def
+    gi {b} mod 0 eq {{ 2 }} {{ 0.5 }} ifelse setlinewidth
+    newpath gx gi gc mul add gy moveto 0 gs rlineto stroke
+    newpath gx gy gi gc mul add moveto gs 0 rlineto stroke
+  }} for
+}} bind def
+/ctext {{
+  /ty exch def /tx exch def
+  dup stringwidth pop 2 div tx exch sub ty moveto show
+}} bind def
+/rtext {{
+  /ty exch def /tx exch def
+  dup stringwidth pop tx exch sub ty moveto show
+}} bind def
+%%EndProlog
+",
+        n = MAX_NUM,
+        b = BOX_DIMEN
+    )
+}
+
+/// Escapes a PostScript string.  Characters outside of printable ASCII are replaced with `?`.
+fn escape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '(' | ')' | '\\' => {
+                out.push('\\');
+                out.push(c);
+            }
+            ' '..='~' => out.push(c),
+            _ => out.push('?'),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod booklet_tests {
+    use super::*;
+
+    const EXAMPLE: &str =
+        "53..7....6..195....98....6.8...6...34..8.3..17...2...6.6....28....419..5....8..79";
+
+    #[test]
+    fn layout_test() {
+        let mut puz: Puzzle = EXAMPLE.parse().unwrap();
+        puz.meta_mut().difficulty = Some("Easy (1)".to_string());
+        let mut booklet = Booklet::new("Weekly Puzzles");
+        for _ in 0..5 {
+            booklet.add(&puz);
+        }
+        let ps = booklet.per_page(4).solutions_per_page(6).to_postscript();
+
+        assert!(ps.starts_with("%!PS-Adobe-3.0\n"));
+        assert!(ps.ends_with("%%EOF\n"));
+        // Two pages of puzzles and one of solutions
+        assert!(ps.contains("%%Pages: 3\n"));
+        assert_eq!(ps.matches("%%Page: ").count(), 3);
+        assert_eq!(ps.matches("showpage").count(), 3);
+        assert_eq!(ps.matches(" grid\n").count(), 10);
+        assert!(ps.contains("(Weekly Puzzles - Solutions)"));
+        assert!(ps.contains("(#5)"));
+        assert!(ps.contains("(Easy \\(1\\))"));
+        assert!(ps.contains("(Page 3 of 3)"));
+
+        // 30 givens in each of the puzzles, and 81 numbers in each of the solutions, plus the
+        // title and page number of each page
+        assert_eq!(ps.matches(" ctext\n").count(), 5 * 30 + 5 * 81 + 3 * 2);
+    }
+
+    #[test]
+    fn solution_label_test() {
+        let mut booklet = Booklet::new("Test");
+        let puz: Puzzle =
+            "295743861431865900876192543387459216612387495549216738763524189928671354154938600"
+                .parse()
+                .unwrap();
+        booklet.add(&puz);
+        let ps = booklet.to_postscript();
+        assert!(ps.contains("(Not unique)"));
+        assert_eq!(escape("a\\b(é)"), "a\\\\b\\(?\\)");
+    }
+}
@@ -1,14 +1,46 @@
 use crate::*;
+use std::time::Instant;
+
+/// How many steps the search takes between looking at the clock.
+const DEADLINE_STEPS: usize = 4096;
 
 /// Brute force solving.  Returns multiple solutions if they exist
 pub trait BruteForce {
     fn brute_force_solve(&mut self) -> Vec<Vec<Element>>;
+
+    /// Same as `brute_force_solve`, but stops once `max` solutions have been found.
+    fn brute_force_solve_max(&mut self, max: usize) -> Vec<Vec<Element>>;
 }
 
 impl BruteForce for Puzzle {
     /// Solves the Sudoku puzzle.  Returns a vector of 1-D vectors.  Each 1-D vector represents a
     /// solution of the sudoku puzzle.  If no solution exists, the vector will be empty.
     fn brute_force_solve(&mut self) -> Vec<Vec<Element>> {
+        self.brute_force_solve_max(usize::MAX)
+    }
+
+    fn brute_force_solve_max(&mut self, max: usize) -> Vec<Vec<Element>> {
+        self.search(max, None).unwrap_or_default()
+    }
+}
+
+impl Puzzle {
+    /// Same as `brute_force_solve_max`, but gives up once the deadline has passed.  Returns
+    /// `SudError::TimedOut` if the search did not finish in time.
+    pub fn brute_force_solve_until(
+        &mut self,
+        max: usize,
+        deadline: Instant,
+    ) -> Result<Vec<Vec<Element>>, SudError> {
+        self.search(max, Some(deadline))
+    }
+
+    /// The brute force search, which gives up once the deadline, if there is one, has passed.
+    pub(crate) fn search(
+        &mut self,
+        max: usize,
+        deadline: Option<Instant>,
+    ) -> Result<Vec<Vec<Element>>, SudError> {
         fn move_cursor_right(puz: &Puzzle, cursor: usize) -> Option<usize> {
             let mut cur = cursor;
             loop {
@@ -24,6 +56,9 @@ impl BruteForce for Puzzle {
         }
 
         let mut position: usize = 0;
+        // Counts the steps of the search, so the clock is only read every so often, starting with
+        // the first step
+        let mut steps: usize = DEADLINE_STEPS - 1;
         // Stores solutions
         let mut to_return: Vec<Vec<Element>> = Vec::new();
 
@@ -41,16 +76,28 @@ impl BruteForce for Puzzle {
         // This loop increments, checks, determine if solved, and adjust the backmarker to check
         // for additional solutions.
         'solving: loop {
-            // check valid
+            steps += 1;
+            if steps == DEADLINE_STEPS {
+                steps = 0;
+                if matches!(deadline, Some(d) if Instant::now() >= d) {
+                    self.cells.iter_mut().for_each(|c| c.reset());
+                    return Err(SudError::TimedOut);
+                }
+            }
 
-            if self.valid_entry(position) {
+            // check valid.  An empty cell is never valid, whether it is the first cell, which has
+            // not been incremented yet, or a cell with no candidates left.
+            if self.cells[position].num() != 0 && self.valid_entry(position) {
                 // if valid, check solved
                 if position == NUM_CELLS - 1 {
                     //dbg!(usize::from(position), back_marker);
                     // Copy cell numbers into a new vector to be added to the solutions.
                     let solution: Vec<Element> = self.cells.iter().map(|c| c.num()).collect();
                     to_return.push(solution);
-
+                    if to_return.len() >= max {
+                        self.cells.iter_mut().for_each(|c| c.reset());
+                        break 'solving;
+                    }
 
                     // reset all after backmarker
                     while position > back_marker {
@@ -58,7 +105,7 @@ impl BruteForce for Puzzle {
                         self.cells[position].reset();
                         position = match position.checked_sub(1) {
                             Some(v) => v,
-                            None => return to_return,
+                            None => return Ok(to_return),
                         }
                     }
                     assert_eq!(position, back_marker);
@@ -103,7 +150,7 @@ impl BruteForce for Puzzle {
                 }
             }
         }
-        to_return
+        Ok(to_return)
     }
 }
 
@@ -205,6 +252,31 @@ pub mod brute_unit {
         }
     }
 
+    #[test]
+    fn max_solutions_test() {
+        let mut puz: Puzzle =
+            "295743861431865900876192543387459216612387495549216738763524189928671354154938600"
+                .parse()
+                .unwrap();
+        assert_eq!(puz.brute_force_solve_max(1).len(), 1);
+        // Stopping early leaves the puzzle as it was
+        assert_eq!(puz.cells[16].num(), 0);
+        assert_eq!(puz.brute_force_solve_max(5).len(), 2);
+        assert_eq!(puz.brute_force_solve().len(), 2);
+    }
+
+    #[test]
+    fn one_empty_cell_test() {
+        // The empty cell has no other empty cells in its row, column or box
+        let mut puz: Puzzle =
+            "534678912672195348198342567859761423426.53791713924856961537284287419635345286179"
+                .parse()
+                .unwrap();
+        let solutions = puz.brute_force_solve();
+        assert_eq!(solutions.len(), 1);
+        assert_eq!(solutions[0][40], 5);
+    }
+
     #[test]
     fn oh_no_test() {
         let example: Vec<Vec<Element>> = vec![
@@ -247,4 +319,20 @@ pub mod brute_unit {
         // used https://www.thonky.com/sudoku/solution-count to verify solution count
         assert!(res.len() == 192);
     }
+
+    #[test]
+    fn deadline_test() {
+        // An empty grid has far too many solutions to find them all
+        let mut puz = Puzzle::new();
+        puz.set_initial(vec![0; NUM_CELLS]);
+        assert_eq!(
+            puz.brute_force_solve_until(usize::MAX, Instant::now()),
+            Err(SudError::TimedOut)
+        );
+        assert!(puz.cells.iter().all(|c| c.num() == 0));
+
+        let later = Instant::now() + std::time::Duration::from_secs(60);
+        let solutions = puz.brute_force_solve_until(2, later).unwrap();
+        assert_eq!(solutions.len(), 2);
+    }
 }
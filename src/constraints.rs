@@ -0,0 +1,578 @@
+//! Variant rules that add restrictions on top of the standard row / column / box rules.
+use crate::*;
+
+/// A variant rule that is attached to a puzzle using `Puzzle::add_constraint`.  Constraints are
+/// checked by `valid_entry` (and therefore the brute force solver), and are used to eliminate
+/// pencil marks.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Constraint {
+    /// No two orthogonally adjacent cells in the puzzle may contain consecutive numbers.
+    NonConsecutive,
+    /// Kropki white dot.  The two adjacent cells contain consecutive numbers.
+    WhiteDot(usize, usize),
+    /// Kropki black dot.  One of the two adjacent cells is double the other.
+    BlackDot(usize, usize),
+    /// Negative constraint for white dots: all white dots are given, so adjacent cells that have
+    /// no dot between them cannot contain consecutive numbers.
+    NegativeWhite,
+    /// Negative constraint for black dots: all black dots are given, so adjacent cells that have
+    /// no dot between them cannot have one be double the other.
+    NegativeBlack,
+    /// Thermometer.  Starting at the bulb (first cell), the numbers strictly increase along the path.
+    Thermo(Vec<usize>),
+    /// The number in the circle is the sum of the numbers along the arrow's path.  Numbers may
+    /// repeat along the path as long as they are not in the same row, column, or box.
+    Arrow { circle: usize, path: Vec<usize> },
+    /// A clue written outside of the grid.
+    Outer(OuterClue),
+    /// The cell contains an odd number.
+    Odd(usize),
+    /// The cell contains an even number.
+    Even(usize),
+    /// Greater-than sign between two adjacent cells.  The first cell is larger than the second.
+    GreaterThan(usize, usize),
+}
+
+/// How the value of a cell relates to the value of another cell.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Relation {
+    Consecutive,
+    Double,
+    NotConsecutive,
+    NotDouble,
+    Greater,
+    Less,
+}
+
+impl Relation {
+    fn allows(self, first: Element, second: Element) -> bool {
+        let consecutive = first + 1 == second || second + 1 == first;
+        let double = first * 2 == second || second * 2 == first;
+        match self {
+            Relation::Consecutive => consecutive,
+            Relation::Double => double,
+            Relation::NotConsecutive => !consecutive,
+            Relation::NotDouble => !double,
+            Relation::Greater => first > second,
+            Relation::Less => first < second,
+        }
+    }
+}
+
+impl Constraint {
+    /// Checks the constraint against the cell at `index` and the cells it is related to.  Empty
+    /// cells (a value of `0`) are ignored, so a partially filled puzzle is valid as long as the
+    /// filled in cells do not break the constraint.
+    pub fn satisfied(&self, puz: &Puzzle, index: usize) -> bool {
+        let val = puz.cells[index].num();
+        if val == 0 {
+            return true;
+        }
+        match self {
+            Constraint::Odd(_) | Constraint::Even(_) => self.allows(index, val),
+            Constraint::Thermo(path) => !path.contains(&index) || thermo_satisfied(puz, path),
+            Constraint::Arrow { circle, path } => {
+                (index != *circle && !path.contains(&index)) || arrow_satisfied(puz, *circle, path)
+            }
+            Constraint::Outer(clue) => {
+                let cells = clue.cells();
+                !cells.contains(&index)
+                    || clue.line_satisfied(
+                        &cells
+                            .iter()
+                            .map(|i| puz.cells[*i].num())
+                            .collect::<Vec<Element>>(),
+                    )
+            }
+            _ => self.pairs_with(puz, index).iter().all(|(other, rel)| {
+                let other_val = puz.cells[*other].num();
+                other_val == 0 || rel.allows(val, other_val)
+            }),
+        }
+    }
+
+    /// True if the constraint allows the cell at `index` to contain `val`, without considering
+    /// any other cells.
+    pub fn allows(&self, index: usize, val: Element) -> bool {
+        match self {
+            Constraint::Odd(i) if *i == index => !val.is_multiple_of(2),
+            Constraint::Even(i) if *i == index => val.is_multiple_of(2),
+            _ => true,
+        }
+    }
+
+    /// The cells related to `index` by this constraint, along with how `index` relates to them.
+    fn pairs_with(&self, puz: &Puzzle, index: usize) -> Vec<(usize, Relation)> {
+        let dot = |first: usize, second: usize, rel: Relation| {
+            if first == index {
+                vec![(second, rel)]
+            } else if second == index {
+                vec![(first, rel)]
+            } else {
+                vec![]
+            }
+        };
+
+        match self {
+            Constraint::NonConsecutive => orthogonal_neighbors(index)
+                .into_iter()
+                .map(|n| (n, Relation::NotConsecutive))
+                .collect(),
+            Constraint::WhiteDot(first, second) => dot(*first, *second, Relation::Consecutive),
+            Constraint::BlackDot(first, second) => dot(*first, *second, Relation::Double),
+            Constraint::GreaterThan(first, second) if *first == index => {
+                vec![(*second, Relation::Greater)]
+            }
+            Constraint::GreaterThan(first, second) if *second == index => {
+                vec![(*first, Relation::Less)]
+            }
+            Constraint::NegativeWhite => orthogonal_neighbors(index)
+                .into_iter()
+                .filter(|n| !has_dot(puz, index, *n))
+                .map(|n| (n, Relation::NotConsecutive))
+                .collect(),
+            Constraint::NegativeBlack => orthogonal_neighbors(index)
+                .into_iter()
+                .filter(|n| !has_dot(puz, index, *n))
+                .map(|n| (n, Relation::NotDouble))
+                .collect(),
+            // A greater-than sign that does not touch `index`.  The rest are not pairwise and are
+            // handled separately.
+            Constraint::GreaterThan(..)
+            | Constraint::Odd(_)
+            | Constraint::Even(_)
+            | Constraint::Thermo(_)
+            | Constraint::Arrow { .. }
+            | Constraint::Outer(_) => Vec::new(),
+        }
+    }
+
+    /// Finds pencil marks that cannot be part of a solution due to this constraint.
+    /// Returns a vector of tuples of (index value, eliminated number).
+    fn prune(&self, puz: &Puzzle) -> Vec<(usize, Element)> {
+        match self {
+            Constraint::Thermo(path) => prune_thermo(puz, path),
+            Constraint::Arrow { circle, path } => prune_arrow(puz, *circle, path),
+            Constraint::Outer(clue) => clue.prune(puz),
+            Constraint::Odd(index) | Constraint::Even(index) if puz.cells[*index].num() == 0 => puz
+                .cells[*index]
+                .poss_iter()
+                .filter(|v| !self.allows(*index, **v))
+                .map(|v| (*index, *v))
+                .collect(),
+            _ => self.prune_pairs(puz),
+        }
+    }
+
+    /// A pencil mark is eliminated when no possible value of a related cell is compatible with it.
+    fn prune_pairs(&self, puz: &Puzzle) -> Vec<(usize, Element)> {
+        let mut elims: Vec<(usize, Element)> = Vec::new();
+        for index in (0..NUM_CELLS).filter(|i| puz.cells[*i].num() == 0) {
+            for (other, rel) in self.pairs_with(puz, index) {
+                let other_vals = domain(puz, other);
+                for val in puz.cells[index].poss_iter() {
+                    if !other_vals.iter().any(|o| rel.allows(*val, *o)) {
+                        elims.push((index, *val));
+                    }
+                }
+            }
+        }
+        elims
+    }
+}
+
+/// The values a cell could be.  Either its number if it is filled in, or its pencil marks.
+pub(crate) fn domain(puz: &Puzzle, index: usize) -> Vec<Element> {
+    match puz.cells[index].num() {
+        0 => puz.cells[index].poss_iter().cloned().collect(),
+        v => vec![v],
+    }
+}
+
+/// Smallest and largest values a cell could be.  A cell without any possible values gives a
+/// range that no value can fit in.
+fn bounds(puz: &Puzzle, index: usize) -> (usize, usize) {
+    let vals = domain(puz, index);
+    match (vals.iter().min(), vals.iter().max()) {
+        (Some(min), Some(max)) => (*min as usize, *max as usize),
+        _ => (MAX_NUM + 1, 0),
+    }
+}
+
+/// Eliminates the pencil marks of an empty cell that are outside of `low..=high`.
+pub(crate) fn prune_outside(
+    puz: &Puzzle,
+    index: usize,
+    low: isize,
+    high: isize,
+) -> Vec<(usize, Element)> {
+    if puz.cells[index].num() != 0 {
+        return Vec::new();
+    }
+    puz.cells[index]
+        .poss_iter()
+        .filter(|v| (**v as isize) < low || (**v as isize) > high)
+        .map(|v| (index, *v))
+        .collect()
+}
+
+/// Each cell on a thermometer has to be at least one more than the cell before it, and at least
+/// one less than the cell after it.
+fn prune_thermo(puz: &Puzzle, path: &[usize]) -> Vec<(usize, Element)> {
+    let limits = path
+        .iter()
+        .map(|i| bounds(puz, *i))
+        .collect::<Vec<(usize, usize)>>();
+
+    let mut low = limits.iter().map(|(min, _)| *min).collect::<Vec<usize>>();
+    let mut high = limits.iter().map(|(_, max)| *max).collect::<Vec<usize>>();
+    for step in 1..path.len() {
+        low[step] = low[step].max(low[step - 1] + 1);
+    }
+    for step in (0..path.len().saturating_sub(1)).rev() {
+        // Avoids underflow when the following cell has no possible values
+        high[step] = high[step].min(high[step + 1].saturating_sub(1));
+    }
+
+    path.iter()
+        .enumerate()
+        .flat_map(|(step, i)| prune_outside(puz, *i, low[step] as isize, high[step] as isize))
+        .collect()
+}
+
+fn thermo_satisfied(puz: &Puzzle, path: &[usize]) -> bool {
+    let filled = path
+        .iter()
+        .enumerate()
+        .map(|(step, i)| (step, puz.cells[*i].num() as usize))
+        .filter(|(_, v)| *v != 0)
+        .collect::<Vec<(usize, usize)>>();
+
+    // There has to be enough room before and after each number for the rest of the thermometer
+    filled
+        .iter()
+        .all(|(step, v)| *v > *step && *v + (path.len() - step) <= MAX_NUM + 1)
+        && filled
+            .windows(2)
+            .all(|pair| pair[1].1 >= pair[0].1 + (pair[1].0 - pair[0].0))
+}
+
+/// Eliminates pencil marks of cells that cannot add up to a total between `total_low` and
+/// `total_high`, given the possible values of the other cells.
+pub(crate) fn prune_sum(
+    puz: &Puzzle,
+    cells: &[usize],
+    total_low: usize,
+    total_high: usize,
+) -> Vec<(usize, Element)> {
+    let limits = cells
+        .iter()
+        .map(|i| bounds(puz, *i))
+        .collect::<Vec<(usize, usize)>>();
+    let sum_min: usize = limits.iter().map(|(min, _)| min).sum();
+    let sum_max: usize = limits.iter().map(|(_, max)| max).sum();
+
+    cells
+        .iter()
+        .zip(limits.iter())
+        .flat_map(|(i, (min, max))| {
+            let low = total_low as isize - (sum_max - max) as isize;
+            let high = total_high as isize - (sum_min - min) as isize;
+            prune_outside(puz, *i, low, high)
+        })
+        .collect()
+}
+
+/// The circle is between the smallest and largest sums of the path, and the path cells must be
+/// able to add up to the circle.
+fn prune_arrow(puz: &Puzzle, circle: usize, path: &[usize]) -> Vec<(usize, Element)> {
+    let sum_min: usize = path.iter().map(|i| bounds(puz, *i).0).sum();
+    let sum_max: usize = path.iter().map(|i| bounds(puz, *i).1).sum();
+    let (circle_min, circle_max) = bounds(puz, circle);
+
+    let mut elims = prune_outside(puz, circle, sum_min as isize, sum_max as isize);
+    elims.append(&mut prune_sum(puz, path, circle_min, circle_max));
+    elims
+}
+
+fn arrow_satisfied(puz: &Puzzle, circle: usize, path: &[usize]) -> bool {
+    let sum: usize = path.iter().map(|i| puz.cells[*i].num() as usize).sum();
+    let empty = path.iter().filter(|i| puz.cells[**i].num() == 0).count();
+    match puz.cells[circle].num() as usize {
+        // Every empty cell on the path adds at least 1
+        0 => sum + empty <= MAX_NUM,
+        total if empty == 0 => sum == total,
+        total => sum + empty <= total,
+    }
+}
+
+/// True if there is a Kropki dot of either color between the two cells.
+fn has_dot(puz: &Puzzle, first: usize, second: usize) -> bool {
+    puz.constraints().iter().any(|c| match c {
+        Constraint::WhiteDot(a, b) | Constraint::BlackDot(a, b) => {
+            (*a == first && *b == second) || (*a == second && *b == first)
+        }
+        _ => false,
+    })
+}
+
+impl Puzzle {
+    /// Adds a variant constraint to the puzzle and eliminates any pencil marks that it rules out.
+    /// Panics if a Kropki dot or greater-than sign is placed between cells that are not
+    /// orthogonally adjacent, or an outer clue does not fit the grid.
+    pub fn add_constraint(&mut self, constraint: Constraint) -> &mut Self {
+        match &constraint {
+            Constraint::WhiteDot(a, b)
+            | Constraint::BlackDot(a, b)
+            | Constraint::GreaterThan(a, b) => {
+                assert!(
+                    are_adjacent(*a, *b),
+                    "cells {} and {} are not adjacent",
+                    a,
+                    b
+                )
+            }
+            Constraint::Outer(clue) => clue.assert_valid(),
+            _ => (),
+        }
+        self.constraints.push(constraint);
+        self.apply_constraints();
+        self
+    }
+
+    pub fn constraints(&self) -> &[Constraint] {
+        &self.constraints
+    }
+
+    /// Removes pencil marks that break one of the puzzle's constraints.  Repeats until no more
+    /// marks can be removed, since one elimination may lead to another.
+    /// Returns a vector of tuples of (index value, eliminated number).
+    pub fn apply_constraints(&mut self) -> Vec<(usize, Element)> {
+        let mut eliminated: Vec<(usize, Element)> = Vec::new();
+        loop {
+            let found = self
+                .constraints
+                .iter()
+                .flat_map(|c| c.prune(self))
+                .collect::<Vec<(usize, Element)>>();
+
+            let initial_size = eliminated.len();
+            for (ind, val) in found {
+                if self.cells[ind].remove_possible(val) {
+                    eliminated.push((ind, val));
+                }
+            }
+            if initial_size == eliminated.len() {
+                break;
+            }
+        }
+        eliminated
+    }
+}
+
+#[cfg(test)]
+mod constraint_tests {
+    use super::*;
+
+    // A solution where no two adjacent cells are consecutive
+    const NON_CONSEC: &str =
+        "135247968792683514468159372624971835381524697957368241573816429816492753249735186";
+
+    const SOLUTION: &str =
+        "534678912672195348198342567859761423426853791713924856961537284287419635345286179";
+
+    #[test]
+    fn non_consecutive_test() {
+        // Blank out every other cell
+        let puz_str = NON_CONSEC
+            .chars()
+            .enumerate()
+            .map(|(i, c)| if i % 2 == 0 { '.' } else { c })
+            .collect::<String>();
+
+        let mut puz = Puzzle::new();
+        puz.add_constraint(Constraint::NonConsecutive)
+            .set_initial(puz_str.as_str().as_input().unwrap());
+
+        // Cell 0 is next to a 3 and a 7
+        assert!(!puz.cells[0].is_possible(2));
+        assert!(!puz.cells[0].is_possible(4));
+        assert!(!puz.cells[0].is_possible(6));
+        assert!(!puz.cells[0].is_possible(8));
+
+        let res = puz.brute_force_solve();
+        assert_eq!(res.len(), 1);
+        assert_eq!(res[0], NON_CONSEC.as_input().unwrap());
+    }
+
+    #[test]
+    fn valid_entry_test() {
+        let mut puz = Puzzle::new();
+        puz.add_constraint(Constraint::NonConsecutive)
+            .add_constraint(Constraint::BlackDot(40, 41));
+        puz.cells[40].set(4);
+        puz.cells[31].set(5);
+        assert!(!puz.valid_entry(40));
+        puz.cells[31].set(7);
+        assert!(puz.valid_entry(40));
+        puz.cells[41].set(2);
+        assert!(puz.valid_entry(40));
+        puz.cells[41].set(9);
+        assert!(!puz.valid_entry(41));
+    }
+
+    #[test]
+    fn kropki_test() {
+        let solution = SOLUTION.as_input().unwrap();
+        let mut puz = Puzzle::new();
+        for index in 0..NUM_CELLS {
+            for other in orthogonal_neighbors(index).into_iter().filter(|o| *o > index) {
+                let (a, b) = (solution[index], solution[other]);
+                if a + 1 == b || b + 1 == a {
+                    puz.add_constraint(Constraint::WhiteDot(index, other));
+                } else if a * 2 == b || b * 2 == a {
+                    puz.add_constraint(Constraint::BlackDot(index, other));
+                }
+            }
+        }
+        puz.add_constraint(Constraint::NegativeWhite)
+            .add_constraint(Constraint::NegativeBlack);
+
+        // Only keep the first three rows
+        let givens = solution
+            .iter()
+            .enumerate()
+            .map(|(i, v)| if i < 27 { *v } else { 0 })
+            .collect::<Vec<Element>>();
+        puz.set_initial(givens);
+
+        // Cells 27 and 28 have no dot between them and the 1 and 9 above them
+        assert!(!puz.cells[27].is_possible(2));
+        assert!(!puz.cells[28].is_possible(8));
+
+        let res = puz.brute_force_solve();
+        assert_eq!(res.len(), 1);
+        assert_eq!(res[0], solution);
+    }
+
+    fn two_solutions() -> Puzzle {
+        let mut puz = Puzzle::new();
+        let str =
+            "295743861431865900876192543387459216612387495549216738763524189928671354154938600";
+        puz.set_initial(str.as_input().unwrap());
+        puz
+    }
+
+    #[test]
+    fn thermo_test() {
+        let mut puz = Puzzle::new();
+        puz.set_initial(vec![0; NUM_CELLS]);
+        puz.add_constraint(Constraint::Thermo(vec![1, 2, 3, 4, 5, 6]));
+        assert_eq!(
+            puz.cells[1].poss_iter().cloned().collect::<Vec<Element>>(),
+            vec![1, 2, 3, 4]
+        );
+        assert_eq!(
+            puz.cells[6].poss_iter().cloned().collect::<Vec<Element>>(),
+            vec![6, 7, 8, 9]
+        );
+
+        // The bulb is at the end of row 1, so the larger number goes to its left.
+        let mut puz = two_solutions();
+        puz.add_constraint(Constraint::Thermo(vec![get_cell(1, 8), get_cell(1, 7)]));
+        assert!(!puz.cells[get_cell(1, 8)].is_possible(7));
+
+        let res = puz.brute_force_solve();
+        assert_eq!(res.len(), 1);
+        assert_eq!(res[0][get_cell(1, 7)], 7);
+        assert_eq!(res[0][get_cell(1, 8)], 2);
+        assert!(!Puzzle::new()
+            .add_constraint(Constraint::Thermo(vec![1, 2, 3]))
+            .set_initial(res[0].clone())
+            .is_solved());
+    }
+
+    #[test]
+    fn arrow_test() {
+        let mut puz = Puzzle::new();
+        puz.set_initial(vec![0; NUM_CELLS]);
+        puz.add_constraint(Constraint::Arrow {
+            circle: 45,
+            path: vec![46, 55],
+        });
+        assert!(!puz.cells[45].is_possible(1));
+        assert!(!puz.cells[46].is_possible(9));
+        assert!(!puz.cells[55].is_possible(9));
+
+        // The circle is a 5, so the cell on the arrow path next to the 3 has to be a 2
+        let mut puz = two_solutions();
+        puz.add_constraint(Constraint::Arrow {
+            circle: 24,
+            path: vec![16, 26],
+        });
+        assert_eq!(
+            puz.cells[16].poss_iter().cloned().collect::<Vec<Element>>(),
+            vec![2]
+        );
+
+        let res = puz.brute_force_solve();
+        assert_eq!(res.len(), 1);
+        assert_eq!(res[0][16], 2);
+        assert_eq!(res[0][17], 7);
+    }
+
+    #[test]
+    fn odd_even_test() {
+        let mut puz = Puzzle::new();
+        puz.add_constraint(Constraint::Odd(0))
+            .add_constraint(Constraint::Even(1))
+            .set_initial(vec![0; NUM_CELLS]);
+        assert_eq!(
+            puz.cells[0].poss_iter().cloned().collect::<Vec<Element>>(),
+            vec![1, 3, 5, 7, 9]
+        );
+        assert_eq!(
+            puz.cells[1].poss_iter().cloned().collect::<Vec<Element>>(),
+            vec![2, 4, 6, 8]
+        );
+
+        let mut puz = Puzzle::new();
+        puz.add_constraint(Constraint::Even(16))
+            .set_initial(two_solutions().cells.iter().map(|c| c.num()).collect());
+        assert_eq!(
+            puz.cells[16].poss_iter().cloned().collect::<Vec<Element>>(),
+            vec![2]
+        );
+        let res = puz.brute_force_solve();
+        assert_eq!(res.len(), 1);
+        assert_eq!(res[0][16], 2);
+    }
+
+    #[test]
+    fn greater_than_test() {
+        let mut puz = Puzzle::new();
+        puz.set_initial(vec![0; NUM_CELLS]);
+        puz.add_constraint(Constraint::GreaterThan(0, 1))
+            .add_constraint(Constraint::GreaterThan(1, 2));
+        assert!(puz.cells[0].poss_iter().all(|v| *v >= 3));
+        assert!(puz.cells[1].poss_iter().all(|v| *v >= 2 && *v <= 8));
+        assert!(puz.cells[2].poss_iter().all(|v| *v <= 7));
+
+        let mut puz = two_solutions();
+        puz.add_constraint(Constraint::GreaterThan(16, 17));
+        let res = puz.brute_force_solve();
+        assert_eq!(res.len(), 1);
+        assert_eq!(res[0][16], 7);
+
+        puz.cells[16].set(2);
+        puz.cells[17].set(7);
+        assert!(!puz.valid_entry(16));
+    }
+
+    #[test]
+    #[should_panic]
+    fn non_adjacent_dot_test() {
+        Puzzle::new().add_constraint(Constraint::WhiteDot(8, 9));
+    }
+}
@@ -1,39 +1,83 @@
 use crate::errors::SudError;
 use crate::errors::SudError::OutputParse;
-use crate::{Element, MAX_NUM, NUM_CELLS};
+use crate::{Alphabet, Element, Metadata, Puzzle, BOX_DIMEN, MAX_NUM, NUM_CELLS};
+use std::fmt;
+use std::str::FromStr;
+
+/// How forgiving to be when parsing puzzle input.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ParseMode {
+    /// Any character that is not a number is treated as an empty cell.  Line breaks are skipped.
+    Lenient,
+    /// Only numbers and `.`, `0`, or `_` for empty cells are accepted.  Whitespace and grid lines
+    /// (`|`, `-`, `+`, `=`) are skipped, and any other character is an error.  The givens are
+    /// also checked to make sure none are repeated in a row, column, or box.
+    Strict,
+}
 
 /// This is used to generate the vector to initially set the puzzle, along with convert the vector to desired output
 pub trait PuzInput {
+    /// Same as `as_input_mode` using `ParseMode::Lenient`
     fn as_input(&self) -> Result<Vec<Element>, SudError>;
+
+    fn as_input_mode(&self, mode: ParseMode) -> Result<Vec<Element>, SudError> {
+        let input = self.as_input()?;
+        if mode == ParseMode::Strict {
+            check_givens(&input)?;
+        }
+        Ok(input)
+    }
 }
 
 impl PuzInput for Vec<Vec<Element>> {
     // Covert a 2-D vector to 1-D
     fn as_input(&self) -> Result<Vec<Element>, SudError> {
+        for (row, vals) in self.iter().enumerate() {
+            if let Some(col) = vals.iter().position(|v| *v as usize > MAX_NUM) {
+                return Err(SudError::OutOfRange {
+                    line: row + 1,
+                    column: col + 1,
+                    value: vals[col] as usize,
+                });
+            }
+        }
+
         let one: Vec<Element> = self.iter().flatten().cloned().collect();
         if one.len() == NUM_CELLS {
             Ok(one)
         } else {
-            Err(SudError::InputParse)
+            Err(SudError::WrongLength {
+                expected: NUM_CELLS,
+                found: one.len(),
+            })
         }
     }
 }
 
-/// This only works when the puzzle size is the normal size of 9x9 or less, as each digit is parsed.
-/// and assumes a base 10 number.  If using a larger puzzle, use other methods to develop the input.
+/// Reads one symbol per cell using the default `Alphabet`, which is the digits 1 to 9 for a 9x9
+/// grid.  Use `Alphabet::parse` for other symbols.
 impl PuzInput for &str {
     fn as_input(&self) -> Result<Vec<Element>, SudError> {
-        let radix = 10;
-        let v = self
-            .chars()
-            .into_iter()
-            .map(|n| n.to_digit(radix).unwrap_or(0) as Element)
-            .collect::<Vec<Element>>();
-        if v.len() == NUM_CELLS {
-            Ok(v)
-        } else {
-            Err(SudError::InputParse)
-        }
+        self.as_input_mode(ParseMode::Lenient)
+    }
+
+    fn as_input_mode(&self, mode: ParseMode) -> Result<Vec<Element>, SudError> {
+        Alphabet::default().parse(self, mode)
+    }
+}
+
+/// Makes sure that no given is repeated in its row, column, or box.
+pub fn check_givens(input: &[Element]) -> Result<(), SudError> {
+    let mut puz = Puzzle::new();
+    for (i, val) in input.iter().enumerate().filter(|(_, v)| **v != 0) {
+        puz.cells[i].set_cell_initial(*val);
+    }
+    match (0..NUM_CELLS).find(|i| input[*i] != 0 && !puz.valid_entry(*i)) {
+        Some(index) => Err(SudError::ConflictingGivens {
+            index,
+            value: input[index],
+        }),
+        None => Ok(()),
     }
 }
 
@@ -44,6 +88,11 @@ pub trait PuzOutput {
     /// display as `.`
     fn as_string(&self) -> Result<String, SudError>;
 
+    /// Same as `as_string`, using the symbols of the alphabet.
+    fn as_string_with(&self, alphabet: &Alphabet) -> Result<String, SudError> {
+        alphabet.format(&self.as_2d_vec()?.concat())
+    }
+
     /// Conver 1-D vector into 2-D array of type Element.  If there is an unsolved cell, it would
     /// display as `0`
     fn as_2d_vec(&self) -> Result<Vec<Vec<Element>>, SudError>;
@@ -51,23 +100,11 @@ pub trait PuzOutput {
 
 impl PuzOutput for Vec<Element> {
     fn as_string(&self) -> Result<String, SudError> {
-        let radix = 10;
-        let str = self
-            .iter()
-            .map(|dig| {
-                if *dig == 0 {
-                    '.'
-                } else {
-                    std::char::from_digit(*dig as u32, radix).unwrap()
-                }
-            })
-            .collect::<String>();
+        self.as_string_with(&Alphabet::default())
+    }
 
-        if str.len() == NUM_CELLS {
-            Ok(str)
-        } else {
-            Err(SudError::OutputParse)
-        }
+    fn as_string_with(&self, alphabet: &Alphabet) -> Result<String, SudError> {
+        alphabet.format(self)
     }
 
     fn as_2d_vec(&self) -> Result<Vec<Vec<Element>>, SudError> {
@@ -88,6 +125,394 @@ impl PuzOutput for Vec<Element> {
     }
 }
 
+/// Characters used to draw grid lines, which are skipped when parsing a puzzle.
+pub(crate) fn is_decoration(c: char) -> bool {
+    c.is_whitespace() || c == '|' || c == '-' || c == '+' || c == '='
+}
+
+/// Parses a puzzle from a string using `ParseMode::Strict`.  The string can either be in the
+/// single line format, such as `53..7....6..195...`, or spread over multiple lines with grid
+/// lines, such as the output of `Display`.
+impl FromStr for Puzzle {
+    type Err = SudError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut puz = Puzzle::new();
+        puz.set_initial(s.as_input_mode(ParseMode::Strict)?);
+        Ok(puz)
+    }
+}
+
+/// Character used to display a number.  Numbers larger than 9 are displayed as letters.  This is
+/// used for every format that does not take an `Alphabet`.
+pub(crate) fn symbol(val: Element) -> char {
+    std::char::from_digit(val as u32, 36)
+        .unwrap_or('?')
+        .to_ascii_uppercase()
+}
+
+/// Horizontal line drawn above, between, and below rows of boxes.
+fn separator(box_width: usize) -> String {
+    let mut line = String::from("+");
+    for _ in 0..BOX_DIMEN {
+        line.push_str(&"-".repeat(box_width));
+        line.push('+');
+    }
+    line
+}
+
+impl Puzzle {
+    fn fmt_grid(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let line = separator(BOX_DIMEN * 2 + 1);
+        for row in 0..MAX_NUM {
+            if row % BOX_DIMEN == 0 {
+                writeln!(f, "{}", line)?;
+            }
+            for col in 0..MAX_NUM {
+                if col % BOX_DIMEN == 0 {
+                    write!(f, "| ")?;
+                }
+                match self.cells[row * MAX_NUM + col].num() {
+                    0 => write!(f, ". ")?,
+                    v => write!(f, "{} ", symbol(v))?,
+                }
+            }
+            writeln!(f, "|")?;
+        }
+        write!(f, "{}", line)
+    }
+
+    /// Each cell is drawn as a small grid, with each pencil mark in its own position.  Pencil
+    /// marks that are not possible are shown as `.`, and filled in cells only show their number
+    /// in the center.
+    fn fmt_pencil_grid(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let box_width = BOX_DIMEN * (BOX_DIMEN + 1) + 1;
+        let line = separator(box_width);
+        let blank_line = format!("{}|", format!("|{}", " ".repeat(box_width)).repeat(BOX_DIMEN));
+        for row in 0..MAX_NUM {
+            if row % BOX_DIMEN == 0 {
+                writeln!(f, "{}", line)?;
+            } else {
+                writeln!(f, "{}", blank_line)?;
+            }
+            for mini_row in 0..BOX_DIMEN {
+                for col in 0..MAX_NUM {
+                    if col % BOX_DIMEN == 0 {
+                        write!(f, "| ")?;
+                    }
+                    let cell = &self.cells[row * MAX_NUM + col];
+                    for mini_col in 0..BOX_DIMEN {
+                        let val = (mini_row * BOX_DIMEN + mini_col + 1) as Element;
+                        let c = match cell.num() {
+                            0 if cell.is_possible(val) => symbol(val),
+                            0 => '.',
+                            v if mini_row == BOX_DIMEN / 2 && mini_col == BOX_DIMEN / 2 => symbol(v),
+                            _ => ' ',
+                        };
+                        write!(f, "{}", c)?;
+                    }
+                    write!(f, " ")?;
+                }
+                writeln!(f, "|")?;
+            }
+        }
+        write!(f, "{}", line)
+    }
+}
+
+/// Draws the puzzle as a grid with lines between the boxes, and `.` for empty cells.
+/// The alternate form (`{:#}`) draws the pencil marks of each cell.
+impl fmt::Display for Puzzle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if f.alternate() {
+            self.fmt_pencil_grid(f)
+        } else {
+            self.fmt_grid(f)
+        }
+    }
+}
+
+/// Reads a metadata line from a SadMan Software file, such as `#AAuthor Name`.  Returns false if
+/// the line is not a metadata line.
+fn read_meta_line(meta: &mut Metadata, line: &str) -> bool {
+    let mut chars = line.trim_end().chars();
+    if chars.next() != Some('#') {
+        return false;
+    }
+    let code = chars.next();
+    let text = chars.as_str().trim().to_string();
+    match code {
+        Some('A') => meta.author = Some(text),
+        Some('D') => meta.description = Some(text),
+        Some('L') => meta.difficulty = Some(text),
+        Some('S') => meta.source = Some(text),
+        Some('U') => meta.url = Some(text),
+        Some('B') => meta.date = Some(text),
+        Some('C') => meta.comments.push(text),
+        // Comments without a code are kept as they are
+        _ => meta.comments.push(line[1..].trim().to_string()),
+    }
+    true
+}
+
+/// Writes the metadata as SadMan Software metadata lines.  Each metadata line holds one line of
+/// text, so line breaks in a field are written as spaces, and a comment with line breaks is
+/// written as several comments.
+fn write_meta_lines(meta: &Metadata) -> String {
+    fn push_field(out: &mut String, code: char, field: &Option<String>) {
+        if let Some(text) = field {
+            let text = text.lines().collect::<Vec<&str>>().join(" ");
+            out.push_str(&format!("#{}{}\n", code, text));
+        }
+    }
+
+    let mut out = String::new();
+    push_field(&mut out, 'A', &meta.author);
+    push_field(&mut out, 'D', &meta.description);
+    for comment in meta.comments.iter() {
+        for line in comment.lines() {
+            out.push_str(&format!("#C{}\n", line));
+        }
+    }
+    push_field(&mut out, 'B', &meta.date);
+    push_field(&mut out, 'S', &meta.source);
+    push_field(&mut out, 'L', &meta.difficulty);
+    push_field(&mut out, 'U', &meta.url);
+    out
+}
+
+fn cell_values(puz: &Puzzle) -> Vec<Element> {
+    puz.cells.iter().map(|c| c.num()).collect()
+}
+
+/// Reads a puzzle in the SadMan Software `.sdk` format.  The file has optional metadata lines
+/// starting with `#` (`#A` author, `#D` description, `#C` comment, `#B` date, `#S` source,
+/// `#L` difficulty level, `#U` url), an optional `[Puzzle]` header, and then one line per row of
+/// the puzzle.  Empty cells can be `.`, `0`, or `_`.  Only lines before the first header or in
+/// the `[Puzzle]` section are read, so other sections, such as `[State]`, are ignored.
+pub fn read_sdk(text: &str) -> Result<Puzzle, SudError> {
+    let mut meta = Metadata::default();
+    // Lines that are not part of the grid are replaced by empty lines so that errors report
+    // the line number in the file.
+    let mut grid = String::new();
+    let mut in_puzzle = true;
+    for line in text.lines() {
+        if line.trim_start().starts_with('[') {
+            in_puzzle = line.trim().eq_ignore_ascii_case("[Puzzle]");
+        } else if in_puzzle && !read_meta_line(&mut meta, line) {
+            grid.push_str(line);
+        }
+        grid.push('\n');
+    }
+
+    let mut puz = Puzzle::new();
+    puz.set_initial(grid.as_str().as_input_mode(ParseMode::Strict)?);
+    puz.meta = meta;
+    Ok(puz)
+}
+
+/// Writes a puzzle in the SadMan Software `.sdk` format, with `.` for empty cells.
+pub fn write_sdk(puz: &Puzzle) -> String {
+    let mut out = write_meta_lines(&puz.meta);
+    out.push_str("[Puzzle]\n");
+    let line = cell_values(puz).as_string().unwrap_or_default();
+    for row in 0..MAX_NUM {
+        out.push_str(&line[row * MAX_NUM..(row + 1) * MAX_NUM]);
+        out.push('\n');
+    }
+    out
+}
+
+/// Reads puzzles in the SadMan Software `.sdm` format, which has one puzzle per line.  Empty
+/// lines are skipped, and metadata lines starting with `#` are applied to the next puzzle.
+/// Errors give the line number in the file, either in the error itself or with `SudError::Line`.
+pub fn read_sdm(text: &str) -> Result<Vec<Puzzle>, SudError> {
+    let mut puzzles: Vec<Puzzle> = Vec::new();
+    let mut meta = Metadata::default();
+    for (line_num, line) in text.lines().enumerate() {
+        if line.trim().is_empty() || read_meta_line(&mut meta, line) {
+            continue;
+        }
+        let input = line.as_input_mode(ParseMode::Strict).map_err(|e| match e {
+            SudError::InvalidChar { column, found, .. } => SudError::InvalidChar {
+                line: line_num + 1,
+                column,
+                found,
+            },
+            SudError::OutOfRange { column, value, .. } => SudError::OutOfRange {
+                line: line_num + 1,
+                column,
+                value,
+            },
+            e => SudError::Line {
+                line: line_num + 1,
+                error: Box::new(e),
+            },
+        })?;
+        let mut puz = Puzzle::new();
+        puz.set_initial(input);
+        puz.meta = std::mem::take(&mut meta);
+        puzzles.push(puz);
+    }
+    Ok(puzzles)
+}
+
+/// Writes puzzles in the SadMan Software `.sdm` format, with `.` for empty cells.  Metadata is
+/// written on the lines before the puzzle it belongs to.
+pub fn write_sdm(puzzles: &[Puzzle]) -> String {
+    let mut out = String::new();
+    for puz in puzzles.iter() {
+        out.push_str(&write_meta_lines(&puz.meta));
+        out.push_str(&cell_values(puz).as_string().unwrap_or_default());
+        out.push('\n');
+    }
+    out
+}
+
+/// Reads a grid of pencil marks, in the format used by HoDoKu and SudokuWiki.  Each cell is
+/// written as a group of numbers, with the cells separated by spaces or grid lines (`|`, `-`,
+/// `+`, `.`, `:`, `'`).  A single number is a given, and a group of numbers is an empty cell with
+/// those pencil marks.  The pencil marks are set exactly as they are written, rather than
+/// calculated from the puzzle.
+///
+/// A few extensions keep what those programs cannot write: a `0` in a group makes it an empty
+/// cell, so `0` has no pencil marks and `05` has only 5, and a single number after `+` was
+/// entered while solving rather than given.  A single number after `*` is a given.
+pub fn read_pencil_grid(text: &str) -> Result<Puzzle, SudError> {
+    // The marker before each cell, with the line and column where it was found, and the numbers
+    // of the cell
+    type Marker = (char, usize, usize);
+    let mut cells: Vec<(Option<Marker>, Vec<Element>)> = Vec::new();
+    for (line, row) in text.lines().enumerate() {
+        let mut in_cell = false;
+        let mut marker = None;
+        for (col, c) in row.chars().enumerate() {
+            let (line, column) = (line + 1, col + 1);
+            match c.to_digit(10) {
+                Some(n) if n as usize > MAX_NUM => {
+                    return Err(SudError::OutOfRange {
+                        line,
+                        column,
+                        value: n as usize,
+                    })
+                }
+                Some(n) => {
+                    if !in_cell {
+                        cells.push((marker.take(), Vec::new()));
+                    }
+                    cells.last_mut().unwrap().1.push(n as Element);
+                    in_cell = true;
+                }
+                None if c == '*' || c == '+' => {
+                    in_cell = false;
+                    marker = Some((c, line, column));
+                }
+                None if is_decoration(c) || ".:'".contains(c) => {
+                    in_cell = false;
+                    marker = None;
+                }
+                None => {
+                    return Err(SudError::InvalidChar {
+                        line,
+                        column,
+                        found: c,
+                    })
+                }
+            }
+        }
+    }
+
+    if cells.len() != NUM_CELLS {
+        return Err(SudError::WrongLength {
+            expected: NUM_CELLS,
+            found: cells.len(),
+        });
+    }
+
+    let mut puz = Puzzle::new();
+    for (index, (marker, marks)) in cells.iter().enumerate() {
+        match (marker, marks.as_slice()) {
+            (Some(('*', _, _)), [val]) if *val != 0 => puz.cells[index].set_cell_initial(*val),
+            (Some(('+', _, _)), [val]) if *val != 0 => {
+                puz.set_entry(index, *val);
+            }
+            // Only a single number can be filled in
+            (Some((found, line, column)), _) => {
+                return Err(SudError::InvalidChar {
+                    line: *line,
+                    column: *column,
+                    found: *found,
+                })
+            }
+            (None, [val]) if *val != 0 => puz.cells[index].set_cell_initial(*val),
+            (None, _) => {
+                for val in marks.iter().filter(|v| **v != 0) {
+                    puz.cells[index].mark_possible(*val);
+                }
+            }
+        }
+    }
+    Ok(puz)
+}
+
+/// Writes the pencil marks of each cell, in the format used by HoDoKu and SudokuWiki, which
+/// `read_pencil_grid` reads back.  Givens are written as their number.  Other filled in cells,
+/// empty cells with no pencil marks and empty cells with one pencil mark use the extensions
+/// described there.
+pub fn write_pencil_grid(puz: &Puzzle) -> String {
+    let text = puz
+        .cells
+        .iter()
+        .map(|c| match c.num() {
+            0 if c.penciled().len() == 1 => {
+                c.poss_iter().map(|v| format!("0{}", symbol(*v))).collect()
+            }
+            0 if c.penciled().is_empty() => "0".to_string(),
+            0 => c.poss_iter().map(|v| symbol(*v)).collect(),
+            v if c.given() => symbol(v).to_string(),
+            v => format!("+{}", symbol(v)),
+        })
+        .collect::<Vec<String>>();
+
+    // Every cell in a column is padded to the same width
+    let widths = (0..MAX_NUM)
+        .map(|col| {
+            (0..MAX_NUM)
+                .map(|row| text[row * MAX_NUM + col].len())
+                .max()
+                .unwrap_or(1)
+        })
+        .collect::<Vec<usize>>();
+    let box_widths = widths
+        .chunks(BOX_DIMEN)
+        .map(|w| w.iter().sum::<usize>() + 2 * (BOX_DIMEN - 1) + 2)
+        .collect::<Vec<usize>>();
+    let line = |edge: char, middle: char| {
+        let dashes = box_widths
+            .iter()
+            .map(|w| "-".repeat(*w))
+            .collect::<Vec<String>>();
+        format!("{}{}{}\n", edge, dashes.join(&middle.to_string()), edge)
+    };
+
+    let mut out = line('.', '.');
+    for row in 0..MAX_NUM {
+        if row != 0 && row % BOX_DIMEN == 0 {
+            out.push_str(&line(':', '+'));
+        }
+        for col in 0..MAX_NUM {
+            if col % BOX_DIMEN == 0 {
+                out.push_str("| ");
+            }
+            out.push_str(&format!("{:width$}", text[row * MAX_NUM + col], width = widths[col]));
+            out.push_str(if col % BOX_DIMEN == BOX_DIMEN - 1 { " " } else { "  " });
+        }
+        out.push_str("|\n");
+    }
+    out.push_str(&line('\'', '\''));
+    out
+}
+
 #[cfg(test)]
 mod input_tests {
     use super::*;
@@ -126,7 +551,10 @@ mod input_tests {
         ];
 
         match two.as_input() {
-            Err(SudError::InputParse) => (),
+            Err(SudError::WrongLength { expected, found }) => {
+                assert_eq!(expected, 81);
+                assert_eq!(found, 80);
+            }
             _ => assert!(false),
         }
     }
@@ -147,7 +575,7 @@ mod input_tests {
         let str =
             "53..7....6..195....98....6.8...6...34..8.3..17...2...6.6....28....419..5....8..9";
         match str.as_input() {
-            Err(SudError::InputParse) => (),
+            Err(SudError::WrongLength { found, .. }) => assert_eq!(found, 80),
             _ => assert!(false),
         }
     }
@@ -209,4 +637,285 @@ mod input_tests {
             _ => assert!(false),
         }
     }
+
+    const EXAMPLE: &str =
+        "53..7....6..195....98....6.8...6...34..8.3..17...2...6.6....28....419..5....8..79";
+
+    #[test]
+    fn display_test() {
+        let puz: Puzzle = EXAMPLE.parse().unwrap();
+        let expected = "\
++-------+-------+-------+
+| 5 3 . | . 7 . | . . . |
+| 6 . . | 1 9 5 | . . . |
+| . 9 8 | . . . | . 6 . |
++-------+-------+-------+
+| 8 . . | . 6 . | . . 3 |
+| 4 . . | 8 . 3 | . . 1 |
+| 7 . . | . 2 . | . . 6 |
++-------+-------+-------+
+| . 6 . | . . . | 2 8 . |
+| . . . | 4 1 9 | . . 5 |
+| . . . | . 8 . | . 7 9 |
++-------+-------+-------+";
+        assert_eq!(puz.to_string(), expected);
+
+        // Cell 2 has pencil marks of 1, 2, and 4.  Cell 3 has pencil marks of 2 and 6.
+        let pencils = format!("{:#}", puz);
+        let lines = pencils.lines().collect::<Vec<&str>>();
+        assert_eq!(lines.len(), 9 * 3 + 6 + 4);
+        assert_eq!(lines[0], "+-------------+-------------+-------------+");
+        assert_eq!(lines[1], "|         12. | .2.     .2. | 1.. 12. .2. |");
+        assert_eq!(lines[2], "|  5   3  4.. | ..6  7  4.6 | 4.. 4.. 4.. |");
+        assert_eq!(lines[3], "|         ... | ...     .8. | .89 ..9 .8. |");
+        assert_eq!(lines[4], "|             |             |             |");
+    }
+
+    #[test]
+    fn from_str_test() {
+        let puz: Puzzle = EXAMPLE.parse().unwrap();
+        assert_eq!(
+            puz.cells.iter().map(|c| c.num()).collect::<Vec<Element>>(),
+            EXAMPLE.as_input().unwrap()
+        );
+
+        // Reading back the displayed grid gives the same puzzle
+        let again: Puzzle = puz.to_string().parse().unwrap();
+        assert_eq!(again, puz);
+
+        let multi_line = "53..7....\n6..195...\n.98....6.\n8...6...3\n4..8.3..1\n7...2...6\n.6....28.\n...419..5\n....8..79\n";
+        let puz: Puzzle = multi_line.parse().unwrap();
+        assert_eq!(puz, again);
+
+        assert!(matches!(
+            "53..7....6..195....98....6.8...6...34..8.3..1".parse::<Puzzle>(),
+            Err(SudError::WrongLength { .. })
+        ));
+    }
+
+    #[test]
+    fn parse_error_test() {
+        let str = "53..7....\n6..195...\n.98..x.6.\n8...6...3\n4..8.3..1\n7...2...6\n.6....28.\n...419..5\n....8..79";
+        assert_eq!(
+            str.as_input_mode(ParseMode::Strict),
+            Err(SudError::InvalidChar {
+                line: 3,
+                column: 6,
+                found: 'x'
+            })
+        );
+        // Lenient mode treats the unknown character as an empty cell
+        assert_eq!(str.as_input().unwrap()[23], 0);
+
+        // The 5 in the first row is repeated
+        let str = "53..7...56..195....98....6.8...6...34..8.3..17...2...6.6....28....419..5....8..79";
+        assert!(str.as_input().is_ok());
+        assert_eq!(
+            str.as_input_mode(ParseMode::Strict),
+            Err(SudError::ConflictingGivens { index: 0, value: 5 })
+        );
+        assert!(str.parse::<Puzzle>().is_err());
+
+        let mut two = vec![vec![0; 9]; 9];
+        two[4][7] = 10;
+        assert_eq!(
+            two.as_input(),
+            Err(SudError::OutOfRange {
+                line: 5,
+                column: 8,
+                value: 10
+            })
+        );
+    }
+
+    #[test]
+    fn sdk_test() {
+        let text = "#AFrank Longo\n#DA classic\n#CFirst comment\n#CSecond comment\n#LHard\n[Puzzle]\n53__7____\n6..195...\n.98....6.\n8...6...3\n4..8.3..1\n7...2...6\n.6....28.\n000419005\n....8..79\n[State]\n534.7....\n";
+        let puz = read_sdk(text).unwrap();
+        assert_eq!(cell_values(&puz), EXAMPLE.as_input().unwrap());
+        assert_eq!(puz.meta().author.as_deref(), Some("Frank Longo"));
+        assert_eq!(puz.meta().description.as_deref(), Some("A classic"));
+        assert_eq!(puz.meta().difficulty.as_deref(), Some("Hard"));
+        assert_eq!(puz.meta().comments, vec!["First comment", "Second comment"]);
+
+        let written = write_sdk(&puz);
+        assert!(written.starts_with("#AFrank Longo\n#DA classic\n#CFirst comment\n"));
+        assert!(written.contains("[Puzzle]\n53..7....\n"));
+        assert_eq!(read_sdk(&written).unwrap(), puz);
+
+        // Without metadata or a header
+        let puz = read_sdk("53..7....\n6..195...\n.98....6.\n8...6...3\n4..8.3..1\n7...2...6\n.6....28.\n...419..5\n....8..79\n").unwrap();
+        assert_eq!(cell_values(&puz), EXAMPLE.as_input().unwrap());
+        assert_eq!(puz.meta(), &Metadata::default());
+
+        // Only the `[Puzzle]` section is read, even when the file has no `[Puzzle]` header
+        let original = read_sdk(text).unwrap();
+        let state = text.replace("[Puzzle]\n", "");
+        assert_eq!(read_sdk(&state).unwrap(), original);
+        let other = text.replace("[Puzzle]\n", "[Notes]\n53x\n[Puzzle]\n");
+        assert_eq!(read_sdk(&other).unwrap(), original);
+
+        // Line breaks in metadata do not add lines to the file
+        let mut multi = original;
+        multi.meta.author = Some("Frank\nLongo".to_string());
+        multi.meta.comments = vec!["Two\nlines".to_string()];
+        let written = write_sdk(&multi);
+        assert!(written.starts_with("#AFrank Longo\n#DA classic\n#CTwo\n#Clines\n"));
+        assert_eq!(
+            read_sdk(&written).unwrap().meta().comments,
+            vec!["Two", "lines"]
+        );
+
+        let bad = text.replace("#LHard\n[Puzzle]\n53__", "#LHard\n[Puzzle]\n53x_");
+        assert_eq!(
+            read_sdk(&bad),
+            Err(SudError::InvalidChar {
+                line: 7,
+                column: 3,
+                found: 'x'
+            })
+        );
+    }
+
+    #[test]
+    fn sdm_test() {
+        let second = "....8..79...419..5.6....28.7...2...64..8.3..18...6...3.98....6.6..195...53..7....";
+        let text = format!("{}\n\n#LEasy\n{}\n", EXAMPLE, second);
+        let puzzles = read_sdm(&text).unwrap();
+        assert_eq!(puzzles.len(), 2);
+        assert_eq!(cell_values(&puzzles[0]), EXAMPLE.as_input().unwrap());
+        assert_eq!(puzzles[0].meta().difficulty, None);
+        assert_eq!(cell_values(&puzzles[1]), second.as_input().unwrap());
+        assert_eq!(puzzles[1].meta().difficulty.as_deref(), Some("Easy"));
+
+        assert_eq!(write_sdm(&puzzles), format!("{}\n#LEasy\n{}\n", EXAMPLE, second));
+        assert_eq!(read_sdm(&write_sdm(&puzzles)).unwrap(), puzzles);
+
+        assert_eq!(
+            read_sdm(&format!("{}\n53..7..", EXAMPLE)),
+            Err(SudError::Line {
+                line: 2,
+                error: Box::new(SudError::WrongLength {
+                    expected: NUM_CELLS,
+                    found: 7
+                })
+            })
+        );
+        assert!(matches!(
+            read_sdm(&format!("\n{}", EXAMPLE.replace("53..7", "55..7"))),
+            Err(SudError::Line { line: 2, .. })
+        ));
+        let mut multi = puzzles.clone();
+        multi[0].meta.description = Some("Two\nlines".to_string());
+        assert!(write_sdm(&multi).starts_with("#DTwo lines\n"));
+        assert!(matches!(
+            read_sdm(&format!("{}\n{}", EXAMPLE, EXAMPLE.replace("53..7", "53..?"))),
+            Err(SudError::InvalidChar {
+                line: 2,
+                column: 5,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn pencil_grid_test() {
+        let puz: Puzzle = EXAMPLE.parse().unwrap();
+        let written = write_pencil_grid(&puz);
+        let lines = written.lines().collect::<Vec<&str>>();
+        assert_eq!(lines.len(), 13);
+        assert_eq!(lines[0].chars().next(), Some('.'));
+        assert_eq!(lines[4].chars().next(), Some(':'));
+        assert_eq!(lines[12].chars().next(), Some('\''));
+        assert_eq!(lines[1], "| 5    3     124    | 26    7   2468 | 1489   1249  248  |");
+        assert_eq!(lines[6], "| 4    25    2569   | 8     05  3    | 579    259   1    |");
+
+        // Reading it back gives the same numbers, givens and pencil marks
+        let read = read_pencil_grid(&written).unwrap();
+        assert_eq!(write_pencil_grid(&read), written);
+        for (before, after) in puz.cells.iter().zip(read.cells.iter()) {
+            assert_eq!(after.num(), before.num());
+            assert_eq!(after.given(), before.given());
+            assert_eq!(after.penciled(), before.penciled());
+        }
+
+        // `+` fills in a cell that is not a given, and a cell with one pencil mark starts with 0
+        let mut entered = puz.clone();
+        entered.set_entry(2, 4);
+        entered.cells[3].remove_possible(2);
+        let read = read_pencil_grid(&write_pencil_grid(&entered)).unwrap();
+        assert_eq!(read.cells[2].num(), 4);
+        assert!(read.cells[2].fixed() && !read.cells[2].given());
+        assert_eq!(read.cells[3].num(), 0);
+        assert_eq!(read.cells[3].poss_iter().cloned().collect::<Vec<_>>(), vec![6]);
+
+        // Pencil marks are set exactly, even when they could be calculated differently
+        let trimmed = written.replacen("124 ", "12  ", 1);
+        let read = read_pencil_grid(&trimmed).unwrap();
+        assert_eq!(
+            read.cells[2].poss_iter().cloned().collect::<Vec<Element>>(),
+            vec![1, 2]
+        );
+        assert_eq!(
+            read_pencil_grid(&write_pencil_grid(&read)).unwrap().cells[2].penciled(),
+            read.cells[2].penciled()
+        );
+
+        assert!(matches!(
+            read_pencil_grid(&written.replacen("124 ", "1x4 ", 1)),
+            Err(SudError::InvalidChar {
+                line: 2,
+                column: 15,
+                found: 'x'
+            })
+        ));
+        assert!(matches!(
+            read_pencil_grid(&written.replacen("124 ", "*12 ", 1)),
+            Err(SudError::InvalidChar {
+                line: 2,
+                column: 14,
+                found: '*'
+            })
+        ));
+        assert!(matches!(
+            read_pencil_grid("1 2 3"),
+            Err(SudError::WrongLength { found: 3, .. })
+        ));
+        let starred = written.replacen("| 5 ", "| *5", 1);
+        assert_eq!(
+            read_pencil_grid(&starred).unwrap(),
+            read_pencil_grid(&written).unwrap()
+        );
+    }
+
+    #[test]
+    fn hodoku_pencil_grid_test() {
+        // As HoDoKu and SudokuWiki write it, with the cells that have one pencil mark filled in
+        let grid = "\
+.-------------------.----------------.-------------------.
+| 5    3     124    | 26    7   2468 | 1489   1249  248  |
+| 6    247   247    | 1     9   5    | 3478   234   2478 |
+| 12   9     8      | 23    34  24   | 13457  6     247  |
+:-------------------+----------------+-------------------:
+| 8    125   1259   | 579   6   147  | 4579   2459  3    |
+| 4    25    2569   | 8     5   3    | 579    259   1    |
+| 7    15    1359   | 59    2   14   | 4589   459   6    |
+:-------------------+----------------+-------------------:
+| 139  6     134579 | 357   35  7    | 2      8     4    |
+| 23   278   237    | 4     1   9    | 36     3     5    |
+| 123  1245  12345  | 2356  8   26   | 1346   7     9    |
+'-------------------'----------------'-------------------'
+";
+        let puz = read_pencil_grid(grid).unwrap();
+        let givens = puz.cells.iter().filter(|c| c.given()).count();
+        assert_eq!(givens, 34);
+        assert_eq!(puz.cells[0].num(), 5);
+        assert!(puz.cells[40].given());
+        assert_eq!(puz.cells[40].num(), 5);
+        assert_eq!(
+            puz.cells[2].poss_iter().cloned().collect::<Vec<Element>>(),
+            vec![1, 2, 4]
+        );
+        assert_eq!(write_pencil_grid(&puz), grid);
+    }
 }
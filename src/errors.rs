@@ -1,8 +1,45 @@
 //! Custom errors
-#[derive(Debug)]
+use crate::{index_to_col, index_to_row, Element};
+
+#[derive(Debug, Clone, PartialEq)]
 pub enum SudError {
-    InputParse,
+    /// The input did not have one value for every cell.
+    WrongLength { expected: usize, found: usize },
+    /// A character that is not a number or an empty cell.  Lines and columns start at 1.
+    InvalidChar { line: usize, column: usize, found: char },
+    /// A number that is larger than the largest number allowed in the puzzle.  Lines and columns start at 1.
+    OutOfRange { line: usize, column: usize, value: usize },
+    /// A given number is repeated in the same row, column, or box as the given at `index`.
+    ConflictingGivens { index: usize, value: Element },
     OutputParse,
+    /// Packed or encoded data that is truncated or does not decode to a puzzle.
+    InvalidEncoding,
+    /// A share code whose checksum does not match, usually because of a typo.
+    ChecksumMismatch,
+    /// A share code written by a newer version of the format.
+    UnsupportedVersion(u8),
+    /// An error from reading or writing a stream.
+    Io(String),
+    /// A name, such as a symmetry or technique, that is not recognized.
+    UnknownName { kind: &'static str, name: String },
+    /// No generated puzzle matched the options.
+    GenerationFailed { attempts: usize },
+    /// An error in one line of a file with several puzzles.  Lines start at 1.
+    Line { line: usize, error: Box<SudError> },
+    /// A cell shared by two grids, written as (grid number, cell index), has a different number
+    /// in each.
+    OverlapConflict {
+        first: (usize, usize),
+        second: (usize, usize),
+    },
+    /// The deadline passed before the work was finished.
+    TimedOut,
+}
+
+impl From<std::io::Error> for SudError {
+    fn from(e: std::io::Error) -> Self {
+        SudError::Io(e.to_string())
+    }
 }
 
 impl std::error::Error for SudError {}
@@ -10,8 +47,59 @@ impl std::error::Error for SudError {}
 impl std::fmt::Display for SudError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
-            SudError::InputParse => write!(f, "Parse error when attempting puzzle input"),
+            SudError::WrongLength { expected, found } => write!(
+                f,
+                "Parse error when attempting puzzle input: expected {} cells, found {}",
+                expected, found
+            ),
+            SudError::InvalidChar {
+                line,
+                column,
+                found,
+            } => write!(
+                f,
+                "Parse error when attempting puzzle input: unexpected character '{}' at line {}, column {}",
+                found, line, column
+            ),
+            SudError::OutOfRange {
+                line,
+                column,
+                value,
+            } => write!(
+                f,
+                "Parse error when attempting puzzle input: {} is out of range at line {}, column {}",
+                value, line, column
+            ),
+            SudError::ConflictingGivens { index, value } => write!(
+                f,
+                "Puzzle input has conflicting givens: {} at row {}, column {} is repeated in its row, column, or box",
+                value,
+                index_to_row(*index) + 1,
+                index_to_col(*index) + 1
+            ),
             SudError::OutputParse => write!(f, "Parse error when attempting output"),
+            SudError::InvalidEncoding => write!(f, "Encoded puzzle data is invalid or truncated"),
+            SudError::ChecksumMismatch => write!(f, "Share code checksum does not match"),
+            SudError::UnsupportedVersion(v) => write!(f, "Share code version {} is not supported", v),
+            SudError::Io(e) => write!(f, "I/O error: {}", e),
+            SudError::UnknownName { kind, name } => write!(f, "Unknown {} '{}'", kind, name),
+            SudError::GenerationFailed { attempts } => write!(
+                f,
+                "No puzzle matching the options was found after {} attempts",
+                attempts
+            ),
+            SudError::Line { line, error } => write!(f, "Line {}: {}", line, error),
+            SudError::OverlapConflict { first, second } => write!(
+                f,
+                "Shared cell has different numbers: grid {} row {}, column {} and grid {} row {}, column {}",
+                first.0,
+                index_to_row(first.1) + 1,
+                index_to_col(first.1) + 1,
+                second.0,
+                index_to_row(second.1) + 1,
+                index_to_col(second.1) + 1
+            ),
+            SudError::TimedOut => write!(f, "The deadline passed before the work was finished"),
         }
     }
 }
@@ -0,0 +1,346 @@
+//! Generating puzzles with a unique solution.
+use crate::*;
+use std::str::FromStr;
+use std::time::Instant;
+
+/// Which cells are removed together, so that the givens form a pattern.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Symmetry {
+    None,
+    /// The same after turning the grid half way around.
+    Rotational,
+    /// The same after turning the grid a quarter of the way around.
+    QuarterTurn,
+    /// The same after flipping the top and bottom.
+    Horizontal,
+    /// The same after flipping the left and right.
+    Vertical,
+    /// The same after flipping across the diagonal from the top left.
+    Diagonal,
+}
+
+impl Symmetry {
+    pub const ALL: [Symmetry; 6] = [
+        Symmetry::None,
+        Symmetry::Rotational,
+        Symmetry::QuarterTurn,
+        Symmetry::Horizontal,
+        Symmetry::Vertical,
+        Symmetry::Diagonal,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Symmetry::None => "none",
+            Symmetry::Rotational => "rotational",
+            Symmetry::QuarterTurn => "quarter-turn",
+            Symmetry::Horizontal => "horizontal",
+            Symmetry::Vertical => "vertical",
+            Symmetry::Diagonal => "diagonal",
+        }
+    }
+
+    /// The cells that must be given or empty together with the cell at `index`, including itself.
+    pub fn orbit(&self, index: usize) -> Vec<usize> {
+        let last = MAX_NUM - 1;
+        let (row, col) = (index_to_row(index), index_to_col(index));
+        let mut cells = match self {
+            Symmetry::None => vec![index],
+            Symmetry::Rotational => vec![index, get_cell(last - row, last - col)],
+            Symmetry::QuarterTurn => vec![
+                index,
+                get_cell(col, last - row),
+                get_cell(last - row, last - col),
+                get_cell(last - col, row),
+            ],
+            Symmetry::Horizontal => vec![index, get_cell(last - row, col)],
+            Symmetry::Vertical => vec![index, get_cell(row, last - col)],
+            Symmetry::Diagonal => vec![index, get_cell(col, row)],
+        };
+        cells.sort_unstable();
+        cells.dedup();
+        cells
+    }
+}
+
+impl FromStr for Symmetry {
+    type Err = SudError;
+
+    fn from_str(s: &str) -> Result<Symmetry, SudError> {
+        let name = s.to_ascii_lowercase().replace('_', "-");
+        Symmetry::ALL
+            .iter()
+            .find(|sym| sym.name() == name)
+            .copied()
+            .ok_or_else(|| SudError::UnknownName {
+                kind: "symmetry",
+                name: s.to_string(),
+            })
+    }
+}
+
+/// A small random number generator (SplitMix64), so that the same seed always generates the same
+/// puzzles.
+#[derive(Clone, Debug)]
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// A number from 0 up to, but not including, `n`.
+    fn below(&mut self, n: usize) -> usize {
+        (self.next() % n as u64) as usize
+    }
+
+    fn shuffle<T>(&mut self, items: &mut [T]) {
+        for i in (1..items.len()).rev() {
+            items.swap(i, self.below(i + 1));
+        }
+    }
+}
+
+/// Generates puzzles with a unique solution.  The same seed and options always generate the same
+/// puzzles, in the same order.
+///
+/// ```
+/// use sudoku::{Generator, Symmetry};
+///
+/// let mut generator = Generator::new(7);
+/// generator.clues(30, 34).symmetry(Symmetry::Rotational);
+/// let puz = generator.generate().unwrap();
+/// let clues = puz.cells.iter().filter(|c| c.num() != 0).count();
+/// assert!((30..=34).contains(&clues));
+/// ```
+#[derive(Clone, Debug)]
+pub struct Generator {
+    rng: Rng,
+    min_clues: usize,
+    max_clues: usize,
+    symmetry: Symmetry,
+    difficulty: Option<Technique>,
+    attempts: usize,
+    deadline: Option<Instant>,
+}
+
+impl Generator {
+    /// The range of the number of givens unless `clues` is called.  Most published puzzles have
+    /// this many, and puzzles with fewer take much longer to find.
+    pub const DEFAULT_CLUES: (usize, usize) = (22, 30);
+
+    pub fn new(seed: u64) -> Generator {
+        Generator {
+            rng: Rng(seed),
+            min_clues: Generator::DEFAULT_CLUES.0,
+            max_clues: Generator::DEFAULT_CLUES.1,
+            symmetry: Symmetry::None,
+            difficulty: None,
+            attempts: 100,
+            deadline: None,
+        }
+    }
+
+    /// The range of the number of givens, inclusive.  Fewer givens takes longer to generate.
+    pub fn clues(&mut self, min: usize, max: usize) -> &mut Self {
+        assert!(
+            min <= max && max <= NUM_CELLS,
+            "Clue range must be within 0 to {}",
+            NUM_CELLS
+        );
+        self.min_clues = min;
+        self.max_clues = max;
+        self
+    }
+
+    pub fn symmetry(&mut self, symmetry: Symmetry) -> &mut Self {
+        self.symmetry = symmetry;
+        self
+    }
+
+    /// Only keep puzzles whose hardest technique, found by `Puzzle::rate`, is `difficulty`.
+    pub fn difficulty(&mut self, difficulty: Technique) -> &mut Self {
+        self.difficulty = Some(difficulty);
+        self
+    }
+
+    /// How many puzzles to try before giving up on finding one that matches the options.
+    pub fn attempts(&mut self, attempts: usize) -> &mut Self {
+        self.attempts = attempts.max(1);
+        self
+    }
+
+    /// Stop generating once this time has passed, even in the middle of an attempt.
+    pub fn deadline(&mut self, deadline: Instant) -> &mut Self {
+        self.deadline = Some(deadline);
+        self
+    }
+
+    /// Generates the next puzzle.  Returns `SudError::GenerationFailed` if no puzzle matching the
+    /// options was found, or `SudError::TimedOut` if the deadline passed first.
+    pub fn generate(&mut self) -> Result<Puzzle, SudError> {
+        for _ in 0..self.attempts {
+            if matches!(self.deadline, Some(d) if Instant::now() >= d) {
+                return Err(SudError::TimedOut);
+            }
+            let solution = self.random_solution();
+            let givens = match self.remove_givens(solution)? {
+                Some(givens) => givens,
+                None => continue,
+            };
+            let mut puz = Puzzle::new();
+            puz.set_initial(givens);
+            match self.difficulty {
+                Some(difficulty) if puz.rate_by(self.deadline)?.hardest() != Some(difficulty) => {
+                    continue
+                }
+                _ => return Ok(puz),
+            }
+        }
+        Err(SudError::GenerationFailed {
+            attempts: self.attempts,
+        })
+    }
+
+    /// Fills the boxes along the diagonal, which do not affect each other, with random numbers and
+    /// solves the rest.
+    fn random_solution(&mut self) -> Vec<Element> {
+        let mut initial = vec![0; NUM_CELLS];
+        for diagonal in 0..BOX_DIMEN {
+            let mut nums = (1..=MAX_NUM as Element).collect::<Vec<Element>>();
+            self.rng.shuffle(&mut nums);
+            let box_number = diagonal * BOX_DIMEN + diagonal;
+            for (i, num) in nums.into_iter().enumerate() {
+                initial[index_from_box(box_number, i)] = num;
+            }
+        }
+        let mut puz = Puzzle::new();
+        puz.set_initial(initial);
+        puz.brute_force_solve_max(1)
+            .pop()
+            .expect("Diagonal boxes can always be completed")
+    }
+
+    /// Removes givens in a random order, keeping the solution unique, until the number of givens
+    /// reaches a random target in the clue range.  Returns `None` if there are still too many.
+    fn remove_givens(&mut self, solution: Vec<Element>) -> Result<Option<Vec<Element>>, SudError> {
+        let target = self.min_clues + self.rng.below(self.max_clues - self.min_clues + 1);
+        let mut orbits = Vec::new();
+        for index in 0..NUM_CELLS {
+            let orbit = self.symmetry.orbit(index);
+            if orbit[0] == index {
+                orbits.push(orbit);
+            }
+        }
+        self.rng.shuffle(&mut orbits);
+
+        let mut givens = solution;
+        let mut count = NUM_CELLS;
+        for orbit in orbits {
+            if count <= target {
+                break;
+            }
+            if count - orbit.len() < self.min_clues {
+                continue;
+            }
+            let mut attempt = givens.clone();
+            orbit.iter().for_each(|i| attempt[*i] = 0);
+            let mut puz = Puzzle::new();
+            puz.set_initial(attempt.clone());
+            if puz.search(2, self.deadline)?.len() == 1 {
+                givens = attempt;
+                count -= orbit.len();
+            }
+        }
+        if count <= self.max_clues {
+            Ok(Some(givens))
+        } else {
+            Ok(None)
+        }
+    }
+}
+
+#[cfg(test)]
+mod generate_tests {
+    use super::*;
+
+    fn clues(puz: &Puzzle) -> usize {
+        puz.cells.iter().filter(|c| c.num() != 0).count()
+    }
+
+    #[test]
+    fn orbit_test() {
+        assert_eq!(Symmetry::None.orbit(10), vec![10]);
+        assert_eq!(Symmetry::Rotational.orbit(0), vec![0, 80]);
+        assert_eq!(Symmetry::Rotational.orbit(40), vec![40]);
+        assert_eq!(Symmetry::QuarterTurn.orbit(1), vec![1, 17, 63, 79]);
+        assert_eq!(Symmetry::Horizontal.orbit(1), vec![1, 73]);
+        assert_eq!(Symmetry::Vertical.orbit(1), vec![1, 7]);
+        assert_eq!(Symmetry::Diagonal.orbit(1), vec![1, 9]);
+        assert_eq!(Symmetry::Diagonal.orbit(10), vec![10]);
+
+        assert_eq!("Quarter_Turn".parse(), Ok(Symmetry::QuarterTurn));
+        assert!(matches!(
+            "spiral".parse::<Symmetry>(),
+            Err(SudError::UnknownName { .. })
+        ));
+    }
+
+    #[test]
+    fn generate_test() {
+        let mut generator = Generator::new(42);
+        generator.clues(32, 36).symmetry(Symmetry::Rotational);
+        let first = generator.generate().unwrap();
+        let second = generator.generate().unwrap();
+        assert_ne!(first, second);
+        for puz in [&first, &second] {
+            assert!((32..=36).contains(&clues(puz)));
+            assert_eq!(puz.clone().brute_force_solve_max(2).len(), 1);
+            for index in 0..NUM_CELLS {
+                assert_eq!(
+                    puz.cells[index].num() == 0,
+                    puz.cells[80 - index].num() == 0
+                );
+            }
+        }
+
+        // The same seed generates the same puzzles
+        let mut again = Generator::new(42);
+        again.clues(32, 36).symmetry(Symmetry::Rotational);
+        assert_eq!(again.generate().unwrap(), first);
+    }
+
+    #[test]
+    fn difficulty_test() {
+        let mut generator = Generator::new(1);
+        generator.clues(30, 34).difficulty(Technique::BruteForce);
+        let puz = generator.generate().unwrap();
+        assert_eq!(puz.rate().hardest(), Some(Technique::BruteForce));
+
+        // Every puzzle with this many givens is easy
+        let mut generator = Generator::new(1);
+        generator
+            .clues(70, 81)
+            .difficulty(Technique::BruteForce)
+            .attempts(3);
+        assert_eq!(
+            generator.generate(),
+            Err(SudError::GenerationFailed { attempts: 3 })
+        );
+    }
+
+    #[test]
+    fn deadline_test() {
+        let mut generator = Generator::new(1);
+        generator
+            .clues(17, 17)
+            .attempts(1_000_000)
+            .deadline(Instant::now());
+        assert_eq!(generator.generate(), Err(SudError::TimedOut));
+    }
+}
@@ -58,7 +58,11 @@ impl BasicHumanMethods for Puzzle {
 
             for i in single_cands {
 
-                let val: Element = *self.cells[i].penciled().iter().next().expect(format!("index: {}", i).as_str());
+                // An earlier cell may have taken the last candidate if the puzzle has no solution
+                let val: Element = match self.cells[i].penciled().iter().next() {
+                    Some(val) => *val,
+                    None => continue,
+                };
                 // Mark the cell that has one candidate
                 if fill {
                     self.cells[i].set(val);
@@ -647,7 +651,49 @@ pub fn update_pencil_after_add(puz: &mut Puzzle, index: usize) {
     for cell in puz.box_iter_mut(index) {
         cell.remove_possible(val);
     }
+    // Variant constraints may rule out more pencil marks now that the cell is known
+    if !puz.constraints().is_empty() {
+        puz.apply_constraints();
+    }
+}
+
+/// After the number `val` is removed from a cell, works out the cell's pencil marks again and
+/// puts `val` back into the pencil marks of the empty cells it sees, unless another number they
+/// see rules it out.  The opposite of `update_pencil_after_add`.
+pub fn update_pencil_after_remove(puz: &mut Puzzle, index: usize, val: Element) {
+    let houses = [
+        House::Row(index_to_row(index)),
+        House::Col(index_to_col(index)),
+        House::Box(index_to_box(index)),
+    ];
+    let mut seen = houses.iter().flat_map(|h| h.cells()).collect::<Vec<usize>>();
+    seen.sort_unstable();
+    seen.dedup();
+    for i in seen {
+        if puz.cells[i].num() != 0 || (i != index && val == 0) {
+            continue;
+        }
+        let taken = puz
+            .single_iterator(i)
+            .map(|c| c.num())
+            .collect::<HashSet<Element>>();
+        // The cell itself may have any number, and the cells it sees only get `val` back
+        let nums = if i == index {
+            (1..=MAX_NUM as Element).collect::<Vec<Element>>()
+        } else {
+            vec![val]
+        };
+        for n in nums {
+            if !taken.contains(&n) && puz.constraints().iter().all(|c| c.allows(i, n)) {
+                puz.cells[i].mark_possible(n);
+            }
+        }
+    }
+    if !puz.constraints().is_empty() {
+        puz.apply_constraints();
+    }
 }
+
 #[cfg(test)]
 mod human_method_tests {
     use super::*;
@@ -697,6 +743,16 @@ mod human_method_tests {
         }
     }
 
+    #[test]
+    fn restore_pencil_test() {
+        let mut puz = get_puzzle();
+        puz.set_entry(10, 2);
+        update_pencil_after_add(&mut puz, 10);
+        puz.set_entry(10, 0);
+        update_pencil_after_remove(&mut puz, 10, 2);
+        assert_eq!(puz.cells, get_puzzle().cells);
+    }
+
     #[test]
     fn single_cand() {
         let mut puz = Puzzle::new();
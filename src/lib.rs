@@ -1,14 +1,35 @@
+mod alphabet;
+mod booklet;
 mod brute;
+mod constraints;
 mod conv_input_output;
 mod errors;
+mod generate;
 mod human_calcs;
+mod multi_grid;
+mod outer_clues;
+mod packed;
+mod rating;
+mod share_code;
 mod support;
+mod svg;
 
+pub use crate::alphabet::*;
+pub use crate::booklet::*;
 pub use crate::brute::BruteForce;
 pub use crate::brute::*;
+pub use crate::constraints::*;
 pub use crate::conv_input_output::*;
+pub use crate::errors::*;
+pub use crate::generate::*;
 pub use crate::human_calcs::*;
+pub use crate::multi_grid::*;
+pub use crate::outer_clues::*;
+pub use crate::packed::*;
+pub use crate::rating::*;
+pub use crate::share_code::*;
 pub use crate::support::*;
+pub use crate::svg::*;
 use std::collections::{BTreeSet, HashSet};
 use std::iter::Skip;
 
@@ -25,9 +46,12 @@ static NUM_CELLS: usize = MAX_NUM * MAX_NUM;
 
 // The Cell struct contains the number, boolean if it is fixed, and functions to incremement
 #[derive(Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Cell {
     num: Element,
     fixed: bool,
+    #[cfg_attr(feature = "serde", serde(default))]
+    given: bool,
     penciled: BTreeSet<Element>,
 }
 /*
@@ -40,6 +64,7 @@ impl Default for Cell {
         Cell {
             num: 0,
             fixed: false,
+            given: false,
             penciled: BTreeSet::new(),
         }
     }
@@ -51,6 +76,11 @@ impl Cell {
         self.fixed
     }
 
+    /// True if the number was part of the puzzle, rather than solved.
+    pub fn given(&self) -> bool {
+        self.given
+    }
+
     pub fn num(&self) -> Element {
         self.num
     }
@@ -89,6 +119,7 @@ impl Cell {
     pub fn set_cell_initial(&mut self, val: Element) {
         self.num = val;
         self.fixed = true;
+        self.given = true;
     }
 
     fn set(&mut self, val: Element) {
@@ -112,10 +143,44 @@ impl Cell {
     }
 }
 
-/// Contains a row dominant 1-D vector for all the cells in the puzzle
+/// Information about a puzzle that is not needed to solve it, such as who wrote it.
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Metadata {
+    pub author: Option<String>,
+    pub description: Option<String>,
+    pub difficulty: Option<String>,
+    pub source: Option<String>,
+    pub url: Option<String>,
+    pub date: Option<String>,
+    pub comments: Vec<String>,
+}
+
+/// Contains a row dominant 1-D vector for all the cells in the puzzle, along with any variant
+/// constraints that apply to it and information about the puzzle.
 #[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Puzzle {
+    #[cfg_attr(feature = "serde", serde(deserialize_with = "deserialize_cells"))]
     pub cells: Vec<Cell>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    constraints: Vec<Constraint>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    meta: Metadata,
+}
+
+/// Reads the cells of a puzzle, which must have a cell for every position in the grid.
+#[cfg(feature = "serde")]
+fn deserialize_cells<'de, D>(deserializer: D) -> Result<Vec<Cell>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    use serde::de::{Deserialize, Error};
+    let cells = Vec::<Cell>::deserialize(deserializer)?;
+    if cells.len() != NUM_CELLS {
+        return Err(D::Error::invalid_length(cells.len(), &"81 cells"));
+    }
+    Ok(cells)
 }
 
 /// Struct used to handle iterating over a 3x3 box (for standard game size grid).
@@ -168,6 +233,8 @@ impl Puzzle {
     pub fn new() -> Puzzle {
         Puzzle {
             cells: vec![Cell::default(); NUM_CELLS],
+            constraints: Vec::new(),
+            meta: Metadata::default(),
         }
     }
 
@@ -218,6 +285,14 @@ impl Puzzle {
             .chain(self.row_iter(index).chain(self.col_iter(index)))
     }
 
+    pub fn meta(&self) -> &Metadata {
+        &self.meta
+    }
+
+    pub fn meta_mut(&mut self) -> &mut Metadata {
+        &mut self.meta
+    }
+
     /// Sets a new puzzle using 2-D vector parameter
     pub fn set_initial(&mut self, initial: Vec<Element>) -> &mut Self {
         initial
@@ -230,6 +305,28 @@ impl Puzzle {
         self.set_penciled()
     }
 
+    /// Fills in a cell that is not a given, the way a player would, or empties it if `val` is 0.
+    /// The cell's pencil marks are cleared, but the pencil marks of other cells are left alone.
+    /// Returns false if the cell is a given.
+    pub fn set_entry(&mut self, index: usize, val: Element) -> bool {
+        let cell = &mut self.cells[index];
+        if cell.given() {
+            return false;
+        }
+        cell.penciled.clear();
+        cell.set(val);
+        cell.fixed = val != 0;
+        true
+    }
+
+    /// Replaces the pencil marks of every empty cell with the numbers that are still possible.
+    pub fn reset_pencil_marks(&mut self) -> &mut Self {
+        for cell in self.cells.iter_mut().filter(|c| !c.fixed()) {
+            cell.penciled.clear();
+        }
+        self.set_penciled()
+    }
+
     // Assumes that the puzzle has already been initially set
     fn set_penciled(&mut self) -> &mut Self {
         // For all cells that are not fixed...
@@ -242,15 +339,19 @@ impl Puzzle {
                 .map(|x| x.num())
                 .collect::<HashSet<Element>>();
             for n in 1..=MAX_NUM {
-                if !set.contains(&(n as Element)) {
+                // Odd / even markers only allow some numbers in the cell.
+                let allowed = self.constraints.iter().all(|c| c.allows(i, n as Element));
+                if allowed && !set.contains(&(n as Element)) {
                     self.cells[i].mark_possible(n as Element);
                 }
             }
         }
+        self.apply_constraints();
         self
     }
 
-    /// Checks if the cell is valid by comparing it to other cells in row, column, and associated box.
+    /// Checks if the cell is valid by comparing it to other cells in row, column, and associated box,
+    /// and by checking any variant constraints on the puzzle.
     pub fn valid_entry(&self, index: usize) -> bool {
         // The three iterators used to check for validity are "dumb", as in they check the cell in question.
         // This will result in 1 match per iterator, therefore the expected Vec length is 3 if it is a valid puzzle.
@@ -259,6 +360,7 @@ impl Puzzle {
             .collect::<Vec<&Cell>>()
             .len()
             == 3
+            && self.constraints.iter().all(|c| c.satisfied(self, index))
     }
 
     /// Goes over every cell in the puzzle and checks that each cell has a value and that value is valid.
@@ -434,6 +536,32 @@ mod tests {
         assert!(iter.next().is_none());
     }
 
+    #[test]
+    fn set_entry_test() {
+        let mut puz = Puzzle::new();
+        puz.set_initial(get_example().into_iter().flatten().collect());
+        assert!(!puz.set_entry(0, 1));
+        assert_eq!(puz.cells[0].num(), 5);
+
+        assert!(puz.set_entry(2, 5));
+        assert!(puz.cells[2].penciled().is_empty());
+        assert!(!puz.valid_entry(2));
+        // Other cells keep their pencil marks until they are reset
+        assert!(puz.set_entry(2, 4));
+        assert!(puz.cells[6].is_possible(4));
+        puz.reset_pencil_marks();
+        assert!(!puz.cells[6].is_possible(4));
+
+        assert!(puz.set_entry(2, 0));
+        assert!(!puz.cells[2].fixed());
+        puz.reset_pencil_marks();
+        assert!(puz.cells[6].is_possible(4));
+        assert_eq!(
+            puz.cells[2].penciled().iter().copied().collect::<Vec<_>>(),
+            vec![1, 2, 4]
+        );
+    }
+
     #[test]
     fn set_pencil_test() {
         let example = get_example();
@@ -464,4 +592,41 @@ mod tests {
         }
         assert!(cell78.is_empty());
     }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_test() {
+        let mut puz = Puzzle::new();
+        puz.set_initial(get_example().as_input().unwrap());
+        puz.cells[2].penciled_mut().remove(&4);
+        puz.add_constraint(Constraint::Odd(80));
+        puz.meta_mut().author = Some("Tim".to_string());
+
+        let json = serde_json::to_string(&puz).unwrap();
+        let back: Puzzle = serde_json::from_str(&json).unwrap();
+        assert_eq!(back, puz);
+        assert_eq!(back.cells[2].penciled().len(), 2);
+        assert!(back.cells[0].fixed());
+
+        // The schema of a cell
+        assert_eq!(
+            serde_json::to_string(&puz.cells[2]).unwrap(),
+            r#"{"num":0,"fixed":false,"given":false,"penciled":[1,2]}"#
+        );
+        assert_eq!(
+            serde_json::to_string(&Constraint::WhiteDot(0, 1)).unwrap(),
+            r#"{"WhiteDot":[0,1]}"#
+        );
+
+        // Constraints and metadata may be left out
+        let cells = serde_json::to_string(&puz.cells).unwrap();
+        let back: Puzzle = serde_json::from_str(&format!(r#"{{"cells":{}}}"#, cells)).unwrap();
+        assert_eq!(back.cells, puz.cells);
+        assert!(back.constraints().is_empty());
+
+        // Every cell of the grid must be there
+        let short = serde_json::to_string(&puz.cells[..80]).unwrap();
+        let error = serde_json::from_str::<Puzzle>(&format!(r#"{{"cells":{}}}"#, short));
+        assert!(error.unwrap_err().to_string().contains("invalid length 80"));
+    }
 }
@@ -0,0 +1,404 @@
+//! Puzzles made of several overlapping grids, such as Samurai Sudoku.
+use crate::*;
+
+/// A box that is shared between two grids, written as (grid number, box number) pairs.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Overlap {
+    pub first: (usize, usize),
+    pub second: (usize, usize),
+}
+
+impl Overlap {
+    /// The pairs of (grid number, cell index) that refer to the same cell.
+    pub fn shared_cells(&self) -> Vec<((usize, usize), (usize, usize))> {
+        let ((grid1, box1), (grid2, box2)) = (self.first, self.second);
+        (0..MAX_NUM)
+            .map(|i| {
+                (
+                    (grid1, index_from_box(box1, i)),
+                    (grid2, index_from_box(box2, i)),
+                )
+            })
+            .collect()
+    }
+}
+
+/// Several grids that share boxes.  Each grid follows the normal rules of sudoku, and a shared
+/// cell has the same value in every grid it belongs to.
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MultiGrid {
+    pub grids: Vec<Puzzle>,
+    overlaps: Vec<Overlap>,
+}
+
+impl MultiGrid {
+    /// Creates a multi-grid puzzle without any overlaps.  The grids should already be initially set.
+    pub fn new(grids: Vec<Puzzle>) -> MultiGrid {
+        MultiGrid {
+            grids,
+            overlaps: Vec::new(),
+        }
+    }
+
+    /// Samurai Sudoku.  The grids are in the order top left, top right, center, bottom left,
+    /// bottom right.  Each corner grid shares its inner corner box with the center grid.
+    pub fn samurai(grids: Vec<Puzzle>) -> MultiGrid {
+        assert_eq!(grids.len(), 5, "Samurai Sudoku has 5 grids");
+        let last = MAX_NUM - 1;
+        let (top_right, bottom_left) = (BOX_DIMEN - 1, last - (BOX_DIMEN - 1));
+
+        let mut samurai = MultiGrid::new(grids);
+        samurai
+            .add_overlap((0, last), (2, 0))
+            .add_overlap((1, bottom_left), (2, top_right))
+            .add_overlap((3, top_right), (2, bottom_left))
+            .add_overlap((4, 0), (2, last));
+        samurai
+    }
+
+    /// Declares that box `first.1` of grid `first.0` is the same as box `second.1` of grid
+    /// `second.0`.  Numbers and pencil marks are then shared between the two boxes.  If the boxes
+    /// already have different numbers in a shared cell, nothing is reported here; call `sync` to
+    /// find the conflict.  Panics if a grid or box does not exist.
+    pub fn add_overlap(&mut self, first: (usize, usize), second: (usize, usize)) -> &mut Self {
+        for (grid, box_number) in [first, second].iter() {
+            assert!(
+                *grid < self.grids.len() && *box_number < MAX_NUM,
+                "There is no box {} in grid {}",
+                box_number,
+                grid
+            );
+        }
+        self.overlaps.push(Overlap { first, second });
+        let _ = self.sync();
+        self
+    }
+
+    pub fn overlaps(&self) -> &[Overlap] {
+        &self.overlaps
+    }
+
+    /// Copies numbers into shared cells that are empty in another grid, and removes pencil marks
+    /// from a shared cell that have been eliminated in another grid.  Repeats until nothing changes.
+    /// Returns true if anything was changed, or an error if a shared cell has different numbers in
+    /// its two grids.
+    pub fn sync(&mut self) -> Result<bool, SudError> {
+        let shared = self
+            .overlaps
+            .iter()
+            .flat_map(|o| o.shared_cells())
+            .collect::<Vec<((usize, usize), (usize, usize))>>();
+
+        let mut changed = false;
+        let mut progress = true;
+        while progress {
+            progress = false;
+            for ((g1, i1), (g2, i2)) in shared.iter().cloned() {
+                let (num1, num2) = (
+                    self.grids[g1].cells[i1].num(),
+                    self.grids[g2].cells[i2].num(),
+                );
+                if num1 != 0 && num2 != 0 && num1 != num2 {
+                    return Err(SudError::OverlapConflict {
+                        first: (g1, i1),
+                        second: (g2, i2),
+                    });
+                } else if num1 != 0 && num2 == 0 {
+                    self.place(g2, i2, num1);
+                    progress = true;
+                } else if num2 != 0 && num1 == 0 {
+                    self.place(g1, i1, num2);
+                    progress = true;
+                } else if num1 == 0 && num2 == 0 {
+                    let common = self.grids[g1].cells[i1]
+                        .penciled()
+                        .intersection(self.grids[g2].cells[i2].penciled())
+                        .cloned()
+                        .collect::<BTreeSet<Element>>();
+                    for (g, i) in [(g1, i1), (g2, i2)].iter() {
+                        if *self.grids[*g].cells[*i].penciled() != common {
+                            *self.grids[*g].cells[*i].penciled_mut() = common.clone();
+                            progress = true;
+                        }
+                    }
+                }
+            }
+            changed |= progress;
+        }
+        Ok(changed)
+    }
+
+    /// Fills in a cell that has been solved in another grid.
+    fn place(&mut self, grid: usize, index: usize, val: Element) {
+        let cell = &mut self.grids[grid].cells[index];
+        cell.penciled_mut().clear();
+        cell.set(val);
+        cell.fixed = true;
+        update_pencil_after_add(&mut self.grids[grid], index);
+    }
+
+    /// Solves each grid with the human methods, sharing the results between grids, until no
+    /// more progress is made.  Returns true if every grid is solved, and false if a shared cell
+    /// has different numbers in its two grids.
+    pub fn logic_solve(&mut self) -> bool {
+        loop {
+            let before = self.grids.clone();
+            for grid in self.grids.iter_mut() {
+                grid.single_candidate(true);
+                grid.single_possibility(true);
+                grid.naked_tuple();
+                grid.locked_candidates_pointing(true);
+            }
+            if self.sync().is_err() {
+                return false;
+            }
+            if before == self.grids {
+                break;
+            }
+        }
+        self.is_solved()
+    }
+
+    pub fn is_solved(&self) -> bool {
+        self.grids.iter().all(|g| g.is_solved())
+    }
+
+    /// Solves the puzzle by trying every possibility.  Returns a vector of solutions, where each
+    /// solution has the 1-D vector of every grid, in the same order as `grids`.  If no solution
+    /// exists, or a shared cell has different numbers in its two grids, the vector will be empty.
+    pub fn brute_force_solve(&mut self) -> Vec<Vec<Vec<Element>>> {
+        if self.sync().is_err() {
+            return Vec::new();
+        }
+
+        // Every distinct cell, and the (grid number, cell index) pairs it appears as.
+        let mut group_of: Vec<Vec<Option<usize>>> = vec![vec![None; NUM_CELLS]; self.grids.len()];
+        let mut groups: Vec<Vec<(usize, usize)>> = Vec::new();
+        for ((g1, i1), (g2, i2)) in self.overlaps.iter().flat_map(|o| o.shared_cells()) {
+            match (group_of[g1][i1], group_of[g2][i2]) {
+                (Some(group), None) => {
+                    group_of[g2][i2] = Some(group);
+                    groups[group].push((g2, i2));
+                }
+                (None, Some(group)) => {
+                    group_of[g1][i1] = Some(group);
+                    groups[group].push((g1, i1));
+                }
+                (None, None) => {
+                    group_of[g1][i1] = Some(groups.len());
+                    group_of[g2][i2] = Some(groups.len());
+                    groups.push(vec![(g1, i1), (g2, i2)]);
+                }
+                _ => (),
+            }
+        }
+        for (g, grid_groups) in group_of.iter().enumerate() {
+            for (i, group) in grid_groups.iter().enumerate() {
+                if group.is_none() {
+                    groups.push(vec![(g, i)]);
+                }
+            }
+        }
+        // Cells that are already filled in do not need to be searched
+        groups.retain(|cells| {
+            cells
+                .iter()
+                .all(|(g, i)| self.grids[*g].cells[*i].num() == 0)
+        });
+
+        let mut solutions: Vec<Vec<Vec<Element>>> = Vec::new();
+        self.search(&groups, 0, &mut solutions);
+        solutions
+    }
+
+    fn search(
+        &mut self,
+        groups: &[Vec<(usize, usize)>],
+        position: usize,
+        solutions: &mut Vec<Vec<Vec<Element>>>,
+    ) {
+        if position == groups.len() {
+            if self.is_solved() {
+                let solution = self
+                    .grids
+                    .iter()
+                    .map(|g| g.cells.iter().map(|c| c.num()).collect())
+                    .collect();
+                solutions.push(solution);
+            }
+            return;
+        }
+
+        let cells = &groups[position];
+        let (first_grid, first_index) = cells[0];
+        let candidates = self.grids[first_grid].cells[first_index]
+            .poss_iter()
+            .cloned()
+            .filter(|v| {
+                cells
+                    .iter()
+                    .all(|(g, i)| self.grids[*g].cells[*i].is_possible(*v))
+            })
+            .collect::<Vec<Element>>();
+
+        for val in candidates {
+            for (g, i) in cells.iter() {
+                self.grids[*g].cells[*i].set(val);
+            }
+            if cells.iter().all(|(g, i)| self.grids[*g].valid_entry(*i)) {
+                self.search(groups, position + 1, solutions);
+            }
+        }
+        for (g, i) in cells.iter() {
+            self.grids[*g].cells[*i].reset();
+        }
+    }
+}
+
+#[cfg(test)]
+mod multi_grid_tests {
+    use super::*;
+
+    const SOLUTION: &str =
+        "534678912672195348198342567859761423426853791713924856961537284287419635345286179";
+
+    /// Renames the digits of the solution so that box `from` of the new grid matches box `to`
+    /// of the original.
+    fn relabel(from: usize, to: usize) -> Vec<Element> {
+        let solution = SOLUTION.as_input().unwrap();
+        let mut map = [0; 10];
+        for i in 0..MAX_NUM {
+            map[solution[index_from_box(from, i)] as usize] = solution[index_from_box(to, i)];
+        }
+        solution.iter().map(|v| map[*v as usize]).collect()
+    }
+
+    /// Solutions for the five grids of a samurai puzzle
+    fn samurai_solution() -> Vec<Vec<Element>> {
+        vec![
+            relabel(8, 0),
+            relabel(6, 2),
+            SOLUTION.as_input().unwrap(),
+            relabel(2, 6),
+            relabel(0, 8),
+        ]
+    }
+
+    fn samurai_puzzle(keep: impl Fn(usize, usize) -> bool) -> MultiGrid {
+        let grids = samurai_solution()
+            .iter()
+            .enumerate()
+            .map(|(g, sol)| {
+                let givens = sol
+                    .iter()
+                    .enumerate()
+                    .map(|(i, v)| if keep(g, i) { *v } else { 0 })
+                    .collect::<Vec<Element>>();
+                let mut puz = Puzzle::new();
+                puz.set_initial(givens);
+                puz
+            })
+            .collect();
+        MultiGrid::samurai(grids)
+    }
+
+    #[test]
+    fn sync_test() {
+        // The top left grid has nothing in its last box, which is shared with the center grid
+        let samurai = samurai_puzzle(|g, i| !(g == 0 && index_to_box(i) == 8) && i % 3 != 0);
+        let solution = samurai_solution();
+        for i in 0..MAX_NUM {
+            let index = index_from_box(8, i);
+            let center = samurai.grids[2].cells[index_from_box(0, i)].num();
+            assert_eq!(samurai.grids[0].cells[index].num(), center);
+            if center != 0 {
+                assert_eq!(center, solution[0][index]);
+            }
+        }
+        // Pencil marks of the empty shared cells are the same in both grids
+        assert_eq!(
+            samurai.grids[0].cells[60].penciled(),
+            samurai.grids[2].cells[0].penciled()
+        );
+    }
+
+    #[test]
+    fn logic_solve_test() {
+        let mut samurai = samurai_puzzle(|g, i| (i * 7 + g) % 4 != 0);
+        assert!(samurai.logic_solve());
+        for (grid, expected) in samurai.grids.iter().zip(samurai_solution().iter()) {
+            assert_eq!(
+                &grid.cells.iter().map(|c| c.num()).collect::<Vec<Element>>(),
+                expected
+            );
+        }
+    }
+
+    #[test]
+    fn brute_force_test() {
+        // The middle box of the center grid is empty
+        let mut samurai = samurai_puzzle(|g, i| i % 5 != 0 && (g != 2 || index_to_box(i) != 4));
+        let res = samurai.brute_force_solve();
+        assert_eq!(res.len(), 1);
+        assert_eq!(res[0], samurai_solution());
+    }
+
+    #[test]
+    fn sync_repeats_test() {
+        // Only the center grid has givens, so its corner boxes are copied to the other grids
+        let grids = samurai_solution()
+            .into_iter()
+            .enumerate()
+            .map(|(g, sol)| {
+                let mut puz = Puzzle::new();
+                puz.set_initial(if g == 2 { sol } else { vec![0; NUM_CELLS] });
+                puz
+            })
+            .collect();
+        let mut samurai = MultiGrid::new(grids);
+        samurai.overlaps.push(Overlap {
+            first: (0, 8),
+            second: (2, 0),
+        });
+        assert_eq!(samurai.sync(), Ok(true));
+        // Everything was done in the first call
+        assert_eq!(samurai.sync(), Ok(false));
+        assert_eq!(
+            samurai.grids[0].cells[80].num(),
+            samurai.grids[2].cells[20].num()
+        );
+    }
+
+    #[test]
+    fn overlap_conflict_test() {
+        let mut samurai = samurai_puzzle(|_, i| i % 5 != 0);
+        // Cell 61 of the top left grid is cell 1 of the center grid, and both are givens
+        let cell = &mut samurai.grids[0].cells[61];
+        cell.set((cell.num() % 9) + 1);
+        assert_eq!(
+            samurai.sync(),
+            Err(SudError::OverlapConflict {
+                first: (0, 61),
+                second: (2, 1)
+            })
+        );
+        assert!(samurai.brute_force_solve().is_empty());
+        assert!(!samurai.logic_solve());
+    }
+
+    #[test]
+    #[should_panic(expected = "There is no box 9 in grid 1")]
+    fn overlap_box_test() {
+        let mut grids = MultiGrid::new(vec![Puzzle::new(), Puzzle::new()]);
+        grids.add_overlap((0, 8), (1, 9));
+    }
+
+    #[test]
+    #[should_panic(expected = "There is no box 0 in grid 2")]
+    fn overlap_grid_test() {
+        let mut grids = MultiGrid::new(vec![Puzzle::new(), Puzzle::new()]);
+        grids.add_overlap((2, 0), (1, 8));
+    }
+}
@@ -0,0 +1,457 @@
+//! Clues written outside of the grid that apply to a whole row, column, or diagonal.
+use crate::constraints::{domain, prune_outside, prune_sum};
+use crate::*;
+
+/// The side of the grid that an outside clue is written on.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Side {
+    Top,
+    Bottom,
+    Left,
+    Right,
+}
+
+/// A clue written outside of the grid.  `side` is where the clue is written and `line` is the
+/// row (for `Left` and `Right`) or column (for `Top` and `Bottom`) number the clue is next to.
+/// Add to a puzzle with `Puzzle::add_constraint(Constraint::Outer(clue))`.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum OuterClue {
+    /// The sum of the numbers between the 1 and the largest number (9 for a standard size game)
+    /// in the row or column.
+    Sandwich { side: Side, line: usize, sum: usize },
+    /// The sum of the numbers along a diagonal.  The diagonal starts at the first cell of the line
+    /// and moves one cell into the grid and one cell toward `toward` each step.  Numbers may
+    /// repeat along the diagonal as long as they are not in the same box.
+    LittleKiller {
+        side: Side,
+        line: usize,
+        toward: Side,
+        sum: usize,
+    },
+    /// Each number is the height of a skyscraper.  Taller skyscrapers hide shorter ones behind
+    /// them, and `count` is how many can be seen looking into the grid from the clue.
+    Skyscraper {
+        side: Side,
+        line: usize,
+        count: usize,
+    },
+}
+
+/// True if the sides are next to each other, rather than the same or opposite.
+fn at_right_angles(a: Side, b: Side) -> bool {
+    let horizontal = |s: Side| s == Side::Left || s == Side::Right;
+    horizontal(a) != horizontal(b)
+}
+
+/// Row and column movement for one step in the direction of a side.
+fn step(side: Side) -> (isize, isize) {
+    match side {
+        Side::Top => (-1, 0),
+        Side::Bottom => (1, 0),
+        Side::Left => (0, -1),
+        Side::Right => (0, 1),
+    }
+}
+
+impl OuterClue {
+    fn side_and_line(&self) -> (Side, usize) {
+        match self {
+            OuterClue::Sandwich { side, line, .. }
+            | OuterClue::LittleKiller { side, line, .. }
+            | OuterClue::Skyscraper { side, line, .. } => (*side, *line),
+        }
+    }
+
+    /// Panics if the clue's line is outside the grid, a little killer does not turn toward a
+    /// side at right angles to its own, or a skyscraper count is not from 1 to the max.
+    pub(crate) fn assert_valid(&self) {
+        let (side, line) = self.side_and_line();
+        assert!(line < MAX_NUM, "line {} is outside the grid", line);
+        match self {
+            OuterClue::LittleKiller { toward, .. } => assert!(
+                at_right_angles(side, *toward),
+                "a little killer on the {:?} side cannot go toward the {:?} side",
+                side,
+                toward
+            ),
+            OuterClue::Skyscraper { count, .. } => assert!(
+                (1..=MAX_NUM).contains(count),
+                "a skyscraper count of {} is not from 1 to {}",
+                count,
+                MAX_NUM
+            ),
+            OuterClue::Sandwich { .. } => (),
+        }
+    }
+
+    /// The cells the clue applies to, in order starting with the cell closest to the clue.  A
+    /// clue that `Puzzle::add_constraint` would reject has no cells.
+    pub fn cells(&self) -> Vec<usize> {
+        let (side, line) = self.side_and_line();
+        if line >= MAX_NUM {
+            return Vec::new();
+        }
+        let last = MAX_NUM as isize - 1;
+        let (mut row, mut col) = match side {
+            Side::Top => (0, line as isize),
+            Side::Bottom => (last, line as isize),
+            Side::Left => (line as isize, 0),
+            Side::Right => (line as isize, last),
+        };
+        // The clue looks into the grid, which is the opposite direction of its side
+        let (inward_row, inward_col) = step(side);
+        let (mut row_step, mut col_step) = (-inward_row, -inward_col);
+        if let OuterClue::LittleKiller { toward, .. } = self {
+            if !at_right_angles(side, *toward) {
+                return Vec::new();
+            }
+            let (toward_row, toward_col) = step(*toward);
+            row_step += toward_row;
+            col_step += toward_col;
+        }
+
+        let mut cells: Vec<usize> = Vec::new();
+        while (0..=last).contains(&row) && (0..=last).contains(&col) {
+            cells.push(get_cell(row as usize, col as usize));
+            row += row_step;
+            col += col_step;
+        }
+        cells
+    }
+
+    /// Checks the clue against a line of numbers.  Empty cells (a value of `0`) are allowed as
+    /// long as the filled in cells do not break the clue.
+    pub fn line_satisfied(&self, vals: &[Element]) -> bool {
+        let max = MAX_NUM as Element;
+        match self {
+            OuterClue::Sandwich { sum, .. } => {
+                let ends = (
+                    vals.iter().position(|v| *v == 1),
+                    vals.iter().position(|v| *v == max),
+                );
+                match ends {
+                    (Some(a), Some(b)) => {
+                        let between = &vals[a.min(b) + 1..a.max(b)];
+                        let total: usize = between.iter().map(|v| *v as usize).sum();
+                        let empty = between.iter().filter(|v| **v == 0).count();
+                        // Empty cells between the ends can be anything from 2 to 1 less than the max
+                        total + 2 * empty <= *sum && total + (MAX_NUM - 1) * empty >= *sum
+                    }
+                    _ => true,
+                }
+            }
+            OuterClue::LittleKiller { sum, .. } => {
+                let total: usize = vals.iter().map(|v| *v as usize).sum();
+                let empty = vals.iter().filter(|v| **v == 0).count();
+                total + empty <= *sum && total + MAX_NUM * empty >= *sum
+            }
+            OuterClue::Skyscraper { count, .. } => {
+                // Only the cells up to the first empty cell are known to be seen
+                let known = vals
+                    .iter()
+                    .take_while(|v| **v != 0)
+                    .collect::<Vec<&Element>>();
+                let mut tallest = 0;
+                let mut seen = 0;
+                for v in known.iter() {
+                    if **v > tallest {
+                        tallest = **v;
+                        seen += 1;
+                    }
+                }
+                if tallest == max || known.len() == vals.len() {
+                    seen == *count
+                } else {
+                    seen <= *count
+                }
+            }
+        }
+    }
+
+    /// Finds pencil marks that cannot be part of a solution due to this clue.
+    /// Returns a vector of tuples of (index value, eliminated number).
+    pub(crate) fn prune(&self, puz: &Puzzle) -> Vec<(usize, Element)> {
+        let cells = self.cells();
+        match self {
+            OuterClue::Sandwich { sum, .. } => prune_sandwich(puz, &cells, *sum),
+            OuterClue::LittleKiller { sum, .. } => prune_sum(puz, &cells, *sum, *sum),
+            OuterClue::Skyscraper { count, .. } => {
+                // With `count` skyscrapers seen, there have to be enough shorter ones in front of
+                // each cell that the rest can still be seen.
+                let mut elims = cells
+                    .iter()
+                    .enumerate()
+                    .flat_map(|(dist, i)| {
+                        let high = MAX_NUM as isize - *count as isize + 1 + dist as isize;
+                        prune_outside(puz, *i, 1, high)
+                    })
+                    .collect::<Vec<(usize, Element)>>();
+                if *count == 1 {
+                    elims.append(&mut prune_outside(
+                        puz,
+                        cells[0],
+                        MAX_NUM as isize,
+                        MAX_NUM as isize,
+                    ));
+                }
+                elims
+            }
+        }
+    }
+}
+
+/// Number of cells between the 1 and the max that can add up to `sum`, using different numbers
+/// from 2 to 1 less than the max.
+fn sandwich_gaps(sum: usize) -> Vec<usize> {
+    (0..MAX_NUM - 1)
+        .filter(|gap| {
+            let smallest: usize = (2..2 + gap).sum();
+            let largest: usize = (MAX_NUM - gap..MAX_NUM).sum();
+            smallest <= sum && sum <= largest
+        })
+        .collect()
+}
+
+/// The 1 and the max can only go where the other could be placed the right distance away.  Once
+/// both are placed, the cells between them have to add up to the sum.
+fn prune_sandwich(puz: &Puzzle, cells: &[usize], sum: usize) -> Vec<(usize, Element)> {
+    let max = MAX_NUM as Element;
+    let gaps = sandwich_gaps(sum);
+    let possible = |pos: usize, val: Element| domain(puz, cells[pos]).contains(&val);
+
+    let mut elims: Vec<(usize, Element)> = Vec::new();
+    for (end, other_end) in [(1, max), (max, 1)].iter() {
+        for pos in (0..cells.len()).filter(|p| puz.cells[cells[*p]].num() == 0) {
+            let partner = (0..cells.len()).filter(|other| *other != pos).any(|other| {
+                let gap = pos.max(other) - pos.min(other) - 1;
+                gaps.contains(&gap) && possible(other, *other_end)
+            });
+            if !partner && puz.cells[cells[pos]].is_possible(*end) {
+                elims.push((cells[pos], *end));
+            }
+        }
+    }
+
+    let placed = (
+        cells.iter().position(|i| puz.cells[*i].num() == 1),
+        cells.iter().position(|i| puz.cells[*i].num() == max),
+    );
+    if let (Some(a), Some(b)) = placed {
+        elims.append(&mut prune_sum(
+            puz,
+            &cells[a.min(b) + 1..a.max(b)],
+            sum,
+            sum,
+        ));
+    }
+    elims
+}
+
+#[cfg(test)]
+mod outer_clue_tests {
+    use super::*;
+
+    fn two_solutions() -> Puzzle {
+        let mut puz = Puzzle::new();
+        let str =
+            "295743861431865900876192543387459216612387495549216738763524189928671354154938600";
+        puz.set_initial(str.as_input().unwrap());
+        puz
+    }
+
+    fn empty() -> Puzzle {
+        let mut puz = Puzzle::new();
+        puz.set_initial(vec![0; NUM_CELLS]);
+        puz
+    }
+
+    #[test]
+    fn cells_test() {
+        let clue = OuterClue::Skyscraper {
+            side: Side::Right,
+            line: 1,
+            count: 3,
+        };
+        assert_eq!(clue.cells(), vec![17, 16, 15, 14, 13, 12, 11, 10, 9]);
+
+        let clue = OuterClue::Sandwich {
+            side: Side::Top,
+            line: 2,
+            sum: 0,
+        };
+        assert_eq!(clue.cells(), vec![2, 11, 20, 29, 38, 47, 56, 65, 74]);
+
+        let clue = OuterClue::LittleKiller {
+            side: Side::Top,
+            line: 6,
+            toward: Side::Right,
+            sum: 13,
+        };
+        assert_eq!(clue.cells(), vec![6, 16, 26]);
+
+        let clue = OuterClue::LittleKiller {
+            side: Side::Bottom,
+            line: 1,
+            toward: Side::Left,
+            sum: 13,
+        };
+        assert_eq!(clue.cells(), vec![73, 63]);
+
+        let clue = OuterClue::LittleKiller {
+            side: Side::Top,
+            line: 1,
+            toward: Side::Bottom,
+            sum: 13,
+        };
+        assert!(clue.cells().is_empty());
+        let clue = OuterClue::Sandwich {
+            side: Side::Top,
+            line: 9,
+            sum: 0,
+        };
+        assert!(clue.cells().is_empty());
+    }
+
+    #[test]
+    #[should_panic(expected = "cannot go toward")]
+    fn little_killer_same_side_test() {
+        empty().add_constraint(Constraint::Outer(OuterClue::LittleKiller {
+            side: Side::Left,
+            line: 3,
+            toward: Side::Left,
+            sum: 10,
+        }));
+    }
+
+    #[test]
+    #[should_panic(expected = "outside the grid")]
+    fn line_outside_test() {
+        empty().add_constraint(Constraint::Outer(OuterClue::Skyscraper {
+            side: Side::Top,
+            line: 9,
+            count: 1,
+        }));
+    }
+
+    #[test]
+    #[should_panic(expected = "skyscraper count of 0")]
+    fn skyscraper_count_test() {
+        empty().add_constraint(Constraint::Outer(OuterClue::Skyscraper {
+            side: Side::Top,
+            line: 0,
+            count: 0,
+        }));
+    }
+
+    #[test]
+    fn sandwich_test() {
+        let mut puz = empty();
+        puz.add_constraint(Constraint::Outer(OuterClue::Sandwich {
+            side: Side::Left,
+            line: 0,
+            sum: 35,
+        }));
+        // The 1 and 9 have to be at the ends of the row
+        for i in 1..8 {
+            assert!(!puz.cells[i].is_possible(1));
+            assert!(!puz.cells[i].is_possible(9));
+        }
+        assert!(puz.cells[0].is_possible(1));
+
+        let mut puz = two_solutions();
+        puz.add_constraint(Constraint::Outer(OuterClue::Sandwich {
+            side: Side::Bottom,
+            line: 8,
+            sum: 24,
+        }));
+        let res = puz.brute_force_solve();
+        assert_eq!(res.len(), 1);
+        assert_eq!(res[0][17], 2);
+    }
+
+    #[test]
+    fn little_killer_test() {
+        let mut puz = empty();
+        puz.add_constraint(Constraint::Outer(OuterClue::LittleKiller {
+            side: Side::Top,
+            line: 7,
+            toward: Side::Right,
+            sum: 3,
+        }));
+        assert_eq!(
+            puz.cells[7].poss_iter().cloned().collect::<Vec<Element>>(),
+            vec![1, 2]
+        );
+        assert_eq!(
+            puz.cells[17].poss_iter().cloned().collect::<Vec<Element>>(),
+            vec![1, 2]
+        );
+
+        let mut puz = two_solutions();
+        puz.add_constraint(Constraint::Outer(OuterClue::LittleKiller {
+            side: Side::Top,
+            line: 6,
+            toward: Side::Right,
+            sum: 13,
+        }));
+        let res = puz.brute_force_solve();
+        assert_eq!(res.len(), 1);
+        assert_eq!(res[0][16], 2);
+    }
+
+    #[test]
+    fn skyscraper_test() {
+        let mut puz = empty();
+        puz.add_constraint(Constraint::Outer(OuterClue::Skyscraper {
+            side: Side::Left,
+            line: 0,
+            count: 1,
+        }))
+        .add_constraint(Constraint::Outer(OuterClue::Skyscraper {
+            side: Side::Top,
+            line: 8,
+            count: 3,
+        }));
+        assert_eq!(
+            puz.cells[0].poss_iter().cloned().collect::<Vec<Element>>(),
+            vec![9]
+        );
+        assert!(!puz.cells[8].is_possible(8));
+        assert!(puz.cells[17].is_possible(8));
+
+        let mut puz = two_solutions();
+        puz.add_constraint(Constraint::Outer(OuterClue::Skyscraper {
+            side: Side::Right,
+            line: 1,
+            count: 3,
+        }));
+        let res = puz.brute_force_solve();
+        assert_eq!(res.len(), 1);
+        assert_eq!(res[0][17], 2);
+    }
+
+    #[test]
+    fn line_satisfied_test() {
+        let clue = OuterClue::Skyscraper {
+            side: Side::Left,
+            line: 0,
+            count: 2,
+        };
+        assert!(clue.line_satisfied(&[3, 0, 0, 0, 0, 0, 0, 0, 0]));
+        assert!(clue.line_satisfied(&[3, 9, 0, 0, 0, 0, 0, 0, 0]));
+        assert!(!clue.line_satisfied(&[3, 4, 9, 0, 0, 0, 0, 0, 0]));
+        assert!(!clue.line_satisfied(&[9, 0, 0, 0, 0, 0, 0, 0, 0]));
+
+        let clue = OuterClue::Sandwich {
+            side: Side::Left,
+            line: 0,
+            sum: 10,
+        };
+        assert!(clue.line_satisfied(&[1, 2, 8, 9, 0, 0, 0, 0, 0]));
+        assert!(!clue.line_satisfied(&[1, 2, 7, 9, 0, 0, 0, 0, 0]));
+        assert!(clue.line_satisfied(&[1, 0, 0, 9, 0, 0, 0, 0, 0]));
+        assert!(!clue.line_satisfied(&[1, 0, 0, 0, 0, 0, 0, 0, 9]));
+    }
+}
@@ -0,0 +1,438 @@
+//! Compact binary encodings for storing large numbers of puzzles and solutions.
+//!
+//! A puzzle is stored as a bitmap with one bit for each cell, set if the cell has a number,
+//! followed by the numbers packed two to a byte.  A 25 clue puzzle takes 24 bytes.
+//!
+//! A solution is stored as the rank of each row among all orderings of the numbers, except for
+//! the last row, which is found from the numbers missing in each column.  A 9x9 solution takes
+//! 19 bytes.
+use crate::*;
+use std::io::{ErrorKind, Read, Write};
+
+/// Packs values into bytes, most significant bit first.
+pub(crate) struct BitWriter {
+    pub(crate) bytes: Vec<u8>,
+    used: usize,
+}
+
+impl BitWriter {
+    pub(crate) fn new() -> BitWriter {
+        BitWriter {
+            bytes: Vec::new(),
+            used: 0,
+        }
+    }
+
+    pub(crate) fn push(&mut self, value: u64, bits: usize) {
+        for bit in (0..bits).rev() {
+            if self.used.is_multiple_of(8) {
+                self.bytes.push(0);
+            }
+            if (value >> bit) & 1 == 1 {
+                *self.bytes.last_mut().unwrap() |= 0x80 >> (self.used % 8);
+            }
+            self.used += 1;
+        }
+    }
+
+    /// Skips to the start of the next byte.
+    pub(crate) fn align(&mut self) {
+        self.used = self.bytes.len() * 8;
+    }
+}
+
+pub(crate) struct BitReader<'a> {
+    bytes: &'a [u8],
+    pub(crate) position: usize,
+}
+
+impl<'a> BitReader<'a> {
+    pub(crate) fn new(bytes: &'a [u8]) -> BitReader<'a> {
+        BitReader { bytes, position: 0 }
+    }
+
+    pub(crate) fn pull(&mut self, bits: usize) -> Result<u64, SudError> {
+        let mut value = 0;
+        for _ in 0..bits {
+            let byte = self
+                .bytes
+                .get(self.position / 8)
+                .ok_or(SudError::InvalidEncoding)?;
+            value = (value << 1) | u64::from((byte >> (7 - self.position % 8)) & 1);
+            self.position += 1;
+        }
+        Ok(value)
+    }
+}
+
+fn bitmap_len() -> usize {
+    NUM_CELLS.div_ceil(8)
+}
+
+/// Bits needed to store the rank of a row.
+fn rank_bits() -> usize {
+    let orderings = (1..=MAX_NUM as u64).product::<u64>();
+    (u64::BITS - (orderings - 1).leading_zeros()) as usize
+}
+
+/// The number of bytes used by a packed solution.
+pub fn solution_len() -> usize {
+    ((MAX_NUM - 1) * rank_bits()).div_ceil(8)
+}
+
+/// Packs the numbers of a puzzle.  Pencil marks, constraints and metadata are not stored.
+pub fn encode_puzzle(puz: &Puzzle) -> Vec<u8> {
+    let mut bits = BitWriter::new();
+    pack_values(
+        &mut bits,
+        &puz.cells.iter().map(|c| c.num()).collect::<Vec<Element>>(),
+    );
+    bits.bytes
+}
+
+/// Unpacks a puzzle written by `encode_puzzle`.  The numbers are set as the puzzle's givens.
+pub fn decode_puzzle(bytes: &[u8]) -> Result<Puzzle, SudError> {
+    let mut bits = BitReader::new(bytes);
+    let values = unpack_values(&mut bits)?;
+    if bytes.len() != bits.position.div_ceil(8) {
+        return Err(SudError::InvalidEncoding);
+    }
+
+    let mut puz = Puzzle::new();
+    puz.set_initial(values);
+    Ok(puz)
+}
+
+/// Writes the bitmap of cells that are not 0, then the values of those cells, each starting on
+/// a new byte.
+pub(crate) fn pack_values(bits: &mut BitWriter, values: &[Element]) {
+    bits.align();
+    for val in values.iter() {
+        bits.push((*val != 0) as u64, 1);
+    }
+    bits.align();
+    for val in values.iter().filter(|v| **v != 0) {
+        bits.push(*val as u64, 4);
+    }
+}
+
+/// Reads values written by `pack_values`.
+pub(crate) fn unpack_values(bits: &mut BitReader) -> Result<Vec<Element>, SudError> {
+    bits.position = bits.position.div_ceil(8) * 8;
+    let mut filled = Vec::with_capacity(NUM_CELLS);
+    for _ in 0..NUM_CELLS {
+        filled.push(bits.pull(1)? == 1);
+    }
+    bits.position = bits.position.div_ceil(8) * 8;
+    let mut values = vec![0; NUM_CELLS];
+    for (value, _) in values.iter_mut().zip(filled.iter()).filter(|(_, f)| **f) {
+        *value = match bits.pull(4)? as usize {
+            v if v == 0 || v > MAX_NUM => return Err(SudError::InvalidEncoding),
+            v => v as Element,
+        };
+    }
+    Ok(values)
+}
+
+/// The length of a packed puzzle, found from its bitmap.
+fn puzzle_len(bitmap: &[u8]) -> usize {
+    let clues = bitmap
+        .iter()
+        .map(|b| b.count_ones() as usize)
+        .sum::<usize>();
+    bitmap_len() + clues.div_ceil(2)
+}
+
+/// Packs a complete solution.  Every row, column and box must have each number once.
+pub fn encode_solution(solution: &[Element]) -> Result<Vec<u8>, SudError> {
+    if solution.len() != NUM_CELLS {
+        return Err(SudError::WrongLength {
+            expected: NUM_CELLS,
+            found: solution.len(),
+        });
+    }
+    if !is_solution(solution) {
+        return Err(SudError::InvalidEncoding);
+    }
+
+    let rows = solution.chunks(MAX_NUM).collect::<Vec<&[Element]>>();
+    let mut bits = BitWriter::new();
+    for row in rows[..MAX_NUM - 1].iter() {
+        bits.push(rank(row).ok_or(SudError::InvalidEncoding)?, rank_bits());
+    }
+    Ok(bits.bytes)
+}
+
+/// Unpacks a solution written by `encode_solution`.
+pub fn decode_solution(bytes: &[u8]) -> Result<Vec<Element>, SudError> {
+    if bytes.len() != solution_len() {
+        return Err(SudError::InvalidEncoding);
+    }
+    let mut bits = BitReader::new(bytes);
+    let mut rows = Vec::with_capacity(MAX_NUM);
+    for _ in 0..MAX_NUM - 1 {
+        rows.push(unrank(bits.pull(rank_bits())?).ok_or(SudError::InvalidEncoding)?);
+    }
+    let last = {
+        let refs = rows
+            .iter()
+            .map(|r| r.as_slice())
+            .collect::<Vec<&[Element]>>();
+        last_row(&refs).ok_or(SudError::InvalidEncoding)?
+    };
+    rows.push(last);
+    // The rows and columns are complete, but a corrupt record may not have complete boxes
+    let solution = rows.concat();
+    if !is_solution(&solution) {
+        return Err(SudError::InvalidEncoding);
+    }
+    Ok(solution)
+}
+
+/// True if every row, column and box has each number once.
+fn is_solution(values: &[Element]) -> bool {
+    let mut puz = Puzzle::new();
+    puz.set_initial(values.to_vec());
+    puz.is_solved()
+}
+
+/// The only row that can follow the others, if each column is missing exactly one number.
+fn last_row(rows: &[&[Element]]) -> Option<Vec<Element>> {
+    (0..MAX_NUM)
+        .map(|col| {
+            let mut missing =
+                (1..=MAX_NUM as Element).filter(|v| rows.iter().all(|r| r[col] != *v));
+            match (missing.next(), missing.next()) {
+                (Some(v), None) => Some(v),
+                _ => None,
+            }
+        })
+        .collect::<Option<Vec<Element>>>()
+        .filter(|row| (1..=MAX_NUM as Element).all(|v| row.contains(&v)))
+}
+
+/// The position of the row among all orderings of the numbers (Lehmer code).
+fn rank(row: &[Element]) -> Option<u64> {
+    let mut remaining = (1..=MAX_NUM as Element).collect::<Vec<Element>>();
+    let mut rank = 0;
+    for val in row.iter() {
+        let pos = remaining.iter().position(|v| v == val)?;
+        remaining.remove(pos);
+        rank = rank * (remaining.len() as u64 + 1) + pos as u64;
+    }
+    Some(rank)
+}
+
+fn unrank(mut rank: u64) -> Option<Vec<Element>> {
+    let mut digits = Vec::with_capacity(MAX_NUM);
+    for base in 1..=MAX_NUM as u64 {
+        digits.push((rank % base) as usize);
+        rank /= base;
+    }
+    if rank != 0 {
+        return None;
+    }
+    let mut remaining = (1..=MAX_NUM as Element).collect::<Vec<Element>>();
+    Some(digits.iter().rev().map(|d| remaining.remove(*d)).collect())
+}
+
+/// Writes packed puzzles and solutions one after another to a stream.
+pub struct PackedWriter<W: Write> {
+    inner: W,
+}
+
+impl<W: Write> PackedWriter<W> {
+    pub fn new(inner: W) -> PackedWriter<W> {
+        PackedWriter { inner }
+    }
+
+    pub fn write_puzzle(&mut self, puz: &Puzzle) -> Result<(), SudError> {
+        self.inner.write_all(&encode_puzzle(puz))?;
+        Ok(())
+    }
+
+    pub fn write_solution(&mut self, solution: &[Element]) -> Result<(), SudError> {
+        self.inner.write_all(&encode_solution(solution)?)?;
+        Ok(())
+    }
+
+    /// Flushes the stream and returns it.
+    pub fn into_inner(mut self) -> Result<W, SudError> {
+        self.inner.flush()?;
+        Ok(self.inner)
+    }
+}
+
+/// Reads packed puzzles and solutions from a stream, in the order they were written.
+pub struct PackedReader<R: Read> {
+    inner: R,
+}
+
+impl<R: Read> PackedReader<R> {
+    pub fn new(inner: R) -> PackedReader<R> {
+        PackedReader { inner }
+    }
+
+    /// Reads the next puzzle.  Returns `None` at the end of the stream.
+    pub fn read_puzzle(&mut self) -> Result<Option<Puzzle>, SudError> {
+        let mut bytes = vec![0; bitmap_len()];
+        if !self.read_record(&mut bytes)? {
+            return Ok(None);
+        }
+        let start = bytes.len();
+        bytes.resize(puzzle_len(&bytes), 0);
+        if self.fill(&mut bytes[start..])? != bytes.len() - start {
+            return Err(SudError::InvalidEncoding);
+        }
+        decode_puzzle(&bytes).map(Some)
+    }
+
+    /// Reads the next solution.  Returns `None` at the end of the stream.
+    pub fn read_solution(&mut self) -> Result<Option<Vec<Element>>, SudError> {
+        let mut bytes = vec![0; solution_len()];
+        if !self.read_record(&mut bytes)? {
+            return Ok(None);
+        }
+        decode_solution(&bytes).map(Some)
+    }
+
+    /// Iterates over the puzzles in the rest of the stream.
+    pub fn puzzles(mut self) -> impl Iterator<Item = Result<Puzzle, SudError>> {
+        std::iter::from_fn(move || self.read_puzzle().transpose())
+    }
+
+    /// Iterates over the solutions in the rest of the stream.
+    pub fn solutions(mut self) -> impl Iterator<Item = Result<Vec<Element>, SudError>> {
+        std::iter::from_fn(move || self.read_solution().transpose())
+    }
+
+    /// Fills the buffer.  Returns false if the stream ended before the first byte.
+    fn read_record(&mut self, buf: &mut [u8]) -> Result<bool, SudError> {
+        match self.fill(buf)? {
+            0 => Ok(false),
+            n if n == buf.len() => Ok(true),
+            _ => Err(SudError::InvalidEncoding),
+        }
+    }
+
+    /// Reads until the buffer is full or the stream ends.  Returns the number of bytes read.
+    fn fill(&mut self, buf: &mut [u8]) -> Result<usize, SudError> {
+        let mut filled = 0;
+        while filled < buf.len() {
+            match self.inner.read(&mut buf[filled..]) {
+                Ok(0) => break,
+                Ok(n) => filled += n,
+                Err(e) if e.kind() == ErrorKind::Interrupted => (),
+                Err(e) => return Err(e.into()),
+            }
+        }
+        Ok(filled)
+    }
+}
+
+#[cfg(test)]
+mod packed_tests {
+    use super::*;
+
+    const EXAMPLE: &str =
+        "53..7....6..195....98....6.8...6...34..8.3..17...2...6.6....28....419..5....8..79";
+    const SOLUTION: &str =
+        "534678912672195348198342567859761423426853791713924856961537284287419635345286179";
+
+    #[test]
+    fn puzzle_test() {
+        let puz: Puzzle = EXAMPLE.parse().unwrap();
+        let bytes = encode_puzzle(&puz);
+        // 11 bytes of bitmap and 30 numbers
+        assert_eq!(bytes.len(), 11 + 15);
+        let back = decode_puzzle(&bytes).unwrap();
+        assert_eq!(back.cells, puz.cells);
+
+        assert_eq!(decode_puzzle(&bytes[..20]), Err(SudError::InvalidEncoding));
+        let mut bad = bytes.clone();
+        bad[11] = 0xF5;
+        assert_eq!(decode_puzzle(&bad), Err(SudError::InvalidEncoding));
+
+        let mut empty = Puzzle::new();
+        empty.set_initial(vec![0; NUM_CELLS]);
+        let bytes = encode_puzzle(&empty);
+        assert_eq!(bytes, vec![0; 11]);
+        assert_eq!(decode_puzzle(&bytes).unwrap(), empty);
+    }
+
+    #[test]
+    fn solution_test() {
+        let solution = SOLUTION.as_input().unwrap();
+        let bytes = encode_solution(&solution).unwrap();
+        assert_eq!(bytes.len(), 19);
+        assert_eq!(decode_solution(&bytes).unwrap(), solution);
+
+        assert_eq!(rank(&[1, 2, 3, 4, 5, 6, 7, 8, 9]), Some(0));
+        assert_eq!(rank(&[9, 8, 7, 6, 5, 4, 3, 2, 1]), Some(362_879));
+        assert_eq!(unrank(362_879), Some(vec![9, 8, 7, 6, 5, 4, 3, 2, 1]));
+        assert_eq!(unrank(362_880), None);
+
+        // Not a solution: the first two numbers of the last row are swapped
+        let mut bad = solution.clone();
+        bad.swap(72, 73);
+        assert_eq!(encode_solution(&bad), Err(SudError::InvalidEncoding));
+        assert!(matches!(
+            encode_solution(&solution[..80]),
+            Err(SudError::WrongLength { found: 80, .. })
+        ));
+        // The last row is a copy of the first
+        let same_rows = encode_solution(&[&solution[..72], &solution[..9]].concat());
+        assert_eq!(same_rows, Err(SudError::InvalidEncoding));
+        // Every row and column is complete, but not the boxes
+        let shifted = (0..NUM_CELLS)
+            .map(|i| ((index_to_row(i) + index_to_col(i)) % MAX_NUM + 1) as Element)
+            .collect::<Vec<Element>>();
+        assert_eq!(encode_solution(&shifted), Err(SudError::InvalidEncoding));
+        let mut bits = BitWriter::new();
+        for row in shifted.chunks(MAX_NUM).take(MAX_NUM - 1) {
+            bits.push(rank(row).unwrap(), rank_bits());
+        }
+        assert_eq!(bits.bytes.len(), solution_len());
+        assert_eq!(decode_solution(&bits.bytes), Err(SudError::InvalidEncoding));
+    }
+
+    #[test]
+    fn stream_test() {
+        let mut puzzles = vec![EXAMPLE.parse::<Puzzle>().unwrap(), Puzzle::new()];
+        puzzles[1].set_initial(vec![0; NUM_CELLS]);
+        puzzles.push(SOLUTION.parse().unwrap());
+
+        let mut writer = PackedWriter::new(Vec::new());
+        for puz in puzzles.iter() {
+            writer.write_puzzle(puz).unwrap();
+        }
+        let bytes = writer.into_inner().unwrap();
+        assert_eq!(bytes.len(), 26 + 11 + (11 + 41));
+
+        let read = PackedReader::new(bytes.as_slice())
+            .puzzles()
+            .collect::<Result<Vec<Puzzle>, SudError>>()
+            .unwrap();
+        assert_eq!(read.len(), 3);
+        for (before, after) in puzzles.iter().zip(read.iter()) {
+            assert_eq!(before.cells, after.cells);
+        }
+
+        // A stream that ends in the middle of a puzzle
+        let mut reader = PackedReader::new(&bytes[..30]);
+        assert!(reader.read_puzzle().unwrap().is_some());
+        assert_eq!(reader.read_puzzle().err(), Some(SudError::InvalidEncoding));
+        // And in the middle of the numbers, after the bitmap
+        let mut reader = PackedReader::new(&bytes[..20]);
+        assert_eq!(reader.read_puzzle().err(), Some(SudError::InvalidEncoding));
+
+        let solution = SOLUTION.as_input().unwrap();
+        let mut writer = PackedWriter::new(Vec::new());
+        writer.write_solution(&solution).unwrap();
+        writer.write_solution(&solution).unwrap();
+        let bytes = writer.into_inner().unwrap();
+        let mut reader = PackedReader::new(bytes.as_slice());
+        assert_eq!(reader.read_solution().unwrap(), Some(solution.clone()));
+        assert_eq!(reader.solutions().count(), 1);
+    }
+}
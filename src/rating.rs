@@ -0,0 +1,395 @@
+//! Rating puzzles by the human solving techniques needed to solve them.
+use crate::*;
+use std::fmt;
+use std::str::FromStr;
+use std::time::Instant;
+
+/// A solving technique, in order from easiest to hardest.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Technique {
+    /// `single_candidate`: a cell with only one pencil mark.
+    NakedSingle,
+    /// `single_possibility`: a number with only one place in a row, column, or box.
+    HiddenSingle,
+    /// `naked_tuple`
+    NakedTuple,
+    /// `locked_candidates_pointing`
+    LockedCandidates,
+    /// Trying every possibility.  Not a human technique, but used when the others are stuck.
+    BruteForce,
+}
+
+impl Technique {
+    pub const ALL: [Technique; 5] = [
+        Technique::NakedSingle,
+        Technique::HiddenSingle,
+        Technique::NakedTuple,
+        Technique::LockedCandidates,
+        Technique::BruteForce,
+    ];
+
+    /// The human techniques, in the order they are tried.
+    pub const HUMAN: [Technique; 4] = [
+        Technique::NakedSingle,
+        Technique::HiddenSingle,
+        Technique::NakedTuple,
+        Technique::LockedCandidates,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Technique::NakedSingle => "naked single",
+            Technique::HiddenSingle => "hidden single",
+            Technique::NakedTuple => "naked tuple",
+            Technique::LockedCandidates => "locked candidates",
+            Technique::BruteForce => "brute force",
+        }
+    }
+
+    /// Applies the technique to the puzzle.
+    fn apply(&self, puz: &mut Puzzle) {
+        match self {
+            Technique::NakedSingle => {
+                puz.single_candidate(true);
+            }
+            Technique::HiddenSingle => {
+                puz.single_possibility(true);
+            }
+            Technique::NakedTuple => {
+                puz.naked_tuple();
+            }
+            Technique::LockedCandidates => {
+                puz.locked_candidates_pointing(true);
+            }
+            Technique::BruteForce => {
+                if let Some(solution) = puz.clone().brute_force_solve_max(1).pop() {
+                    for (index, val) in solution.into_iter().enumerate() {
+                        if puz.cells[index].num() == 0 {
+                            puz.cells[index].penciled.clear();
+                            puz.cells[index].set(val);
+                            puz.cells[index].fixed = true;
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl fmt::Display for Technique {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+/// Reads a technique's name.  Words may be separated by spaces, `-` or `_`.
+impl FromStr for Technique {
+    type Err = SudError;
+
+    fn from_str(s: &str) -> Result<Technique, SudError> {
+        let name = s
+            .to_ascii_lowercase()
+            .replace(['-', '_'], " ");
+        Technique::ALL
+            .iter()
+            .find(|t| t.name() == name)
+            .copied()
+            .ok_or_else(|| SudError::UnknownName {
+                kind: "technique",
+                name: s.to_string(),
+            })
+    }
+}
+
+/// The changes made by one use of a technique.  Placements and eliminations are written as
+/// (index, value).  Eliminations do not include the pencil marks of cells that were filled in.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Step {
+    pub technique: Technique,
+    pub placements: Vec<(usize, Element)>,
+    pub eliminations: Vec<(usize, Element)>,
+}
+
+impl Step {
+    /// Finds what changed between two states of a puzzle.
+    fn between(technique: Technique, before: &Puzzle, after: &Puzzle) -> Step {
+        let mut step = Step {
+            technique,
+            placements: Vec::new(),
+            eliminations: Vec::new(),
+        };
+        for (index, (old, new)) in before.cells.iter().zip(after.cells.iter()).enumerate() {
+            if old.num() == 0 && new.num() != 0 {
+                step.placements.push((index, new.num()));
+            } else if new.num() == 0 {
+                step.eliminations.extend(
+                    old.penciled()
+                        .difference(new.penciled())
+                        .map(|val| (index, *val)),
+                );
+            }
+        }
+        step
+    }
+}
+
+/// How hard a puzzle is to solve, found by `Puzzle::rate`.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Rating {
+    /// The human techniques used, in order.
+    pub steps: Vec<Step>,
+    /// True if the human techniques could not finish a puzzle that has a solution.
+    pub brute_force: bool,
+    /// The number of solutions, counting no more than 2.
+    pub solutions: usize,
+}
+
+impl Rating {
+    /// The number of times the technique was used.
+    pub fn count(&self, technique: Technique) -> usize {
+        match technique {
+            Technique::BruteForce => self.brute_force as usize,
+            _ => self
+                .steps
+                .iter()
+                .filter(|s| s.technique == technique)
+                .count(),
+        }
+    }
+
+    /// The hardest technique that was needed, or `None` if the puzzle was already solved.
+    pub fn hardest(&self) -> Option<Technique> {
+        if self.brute_force {
+            return Some(Technique::BruteForce);
+        }
+        self.steps.iter().map(|s| s.technique).max()
+    }
+}
+
+impl Puzzle {
+    /// Uses the easiest human technique that makes progress.  Returns `None` if the puzzle is
+    /// solved or none of the techniques help.
+    pub fn next_step(&mut self) -> Option<Step> {
+        if self.is_solved() {
+            return None;
+        }
+        for technique in Technique::HUMAN.iter() {
+            let before = self.clone();
+            technique.apply(self);
+            if before.cells != self.cells {
+                return Some(Step::between(*technique, &before, self));
+            }
+        }
+        None
+    }
+
+    /// Applies a technique whether or not it makes progress, and returns what changed.  Use
+    /// `Technique::BruteForce` to fill in the rest of the puzzle.
+    pub fn apply_technique(&mut self, technique: Technique) -> Step {
+        let before = self.clone();
+        technique.apply(self);
+        Step::between(technique, &before, self)
+    }
+
+    /// Finds the next number that can be filled in, from the numbers filled in so far, with the
+    /// pencil marks worked out again rather than taken from the cells.  The step's technique is the
+    /// hardest one needed, and its eliminations are the ones made on the way.  If the human
+    /// techniques get stuck, one cell is filled in from the solution with `Technique::BruteForce`.
+    /// Returns `None` if the puzzle is solved or cannot be solved from here.
+    pub fn hint(&self) -> Option<Step> {
+        self.hint_by(None).unwrap_or_default()
+    }
+
+    /// Same as `hint`, but gives up once the deadline has passed with `SudError::TimedOut`.
+    pub fn hint_until(&self, deadline: Instant) -> Result<Option<Step>, SudError> {
+        self.hint_by(Some(deadline))
+    }
+
+    fn hint_by(&self, deadline: Option<Instant>) -> Result<Option<Step>, SudError> {
+        let mut puz = self.clone();
+        puz.reset_pencil_marks();
+        if puz.is_solved() {
+            return Ok(None);
+        }
+        let solution = match puz.clone().search(1, deadline)?.pop() {
+            Some(solution) => solution,
+            None => return Ok(None),
+        };
+        let mut hint = Step {
+            technique: Technique::NakedSingle,
+            placements: Vec::new(),
+            eliminations: Vec::new(),
+        };
+        while hint.placements.is_empty() {
+            match puz.next_step() {
+                Some(step) => {
+                    hint.technique = hint.technique.max(step.technique);
+                    hint.placements = step.placements;
+                    hint.eliminations.extend(step.eliminations);
+                }
+                None => {
+                    let index = match puz.cells.iter().position(|c| c.num() == 0) {
+                        Some(index) => index,
+                        None => return Ok(None),
+                    };
+                    hint.technique = Technique::BruteForce;
+                    hint.placements.push((index, solution[index]));
+                }
+            }
+        }
+        Ok(Some(hint))
+    }
+
+    /// Solves a copy of the puzzle with the human techniques, trying the easiest first, and
+    /// counts the solutions.  A puzzle with no solution is not solved with the human techniques,
+    /// so it has no steps and no hardest technique.
+    pub fn rate(&self) -> Rating {
+        self.rate_by(None)
+            .expect("Only a deadline stops the search")
+    }
+
+    /// Same as `rate`, but gives up once the deadline has passed with `SudError::TimedOut`.
+    pub fn rate_until(&self, deadline: Instant) -> Result<Rating, SudError> {
+        self.rate_by(Some(deadline))
+    }
+
+    pub(crate) fn rate_by(&self, deadline: Option<Instant>) -> Result<Rating, SudError> {
+        let solutions = self.clone().search(2, deadline)?.len();
+        let mut puz = self.clone();
+        let mut steps = Vec::new();
+        if solutions > 0 {
+            while let Some(step) = puz.next_step() {
+                steps.push(step);
+            }
+        }
+        Ok(Rating {
+            steps,
+            brute_force: solutions > 0 && !puz.is_solved(),
+            solutions,
+        })
+    }
+}
+
+#[cfg(test)]
+mod rating_tests {
+    use super::*;
+
+    const EXAMPLE: &str =
+        "53..7....6..195....98....6.8...6...34..8.3..17...2...6.6....28....419..5....8..79";
+    const SOLUTION: &str =
+        "534678912672195348198342567859761423426853791713924856961537284287419635345286179";
+    // Needs more than the techniques above
+    const HARD: &str =
+        "..53.....8......2..7..1.5..4....53...1..7...6..32...8..6.5....9..4....3......97..";
+
+    #[test]
+    fn next_step_test() {
+        let mut puz: Puzzle = EXAMPLE.parse().unwrap();
+        let step = puz.next_step().unwrap();
+        assert_eq!(step.technique, Technique::NakedSingle);
+        assert!(!step.placements.is_empty());
+        for (index, val) in step.placements.iter() {
+            assert_eq!(puz.cells[*index].num(), *val);
+            assert_eq!(SOLUTION.as_bytes()[*index] - b'0', *val);
+        }
+
+        while puz.next_step().is_some() {}
+        assert!(puz.is_solved());
+        assert_eq!(puz.next_step(), None);
+    }
+
+    #[test]
+    fn rate_test() {
+        let puz: Puzzle = EXAMPLE.parse().unwrap();
+        let rating = puz.rate();
+        assert!(!rating.brute_force);
+        assert_eq!(rating.solutions, 1);
+        assert!(rating.count(Technique::NakedSingle) > 0);
+        assert_eq!(
+            rating.hardest(),
+            rating.steps.iter().map(|s| s.technique).max()
+        );
+        // Rating does not change the puzzle
+        assert_eq!(puz, EXAMPLE.parse().unwrap());
+
+        let solved: Puzzle = SOLUTION.parse().unwrap();
+        assert_eq!(solved.rate().hardest(), None);
+
+        let hard: Puzzle = HARD.parse().unwrap();
+        let rating = hard.rate();
+        assert!(rating.brute_force);
+        assert_eq!(rating.hardest(), Some(Technique::BruteForce));
+        assert_eq!(rating.count(Technique::BruteForce), 1);
+        assert_eq!(rating.solutions, 1);
+
+        let two: Puzzle =
+            "295743861431865900876192543387459216612387495549216738763524189928671354154938600"
+                .parse()
+                .unwrap();
+        assert_eq!(two.rate().solutions, 2);
+
+        // The first row needs a 9 where the column already has one
+        let none: Puzzle = format!("{:.<81}", "12345678.........9").parse().unwrap();
+        let rating = none.rate();
+        assert_eq!(rating.solutions, 0);
+        assert!(rating.steps.is_empty());
+        assert!(!rating.brute_force);
+        assert_eq!(rating.hardest(), None);
+    }
+
+    #[test]
+    fn hint_test() {
+        let mut puz: Puzzle = EXAMPLE.parse().unwrap();
+        // Pencil marks that were removed by hand are worked out again
+        puz.cells[2].penciled_mut().clear();
+        let step = puz.hint().unwrap();
+        assert_eq!(step.technique, Technique::NakedSingle);
+        assert_eq!(puz, {
+            let mut p: Puzzle = EXAMPLE.parse().unwrap();
+            p.cells[2].penciled_mut().clear();
+            p
+        });
+
+        // Following the hints solves the puzzle, with brute force once the techniques are stuck
+        let mut hard: Puzzle = HARD.parse().unwrap();
+        let solution = hard.clone().brute_force_solve_max(1).pop().unwrap();
+        let mut techniques = Vec::new();
+        while let Some(step) = hard.hint() {
+            for (index, val) in step.placements {
+                assert_eq!(solution[index], val);
+                hard.set_entry(index, val);
+            }
+            techniques.push(step.technique);
+        }
+        assert!(hard.is_solved());
+        assert!(techniques.contains(&Technique::BruteForce));
+
+        // A wrong number
+        puz.set_entry(2, 1);
+        assert_eq!(puz.hint(), None);
+        let solved: Puzzle = SOLUTION.parse().unwrap();
+        assert_eq!(solved.hint(), None);
+    }
+
+    #[test]
+    fn apply_technique_test() {
+        let mut puz: Puzzle = HARD.parse().unwrap();
+        let step = puz.apply_technique(Technique::BruteForce);
+        assert_eq!(step.placements.len(), 81 - 23);
+        assert!(puz.is_solved());
+        assert!(step.eliminations.is_empty());
+    }
+
+    #[test]
+    fn technique_name_test() {
+        for technique in Technique::ALL.iter() {
+            assert_eq!(technique.name().parse(), Ok(*technique));
+        }
+        assert_eq!("Hidden_Single".parse(), Ok(Technique::HiddenSingle));
+        assert_eq!("locked-candidates".parse(), Ok(Technique::LockedCandidates));
+        assert!("x-wing".parse::<Technique>().is_err());
+    }
+}
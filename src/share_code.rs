@@ -0,0 +1,303 @@
+//! Short, URL-safe codes for sharing a puzzle, optionally along with the player's progress and
+//! pencil marks.
+//!
+//! A code is the base64url encoding (without padding) of:
+//! - a version byte, currently 1,
+//! - a flags byte: bit 0 is set if progress follows, bit 1 if pencil marks follow,
+//! - the givens, packed as in `encode_puzzle`,
+//! - if flagged, the numbers filled in by the player, packed the same way,
+//! - if flagged, one bit for each possible number of each empty cell,
+//! - a two byte Fletcher-16 checksum of everything before it.
+//!
+//! Later versions may use the remaining flags to add data, such as variant constraints, before
+//! the checksum.
+use crate::packed::{pack_values, unpack_values, BitReader, BitWriter};
+use crate::*;
+
+const VERSION: u8 = 1;
+const PROGRESS: u8 = 0b01;
+const PENCIL_MARKS: u8 = 0b10;
+const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
+
+/// A share code that has been received.  It can be given to anything that accepts a `PuzInput`,
+/// which reads only the givens, or turned back into a `Puzzle` with `to_puzzle`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ShareCode<'a>(pub &'a str);
+
+impl Puzzle {
+    /// Returns a code for the puzzle that is safe to use in a URL.  If `progress` is true the
+    /// numbers filled in so far are included, and if `pencil_marks` is true the pencil marks of
+    /// the empty cells are included.
+    pub fn share_code(&self, progress: bool, pencil_marks: bool) -> String {
+        let mut flags = 0;
+        if progress {
+            flags |= PROGRESS;
+        }
+        if pencil_marks {
+            flags |= PENCIL_MARKS;
+        }
+
+        let mut bits = BitWriter::new();
+        bits.push(VERSION as u64, 8);
+        bits.push(flags as u64, 8);
+        let givens = self
+            .cells
+            .iter()
+            .map(|c| if c.given() { c.num() } else { 0 })
+            .collect::<Vec<Element>>();
+        pack_values(&mut bits, &givens);
+        if progress {
+            let filled = self
+                .cells
+                .iter()
+                .map(|c| if c.given() { 0 } else { c.num() })
+                .collect::<Vec<Element>>();
+            pack_values(&mut bits, &filled);
+        }
+        if pencil_marks {
+            bits.align();
+            for cell in self.cells.iter().filter(|c| shows_pencil(c, progress)) {
+                for val in 1..=MAX_NUM as Element {
+                    bits.push(cell.is_possible(val) as u64, 1);
+                }
+            }
+        }
+
+        let mut bytes = bits.bytes;
+        let sum = checksum(&bytes);
+        bytes.extend_from_slice(&sum);
+        to_base64(&bytes)
+    }
+}
+
+/// Whether the cell's pencil marks are part of the code.  Without progress, any cell that is not
+/// a given is empty.
+fn shows_pencil(cell: &Cell, progress: bool) -> bool {
+    if progress {
+        cell.num() == 0
+    } else {
+        !cell.given()
+    }
+}
+
+impl ShareCode<'_> {
+    /// Decodes the puzzle, along with the progress and pencil marks if the code has them.  Pencil
+    /// marks that are not in the code are calculated from the numbers.
+    pub fn to_puzzle(&self) -> Result<Puzzle, SudError> {
+        let bytes = from_base64(self.0)?;
+        if bytes.len() < 4 {
+            return Err(SudError::InvalidEncoding);
+        }
+        let (data, sum) = bytes.split_at(bytes.len() - 2);
+        if checksum(data) != sum {
+            return Err(SudError::ChecksumMismatch);
+        }
+        match data[0] {
+            VERSION => (),
+            v => return Err(SudError::UnsupportedVersion(v)),
+        }
+        let flags = data[1];
+        if flags & !(PROGRESS | PENCIL_MARKS) != 0 {
+            return Err(SudError::InvalidEncoding);
+        }
+
+        let mut bits = BitReader::new(data);
+        bits.position = 16;
+        let mut puz = Puzzle::new();
+        puz.set_initial(unpack_values(&mut bits)?);
+
+        let progress = flags & PROGRESS != 0;
+        if progress {
+            let filled = unpack_values(&mut bits)?;
+            for (index, val) in filled.into_iter().enumerate().filter(|(_, v)| *v != 0) {
+                if puz.cells[index].given() {
+                    return Err(SudError::InvalidEncoding);
+                }
+                let cell = &mut puz.cells[index];
+                cell.penciled.clear();
+                cell.set(val);
+                cell.fixed = true;
+                update_pencil_after_add(&mut puz, index);
+            }
+        }
+        if flags & PENCIL_MARKS != 0 {
+            bits.position = bits.position.div_ceil(8) * 8;
+            for cell in puz.cells.iter_mut().filter(|c| shows_pencil(c, progress)) {
+                cell.penciled.clear();
+                for val in 1..=MAX_NUM as Element {
+                    if bits.pull(1)? == 1 {
+                        cell.mark_possible(val);
+                    }
+                }
+            }
+        }
+        if data.len() != bits.position.div_ceil(8) {
+            return Err(SudError::InvalidEncoding);
+        }
+        Ok(puz)
+    }
+}
+
+impl PuzInput for ShareCode<'_> {
+    /// The givens of the puzzle.
+    fn as_input(&self) -> Result<Vec<Element>, SudError> {
+        Ok(self
+            .to_puzzle()?
+            .cells
+            .iter()
+            .map(|c| if c.given() { c.num() } else { 0 })
+            .collect())
+    }
+}
+
+/// Fletcher-16 checksum
+fn checksum(bytes: &[u8]) -> [u8; 2] {
+    let (mut low, mut high) = (0u16, 0u16);
+    for b in bytes.iter() {
+        low = (low + *b as u16) % 255;
+        high = (high + low) % 255;
+    }
+    [high as u8, low as u8]
+}
+
+fn to_base64(bytes: &[u8]) -> String {
+    let mut bits = BitReader::new(bytes);
+    let mut out = String::with_capacity((bytes.len() * 8).div_ceil(6));
+    while bits.position < bytes.len() * 8 {
+        let remaining = bytes.len() * 8 - bits.position;
+        let value = if remaining >= 6 {
+            bits.pull(6).unwrap()
+        } else {
+            bits.pull(remaining).unwrap() << (6 - remaining)
+        };
+        out.push(ALPHABET[value as usize] as char);
+    }
+    out
+}
+
+fn from_base64(text: &str) -> Result<Vec<u8>, SudError> {
+    let mut bits = BitWriter::new();
+    for (col, c) in text.trim().chars().enumerate() {
+        let value = ALPHABET
+            .iter()
+            .position(|a| *a as char == c)
+            .ok_or(SudError::InvalidChar {
+                line: 1,
+                column: col + 1,
+                found: c,
+            })?;
+        bits.push(value as u64, 6);
+    }
+    // The last partial byte is padding
+    let mut bytes = bits.bytes;
+    if !(text.trim().len() * 6).is_multiple_of(8) {
+        bytes.pop();
+    }
+    Ok(bytes)
+}
+
+#[cfg(test)]
+mod share_code_tests {
+    use super::*;
+
+    const EXAMPLE: &str =
+        "53..7....6..195....98....6.8...6...34..8.3..17...2...6.6....28....419..5....8..79";
+
+    #[test]
+    fn givens_test() {
+        let puz: Puzzle = EXAMPLE.parse().unwrap();
+        let code = puz.share_code(false, false);
+        assert!(code.len() <= 40);
+        assert!(code
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_'));
+
+        let input = ShareCode(&code).as_input().unwrap();
+        assert_eq!(input, EXAMPLE.as_input().unwrap());
+        assert_eq!(ShareCode(&code).to_puzzle().unwrap(), puz);
+    }
+
+    #[test]
+    fn progress_test() {
+        let mut puz: Puzzle = EXAMPLE.parse().unwrap();
+        // The player has filled in two cells
+        for (index, val) in [(2, 4), (3, 6)].iter() {
+            puz.cells[*index].penciled.clear();
+            puz.cells[*index].set(*val);
+            puz.cells[*index].fixed = true;
+            update_pencil_after_add(&mut puz, *index);
+        }
+        let index = (0..NUM_CELLS)
+            .find(|i| puz.cells[*i].penciled().len() > 1)
+            .unwrap();
+        let removed = *puz.cells[index].penciled().iter().next().unwrap();
+        puz.cells[index].remove_possible(removed);
+
+        // Progress without pencil marks recalculates the pencil marks
+        let code = puz.share_code(true, false);
+        let back = ShareCode(&code).to_puzzle().unwrap();
+        for (before, after) in puz.cells.iter().zip(back.cells.iter()) {
+            assert_eq!(before.num(), after.num());
+            assert_eq!(before.given(), after.given());
+        }
+        assert!(back.cells[index].is_possible(removed));
+
+        let code = puz.share_code(true, true);
+        assert_eq!(ShareCode(&code).to_puzzle().unwrap(), puz);
+        // Only the givens are used as input
+        assert_eq!(
+            ShareCode(&code).as_input().unwrap(),
+            EXAMPLE.as_input().unwrap()
+        );
+
+        // Pencil marks without progress
+        let code = puz.share_code(false, true);
+        let back = ShareCode(&code).to_puzzle().unwrap();
+        assert_eq!(back.cells[index].penciled(), puz.cells[index].penciled());
+        assert_eq!(back.cells.iter().filter(|c| c.num() != 0).count(), 30);
+    }
+
+    #[test]
+    fn errors_test() {
+        let puz: Puzzle = EXAMPLE.parse().unwrap();
+        let code = puz.share_code(false, false);
+
+        // A typo in any position is caught
+        for i in 0..code.len() {
+            let mut typo = code.clone().into_bytes();
+            typo[i] = if typo[i] == b'A' { b'B' } else { b'A' };
+            let typo = String::from_utf8(typo).unwrap();
+            assert!(ShareCode(&typo).to_puzzle().is_err(), "typo at {}", i);
+        }
+        assert_eq!(
+            ShareCode(&code[..code.len() - 1]).to_puzzle(),
+            Err(SudError::ChecksumMismatch)
+        );
+        assert!(matches!(
+            ShareCode("AB+C").to_puzzle(),
+            Err(SudError::InvalidChar {
+                column: 3,
+                found: '+',
+                ..
+            })
+        ));
+
+        // A later version
+        let mut bytes = vec![2, 0];
+        let sum = checksum(&bytes);
+        bytes.extend_from_slice(&sum);
+        assert_eq!(
+            ShareCode(&to_base64(&bytes)).to_puzzle(),
+            Err(SudError::UnsupportedVersion(2))
+        );
+    }
+
+    #[test]
+    fn base64_test() {
+        assert_eq!(to_base64(b"Man"), "TWFu");
+        assert_eq!(to_base64(b"Ma"), "TWE");
+        assert_eq!(from_base64("TWE").unwrap(), b"Ma".to_vec());
+        assert_eq!(from_base64("TWFu").unwrap(), b"Man".to_vec());
+        assert_eq!(to_base64(&[0xfb, 0xff]), "-_8");
+    }
+}
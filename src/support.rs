@@ -42,8 +42,54 @@ pub fn index_from_col(col: usize, index: usize) -> usize {
     index * MAX_NUM + col
 }
 
+/// Returns the indices of the cells directly above, below, left and right of the cell.
+pub fn orthogonal_neighbors(index: usize) -> Vec<usize> {
+    let (row, col) = (index_to_row(index), index_to_col(index));
+    let mut neighbors = Vec::with_capacity(4);
+    if row > 0 {
+        neighbors.push(index - MAX_NUM);
+    }
+    if row < MAX_NUM - 1 {
+        neighbors.push(index + MAX_NUM);
+    }
+    if col > 0 {
+        neighbors.push(index - 1);
+    }
+    if col < MAX_NUM - 1 {
+        neighbors.push(index + 1);
+    }
+    neighbors
+}
+
+/// True if the two cells share an edge
+pub fn are_adjacent(first: usize, second: usize) -> bool {
+    orthogonal_neighbors(first).contains(&second)
+}
+
 
 
+/// A row, column, or box of the puzzle, each numbered from 0.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum House {
+    Row(usize),
+    Col(usize),
+    Box(usize),
+}
+
+impl House {
+    /// The indices of the cells in the house
+    pub fn cells(&self) -> Vec<usize> {
+        (0..MAX_NUM)
+            .map(|i| match *self {
+                House::Row(row) => index_from_row(row, i),
+                House::Col(col) => index_from_col(col, i),
+                House::Box(box_num) => index_from_box(box_num, i),
+            })
+            .collect()
+    }
+}
+
 #[cfg(test)]
 mod support_tests {
 
@@ -56,4 +102,13 @@ mod support_tests {
         assert_eq!(index_from_box(8, 8), 80);
         assert_eq!(index_from_box(2, 5), 17);
     }
+
+    #[test]
+    fn neighbors_test() {
+        assert_eq!(orthogonal_neighbors(0), vec![9, 1]);
+        assert_eq!(orthogonal_neighbors(40), vec![31, 49, 39, 41]);
+        assert_eq!(orthogonal_neighbors(80), vec![71, 79]);
+        assert!(are_adjacent(8, 17));
+        assert!(!are_adjacent(8, 9));
+    }
 }
@@ -0,0 +1,355 @@
+//! Draws a puzzle as an SVG image, optionally with pencil marks and the cells, candidates, links
+//! and houses used by a solving step.
+use crate::*;
+use std::fmt::Write;
+
+const GIVEN_COLOR: &str = "#000000";
+const SOLVED_COLOR: &str = "#1a5fb4";
+const PENCIL_COLOR: &str = "#555555";
+const CELL_HIGHLIGHT: &str = "#cde4ff";
+const HOUSE_HIGHLIGHT: &str = "#fff3c4";
+const CANDIDATE_HIGHLIGHT: &str = "#9fe39f";
+const LINK_COLOR: &str = "#c01c28";
+
+/// Something to draw attention to on top of the puzzle.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Highlight {
+    /// Shades the cell at the index.
+    Cell(usize),
+    /// Circles a pencil mark of the cell at the index.
+    Candidate(usize, Element),
+    /// Draws a line between two candidates, written as (index, value).  Strong links are solid
+    /// and weak links are dashed.
+    Link {
+        from: (usize, Element),
+        to: (usize, Element),
+        strong: bool,
+    },
+    /// Shades a row, column, or box.
+    House(House),
+}
+
+/// Settings for drawing a puzzle.  Givens are drawn in bold black, and solved numbers in blue.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Svg {
+    cell_size: usize,
+    pencil_marks: bool,
+    highlights: Vec<Highlight>,
+}
+
+impl Default for Svg {
+    fn default() -> Self {
+        Svg {
+            cell_size: 50,
+            pencil_marks: false,
+            highlights: Vec::new(),
+        }
+    }
+}
+
+impl Svg {
+    pub fn new() -> Svg {
+        Svg::default()
+    }
+
+    /// The width and height of each cell, in pixels.
+    pub fn cell_size(&mut self, size: usize) -> &mut Self {
+        self.cell_size = size;
+        self
+    }
+
+    /// Draws the pencil marks of the empty cells.
+    pub fn pencil_marks(&mut self, show: bool) -> &mut Self {
+        self.pencil_marks = show;
+        self
+    }
+
+    /// Adds something to draw attention to.  Panics if a cell or house is not in the grid, or a
+    /// candidate is not from 1 to 9.
+    pub fn highlight(&mut self, highlight: Highlight) -> &mut Self {
+        let mut candidates = Vec::new();
+        match &highlight {
+            Highlight::Cell(index) => {
+                assert!(*index < NUM_CELLS, "cell {} is not in the grid", index)
+            }
+            Highlight::Candidate(index, val) => candidates.push((*index, *val)),
+            Highlight::Link { from, to, .. } => candidates.extend([*from, *to].iter()),
+            Highlight::House(house) => {
+                let number = match house {
+                    House::Row(n) | House::Col(n) | House::Box(n) => *n,
+                };
+                assert!(number < MAX_NUM, "{:?} is not in the grid", house)
+            }
+        }
+        for (index, val) in candidates {
+            assert!(index < NUM_CELLS, "cell {} is not in the grid", index);
+            assert!(
+                val >= 1 && val as usize <= MAX_NUM,
+                "candidate {} is not from 1 to {}",
+                val,
+                MAX_NUM
+            );
+        }
+        self.highlights.push(highlight);
+        self
+    }
+
+    pub fn highlights(&self) -> &[Highlight] {
+        &self.highlights
+    }
+
+    /// Returns the SVG document for the puzzle.
+    pub fn render(&self, puz: &Puzzle) -> String {
+        let size = self.cell_size as f64;
+        let margin = size / 10.0;
+        let width = size * MAX_NUM as f64 + 2.0 * margin;
+
+        let mut out = String::new();
+        // Writing to a String cannot fail
+        let _ = writeln!(
+            out,
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="{w:.1}" height="{w:.1}" viewBox="0 0 {w:.1} {w:.1}">"#,
+            w = width
+        );
+        let _ = writeln!(
+            out,
+            r##"<rect x="0" y="0" width="{w:.1}" height="{w:.1}" fill="#ffffff"/>"##,
+            w = width
+        );
+        let _ = writeln!(
+            out,
+            r#"<g transform="translate({m:.1},{m:.1})">"#,
+            m = margin
+        );
+
+        self.draw_shading(&mut out);
+        self.draw_grid(&mut out);
+        for (index, cell) in puz.cells.iter().enumerate() {
+            self.draw_cell(&mut out, index, cell);
+        }
+        self.draw_links(&mut out);
+
+        out.push_str("</g>\n</svg>\n");
+        out
+    }
+
+    fn draw_shading(&self, out: &mut String) {
+        let size = self.cell_size as f64;
+        for highlight in self.highlights.iter() {
+            let (cells, color) = match highlight {
+                Highlight::House(house) => (house.cells(), HOUSE_HIGHLIGHT),
+                Highlight::Cell(index) => (vec![*index], CELL_HIGHLIGHT),
+                _ => continue,
+            };
+            for index in cells {
+                let _ = writeln!(
+                    out,
+                    r#"<rect x="{:.1}" y="{:.1}" width="{s:.1}" height="{s:.1}" fill="{}"/>"#,
+                    index_to_col(index) as f64 * size,
+                    index_to_row(index) as f64 * size,
+                    color,
+                    s = size
+                );
+            }
+        }
+    }
+
+    fn draw_grid(&self, out: &mut String) {
+        let size = self.cell_size as f64;
+        let end = size * MAX_NUM as f64;
+        for i in 0..=MAX_NUM {
+            let pos = i as f64 * size;
+            let stroke = if i % BOX_DIMEN == 0 {
+                size / 16.0
+            } else {
+                size / 50.0
+            };
+            let _ = writeln!(
+                out,
+                r##"<line x1="{p:.1}" y1="0" x2="{p:.1}" y2="{e:.1}" stroke="#000000" stroke-width="{w:.2}" stroke-linecap="square"/>"##,
+                p = pos,
+                e = end,
+                w = stroke
+            );
+            let _ = writeln!(
+                out,
+                r##"<line x1="0" y1="{p:.1}" x2="{e:.1}" y2="{p:.1}" stroke="#000000" stroke-width="{w:.2}" stroke-linecap="square"/>"##,
+                p = pos,
+                e = end,
+                w = stroke
+            );
+        }
+    }
+
+    fn draw_cell(&self, out: &mut String, index: usize, cell: &Cell) {
+        let size = self.cell_size as f64;
+        let (x, y) = (
+            (index_to_col(index) as f64 + 0.5) * size,
+            (index_to_row(index) as f64 + 0.5) * size,
+        );
+
+        if cell.num() != 0 {
+            let (color, weight) = if cell.given() {
+                (GIVEN_COLOR, "bold")
+            } else {
+                (SOLVED_COLOR, "normal")
+            };
+            let _ = writeln!(
+                out,
+                r#"<text x="{:.1}" y="{:.1}" font-family="sans-serif" font-size="{:.1}" font-weight="{}" fill="{}" text-anchor="middle" dominant-baseline="central">{}</text>"#,
+                x,
+                y,
+                size * 0.7,
+                weight,
+                color,
+                symbol(cell.num())
+            );
+            return;
+        }
+
+        for val in cell.poss_iter().cloned() {
+            let (cx, cy) = self.candidate_center(index, val);
+            if self.highlights.contains(&Highlight::Candidate(index, val)) {
+                let _ = writeln!(
+                    out,
+                    r#"<circle cx="{:.1}" cy="{:.1}" r="{:.1}" fill="{}"/>"#,
+                    cx,
+                    cy,
+                    size / (2.0 * BOX_DIMEN as f64),
+                    CANDIDATE_HIGHLIGHT
+                );
+            }
+            if self.pencil_marks {
+                let _ = writeln!(
+                    out,
+                    r#"<text x="{:.1}" y="{:.1}" font-family="sans-serif" font-size="{:.1}" fill="{}" text-anchor="middle" dominant-baseline="central">{}</text>"#,
+                    cx,
+                    cy,
+                    size * 0.8 / BOX_DIMEN as f64,
+                    PENCIL_COLOR,
+                    symbol(val)
+                );
+            }
+        }
+    }
+
+    fn draw_links(&self, out: &mut String) {
+        for highlight in self.highlights.iter() {
+            if let Highlight::Link { from, to, strong } = highlight {
+                let (x1, y1) = self.candidate_center(from.0, from.1);
+                let (x2, y2) = self.candidate_center(to.0, to.1);
+                let dash = if *strong {
+                    ""
+                } else {
+                    r#" stroke-dasharray="4,3""#
+                };
+                let _ = writeln!(
+                    out,
+                    r#"<line x1="{:.1}" y1="{:.1}" x2="{:.1}" y2="{:.1}" stroke="{}" stroke-width="{:.2}"{}/>"#,
+                    x1,
+                    y1,
+                    x2,
+                    y2,
+                    LINK_COLOR,
+                    self.cell_size as f64 / 25.0,
+                    dash
+                );
+            }
+        }
+    }
+
+    /// The position of a pencil mark within its cell.
+    fn candidate_center(&self, index: usize, val: Element) -> (f64, f64) {
+        let size = self.cell_size as f64;
+        let mini = size / BOX_DIMEN as f64;
+        let position = (val as usize - 1) % MAX_NUM;
+        (
+            index_to_col(index) as f64 * size + ((position % BOX_DIMEN) as f64 + 0.5) * mini,
+            index_to_row(index) as f64 * size + ((position / BOX_DIMEN) as f64 + 0.5) * mini,
+        )
+    }
+}
+
+impl Puzzle {
+    /// Draws the puzzle as an SVG image with the default settings and no pencil marks.
+    pub fn to_svg(&self) -> String {
+        Svg::new().render(self)
+    }
+}
+
+#[cfg(test)]
+mod svg_tests {
+    use super::*;
+
+    const EXAMPLE: &str =
+        "53..7....6..195....98....6.8...6...34..8.3..17...2...6.6....28....419..5....8..79";
+
+    #[test]
+    fn givens_and_solved_test() {
+        let mut puz: Puzzle = EXAMPLE.parse().unwrap();
+        let svg = puz.to_svg();
+        assert!(svg.starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\""));
+        assert!(svg.ends_with("</svg>\n"));
+        assert_eq!(svg.matches("font-weight=\"bold\"").count(), 30);
+        assert_eq!(svg.matches("<text").count(), 30);
+        // 10 lines in each direction
+        assert_eq!(svg.matches("<line").count(), 20);
+
+        let solved = puz.single_candidate(true).len();
+        assert!(solved > 0);
+        let svg = puz.to_svg();
+        assert_eq!(svg.matches("font-weight=\"bold\"").count(), 30);
+        assert_eq!(svg.matches(SOLVED_COLOR).count(), solved);
+    }
+
+    #[test]
+    fn pencil_marks_test() {
+        let puz: Puzzle = EXAMPLE.parse().unwrap();
+        let marks = puz.cells.iter().map(|c| c.penciled().len()).sum::<usize>();
+        let svg = Svg::new().pencil_marks(true).render(&puz);
+        assert_eq!(svg.matches(PENCIL_COLOR).count(), marks);
+        // Cell 2 can be 1, 2 or 4.  The 4 is in the middle row on the left.
+        assert!(svg.contains(
+            r##"<text x="108.3" y="25.0" font-family="sans-serif" font-size="13.3" fill="#555555""##
+        ));
+    }
+
+    #[test]
+    fn highlight_test() {
+        let puz: Puzzle = EXAMPLE.parse().unwrap();
+        let svg = Svg::new()
+            .cell_size(30)
+            .highlight(Highlight::House(House::Row(0)))
+            .highlight(Highlight::Cell(2))
+            .highlight(Highlight::Candidate(2, 4))
+            .highlight(Highlight::Candidate(2, 5))
+            .highlight(Highlight::Link {
+                from: (2, 1),
+                to: (20, 1),
+                strong: false,
+            })
+            .render(&puz);
+        assert_eq!(svg.matches(HOUSE_HIGHLIGHT).count(), MAX_NUM);
+        assert_eq!(svg.matches(CELL_HIGHLIGHT).count(), 1);
+        // 5 is not a candidate of cell 2
+        assert_eq!(svg.matches(CANDIDATE_HIGHLIGHT).count(), 1);
+        assert_eq!(svg.matches("stroke-dasharray").count(), 1);
+        assert!(svg.contains(r#"<line x1="65.0" y1="5.0" x2="65.0" y2="65.0""#));
+    }
+
+    #[test]
+    #[should_panic(expected = "candidate 0 is not from 1 to 9")]
+    fn link_value_test() {
+        Svg::new().highlight(Highlight::Link {
+            from: (2, 1),
+            to: (20, 0),
+            strong: true,
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "Box(12) is not in the grid")]
+    fn house_test() {
+        Svg::new().highlight(Highlight::House(House::Box(12)));
+    }
+}
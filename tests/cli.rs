@@ -0,0 +1,114 @@
+//! Runs the `sudoku` binary the way a user would.
+use std::io::Write;
+use std::process::{Command, Output, Stdio};
+
+const EXAMPLE: &str =
+    "53..7....6..195....98....6.8...6...34..8.3..17...2...6.6....28....419..5....8..79";
+const SOLUTION: &str =
+    "534678912672195348198342567859761423426853791713924856961537284287419635345286179";
+const TWO_SOLUTIONS: &str =
+    "295743861431865900876192543387459216612387495549216738763524189928671354154938600";
+
+fn run(args: &[&str], input: &str) -> Output {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_sudoku"))
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    // The command may exit without reading its input
+    let _ = child.stdin.take().unwrap().write_all(input.as_bytes());
+    child.wait_with_output().unwrap()
+}
+
+fn stdout(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stdout).to_string()
+}
+
+#[test]
+fn solve_stdin_test() {
+    let input = format!("# comment\n{}\n\n{}\n", EXAMPLE, TWO_SOLUTIONS);
+    let output = run(&["solve"], &input);
+    assert_eq!(output.status.code(), Some(0));
+    let lines = stdout(&output);
+    let lines = lines.lines().collect::<Vec<&str>>();
+    assert_eq!(lines.len(), 2);
+    assert_eq!(lines[0], SOLUTION);
+
+    let output = run(&["solve", "--max-solutions", "5"], &input);
+    assert_eq!(stdout(&output).lines().count(), 3);
+
+    let output = run(&["solve", "--pretty"], EXAMPLE);
+    assert!(stdout(&output).starts_with("+-------+-------+-------+\n| 5 3 4 |"));
+}
+
+#[test]
+fn solve_exit_codes_test() {
+    // Two 5s in the first row
+    let output = run(&["solve"], &EXAMPLE.replacen('3', "5", 1));
+    assert_eq!(output.status.code(), Some(2));
+    assert!(String::from_utf8_lossy(&output.stderr).starts_with("<stdin>:1: "));
+
+    let output = run(&["solve", "--bogus"], EXAMPLE);
+    assert_eq!(output.status.code(), Some(1));
+
+    let output = run(&["solve", "no/such/file.txt"], "");
+    assert_eq!(output.status.code(), Some(1));
+}
+
+#[test]
+fn rate_test() {
+    let input = format!("{}\nnot a puzzle\n{}\n", EXAMPLE, TWO_SOLUTIONS);
+    let output = run(&["rate"], &input);
+    assert_eq!(output.status.code(), Some(2));
+    let lines = stdout(&output);
+    let lines = lines.lines().collect::<Vec<&str>>();
+    assert_eq!(lines.len(), 3);
+    assert!(lines[0].starts_with("source,line,puzzle,hardest,naked_single,"));
+    assert!(lines[1].starts_with(&format!("<stdin>,1,{},", EXAMPLE)));
+    assert!(lines[2].contains(",2,"));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("Puzzles: 2 (1 invalid)"));
+
+    let output = run(&["rate", "--format", "json"], EXAMPLE);
+    assert_eq!(output.status.code(), Some(0));
+    let json = stdout(&output);
+    assert!(json.contains("\"summary\": {\"puzzles\": 1,"));
+    assert!(json.contains("\"brute_force\": false, \"solutions\": 1,"));
+}
+
+#[test]
+fn generate_test() {
+    let args = ["generate", "-n", "2", "--seed", "5", "-c", "30-34", "-y", "rotational"];
+    let output = run(&args, "");
+    assert_eq!(output.status.code(), Some(0));
+    let puzzles = stdout(&output);
+    let lines = puzzles.lines().collect::<Vec<&str>>();
+    assert_eq!(lines.len(), 2);
+    for line in lines.iter() {
+        assert_eq!(line.len(), 81);
+        assert!(line.chars().all(|c| c.is_ascii_digit()));
+    }
+    // The same seed generates the same puzzles, which solve uniquely
+    assert_eq!(stdout(&run(&args, "")), puzzles);
+    let output = run(&["solve", "-n", "2"], &puzzles);
+    assert_eq!(stdout(&output).lines().count(), 2);
+
+    let output = run(&["generate", "--size", "16"], "");
+    assert_eq!(output.status.code(), Some(1));
+    let output = run(&["generate", "-c", "75-81", "-d", "brute-force", "-a", "2"], "");
+    assert_eq!(output.status.code(), Some(2));
+}
+
+#[test]
+fn repl_test() {
+    let output = run(&["repl", EXAMPLE], "set r1c3 4\nbogus\nundo\nquit\nshow\n");
+    assert_eq!(output.status.code(), Some(0));
+    let text = stdout(&output);
+    assert!(text.starts_with("r1c3 = 4\nr1c6 -4\n"));
+    assert!(text.contains("\nError: Unknown command 'bogus'"));
+    assert!(text.contains("\nr1c3 cleared (was 4)\nr1c6 +4\n"));
+    // Nothing is run after quit
+    assert!(text.ends_with("r9c3 +4\n"));
+}
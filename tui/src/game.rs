@@ -0,0 +1,367 @@
+//! The state of a game, separate from drawing it, so that it can be tested without a terminal.
+use std::time::{Duration, Instant};
+use sudoku::{get_cell, index_to_col, index_to_row, Puzzle, ShareCode};
+
+/// The width of the grid.
+pub const SIZE: usize = 9;
+
+/// The first line of a save file.
+const SAVE_HEADER: &str = "# sudoku-tui save";
+
+pub struct Game {
+    /// The givens, the player's numbers and the player's pencil marks
+    pub puzzle: Puzzle,
+    pub cursor: usize,
+    /// When true, digits toggle pencil marks instead of filling in the cell
+    pub pencil_mode: bool,
+    /// Cells picked out by the last hint
+    pub hint: Vec<usize>,
+    pub message: String,
+    undo: Vec<Puzzle>,
+    redo: Vec<Puzzle>,
+    /// Time played before `started`
+    elapsed: Duration,
+    /// When the clock was last started, or `None` once the puzzle is solved
+    started: Option<Instant>,
+}
+
+impl Game {
+    /// Starts a new game.  The player starts without any pencil marks.
+    pub fn new(mut puzzle: Puzzle) -> Game {
+        for cell in puzzle.cells.iter_mut() {
+            cell.penciled_mut().clear();
+        }
+        Game::resume(puzzle, Duration::default())
+    }
+
+    /// Carries on with a game that has already been played for `elapsed`.
+    pub fn resume(puzzle: Puzzle, elapsed: Duration) -> Game {
+        let mut game = Game {
+            puzzle,
+            cursor: 0,
+            pencil_mode: false,
+            hint: Vec::new(),
+            message: String::new(),
+            undo: Vec::new(),
+            redo: Vec::new(),
+            elapsed,
+            started: Some(Instant::now()),
+        };
+        game.check_solved();
+        game
+    }
+
+    /// Moves the cursor, wrapping around the edges of the grid.
+    pub fn move_cursor(&mut self, rows: isize, cols: isize) {
+        let size = SIZE as isize;
+        let row = (index_to_row(self.cursor) as isize + rows).rem_euclid(size);
+        let col = (index_to_col(self.cursor) as isize + cols).rem_euclid(size);
+        self.cursor = get_cell(row as usize, col as usize);
+    }
+
+    /// Fills in the cell under the cursor, or toggles the pencil mark in pencil mode.
+    pub fn digit(&mut self, val: u8) {
+        if self.is_solved() || self.puzzle.cells[self.cursor].given() {
+            return;
+        }
+        let before = self.puzzle.clone();
+        let cell = &mut self.puzzle.cells[self.cursor];
+        if self.pencil_mode {
+            if cell.num() != 0 {
+                return;
+            }
+            if !cell.remove_possible(val) {
+                cell.mark_possible(val);
+            }
+        } else if cell.num() == val {
+            return;
+        } else {
+            self.puzzle.set_entry(self.cursor, val);
+        }
+        self.push_undo(before);
+        self.check_solved();
+    }
+
+    /// Empties the cell under the cursor, or clears its pencil marks if it is already empty.
+    pub fn clear(&mut self) {
+        let cell = &self.puzzle.cells[self.cursor];
+        if self.is_solved() || cell.given() || (cell.num() == 0 && cell.penciled().is_empty()) {
+            return;
+        }
+        let before = self.puzzle.clone();
+        self.puzzle.set_entry(self.cursor, 0);
+        self.push_undo(before);
+    }
+
+    fn push_undo(&mut self, before: Puzzle) {
+        self.undo.push(before);
+        self.redo.clear();
+        self.hint.clear();
+        self.message.clear();
+    }
+
+    /// Goes back to before the last change.  A solved game stays solved, as the clock has stopped.
+    pub fn undo(&mut self) -> bool {
+        if self.is_solved() {
+            return false;
+        }
+        match self.undo.pop() {
+            Some(puzzle) => {
+                self.redo.push(std::mem::replace(&mut self.puzzle, puzzle));
+                self.hint.clear();
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn redo(&mut self) -> bool {
+        match self.redo.pop() {
+            Some(puzzle) => {
+                self.undo.push(std::mem::replace(&mut self.puzzle, puzzle));
+                self.check_solved();
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// True if the cell has a number that is repeated in its row, column or box.
+    pub fn conflict(&self, index: usize) -> bool {
+        self.puzzle.cells[index].num() != 0 && !self.puzzle.valid_entry(index)
+    }
+
+    pub fn is_solved(&self) -> bool {
+        self.started.is_none()
+    }
+
+    fn check_solved(&mut self) {
+        if self.started.is_some() && self.puzzle.is_solved() {
+            self.elapsed = self.elapsed();
+            self.started = None;
+            self.message = format!("Solved in {}", clock(self.elapsed));
+        }
+    }
+
+    pub fn elapsed(&self) -> Duration {
+        match self.started {
+            Some(started) => self.elapsed + started.elapsed(),
+            None => self.elapsed,
+        }
+    }
+
+    /// Finds the next number with the easiest human technique that applies, and moves the cursor
+    /// to it.
+    pub fn hint(&mut self) {
+        if self.is_solved() {
+            return;
+        }
+        self.hint = (0..self.puzzle.cells.len())
+            .filter(|i| self.conflict(*i))
+            .collect();
+        if !self.hint.is_empty() {
+            self.message = "Some numbers conflict with each other".to_string();
+            return;
+        }
+        match self.puzzle.hint() {
+            Some(step) => {
+                let (index, val) = step.placements[0];
+                self.hint = vec![index];
+                self.message = format!("{}: {} is {}", step.technique, at(index), val);
+                self.cursor = index;
+            }
+            None => {
+                self.message = "There is a mistake: the puzzle cannot be solved from here".into();
+            }
+        }
+    }
+
+    /// The game as text, which can be read back with `load`.
+    pub fn save(&self) -> String {
+        format!(
+            "{}\ncode {}\ntime {}\n",
+            SAVE_HEADER,
+            self.puzzle.share_code(true, true),
+            self.elapsed().as_secs()
+        )
+    }
+
+    /// Reads a game written by `save`, or starts a new game from a puzzle in any format the
+    /// library can parse.
+    pub fn load(text: &str) -> Result<Game, String> {
+        if !text.starts_with(SAVE_HEADER) {
+            let puzzle = text.parse::<Puzzle>().map_err(|e| e.to_string())?;
+            return Ok(Game::new(puzzle));
+        }
+        let mut code = None;
+        let mut time = 0;
+        for line in text.lines().skip(1) {
+            let mut parts = line.splitn(2, ' ');
+            match (parts.next(), parts.next()) {
+                (Some("code"), Some(value)) => code = Some(value.trim().to_string()),
+                (Some("time"), Some(value)) => {
+                    time = value
+                        .trim()
+                        .parse()
+                        .map_err(|_| format!("Invalid time '{}'", value))?
+                }
+                _ => (),
+            }
+        }
+        let code = code.ok_or_else(|| "The save file has no puzzle".to_string())?;
+        let puzzle = ShareCode(&code).to_puzzle().map_err(|e| e.to_string())?;
+        Ok(Game::resume(puzzle, Duration::from_secs(time)))
+    }
+}
+
+/// A cell's position for messages, such as `row 3, column 4`.
+fn at(index: usize) -> String {
+    format!(
+        "row {}, column {}",
+        index_to_row(index) + 1,
+        index_to_col(index) + 1
+    )
+}
+
+/// Formats a time as `m:ss`, or `h:mm:ss` after an hour.
+pub fn clock(time: Duration) -> String {
+    let secs = time.as_secs();
+    if secs >= 3600 {
+        format!("{}:{:02}:{:02}", secs / 3600, secs / 60 % 60, secs % 60)
+    } else {
+        format!("{}:{:02}", secs / 60, secs % 60)
+    }
+}
+
+#[cfg(test)]
+mod game_tests {
+    use super::*;
+
+    const EXAMPLE: &str =
+        "53..7....6..195....98....6.8...6...34..8.3..17...2...6.6....28....419..5....8..79";
+    const SOLUTION: &str =
+        "534678912672195348198342567859761423426853791713924856961537284287419635345286179";
+
+    fn game() -> Game {
+        Game::new(EXAMPLE.parse().unwrap())
+    }
+
+    #[test]
+    fn entry_test() {
+        let mut game = game();
+        assert!(game.puzzle.cells.iter().all(|c| c.penciled().is_empty()));
+        game.move_cursor(0, -1);
+        assert_eq!(game.cursor, 8);
+        game.move_cursor(-1, 1);
+        assert_eq!(game.cursor, 72);
+
+        // Givens cannot be changed
+        game.cursor = 0;
+        game.digit(1);
+        assert_eq!(game.puzzle.cells[0].num(), 5);
+
+        game.cursor = 2;
+        game.digit(5);
+        assert!(game.conflict(2));
+        assert!(game.conflict(0));
+        game.digit(4);
+        assert!(!game.conflict(2));
+
+        game.pencil_mode = true;
+        game.cursor = 3;
+        game.digit(6);
+        game.digit(2);
+        game.digit(6);
+        assert_eq!(
+            game.puzzle.cells[3].penciled().iter().collect::<Vec<_>>(),
+            vec![&2]
+        );
+
+        assert!(game.undo());
+        assert!(game.puzzle.cells[3].is_possible(6));
+        assert!(game.redo());
+        assert!(!game.puzzle.cells[3].is_possible(6));
+        assert!(!game.redo());
+        while game.undo() {}
+        assert_eq!(game.puzzle.cells[2].num(), 0);
+        assert!(game.redo());
+        game.pencil_mode = false;
+        game.cursor = 2;
+        game.clear();
+        assert_eq!(game.puzzle.cells[2].num(), 0);
+        // A new move forgets the moves that were undone
+        assert!(!game.redo());
+    }
+
+    #[test]
+    fn solve_test() {
+        let mut game = game();
+        for (index, c) in SOLUTION.chars().enumerate() {
+            game.cursor = index;
+            game.digit(c.to_digit(10).unwrap() as u8);
+        }
+        assert!(game.is_solved());
+        assert!(game.message.starts_with("Solved in "));
+        let elapsed = game.elapsed();
+        game.cursor = 2;
+        game.clear();
+        assert_eq!(game.puzzle.cells[2].num(), 4);
+        assert!(!game.undo());
+        assert!(game.puzzle.is_solved());
+        assert_eq!(game.elapsed(), elapsed);
+    }
+
+    #[test]
+    fn hint_test() {
+        let mut game = game();
+        game.hint();
+        assert_eq!(game.hint.len(), 1);
+        let index = game.hint[0];
+        assert_eq!(game.cursor, index);
+        let val = SOLUTION.as_bytes()[index] - b'0';
+        assert!(game.message.starts_with("naked single: row"));
+        assert!(game.message.ends_with(&format!(" is {}", val)));
+
+        // A wrong number that does not conflict yet
+        game.cursor = 2;
+        game.digit(1);
+        game.hint();
+        assert!(game.message.starts_with("There is a mistake"));
+
+        game.digit(5);
+        game.hint();
+        assert_eq!(game.hint, vec![0, 2]);
+    }
+
+    #[test]
+    fn save_test() {
+        let mut game = game();
+        game.cursor = 2;
+        game.digit(4);
+        game.pencil_mode = true;
+        game.cursor = 3;
+        game.digit(6);
+        let text = game.save();
+        assert!(text.starts_with(SAVE_HEADER));
+
+        let loaded = Game::load(&text).unwrap();
+        assert_eq!(loaded.puzzle.cells[2].num(), 4);
+        assert!(!loaded.puzzle.cells[2].given());
+        assert!(loaded.puzzle.cells[0].given());
+        assert_eq!(
+            loaded.puzzle.cells[3].penciled().iter().collect::<Vec<_>>(),
+            vec![&6]
+        );
+
+        let fresh = Game::load(EXAMPLE).unwrap();
+        assert_eq!(fresh.puzzle.cells[0].num(), 5);
+        assert!(Game::load("# sudoku-tui save\ntime 3\n").is_err());
+        assert!(Game::load("not a puzzle").is_err());
+    }
+
+    #[test]
+    fn clock_test() {
+        assert_eq!(clock(Duration::from_secs(65)), "1:05");
+        assert_eq!(clock(Duration::from_secs(3725)), "1:02:05");
+    }
+}
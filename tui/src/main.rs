@@ -0,0 +1,172 @@
+//! A sudoku game for the terminal, built on the sudoku library.  It is a separate crate so that
+//! the library does not depend on a terminal library.
+mod game;
+mod ui;
+
+use crate::game::Game;
+use crossterm::cursor::{Hide, Show};
+use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use crossterm::execute;
+use crossterm::terminal::{self, EnterAlternateScreen, LeaveAlternateScreen};
+use std::fs;
+use std::io::{self, stdout};
+use std::process;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use sudoku::Generator;
+
+const USAGE: &str = "Usage: sudoku-tui [--save FILE] [PUZZLE | FILE]
+
+Plays a puzzle, given on the command line or read from a file.  The file may be a saved game.
+A new puzzle is generated if none is given.
+
+Options:
+      --save FILE  Where 's' saves the game and 'o' opens it (default sudoku-tui.save)
+  -h, --help       Print this message";
+
+const DEFAULT_SAVE: &str = "sudoku-tui.save";
+
+/// Puts the terminal back the way it was, even if the game panics.
+struct Terminal;
+
+impl Terminal {
+    fn start() -> io::Result<Terminal> {
+        terminal::enable_raw_mode()?;
+        execute!(stdout(), EnterAlternateScreen, Hide)?;
+        Ok(Terminal)
+    }
+}
+
+impl Drop for Terminal {
+    fn drop(&mut self) {
+        let _ = execute!(stdout(), Show, LeaveAlternateScreen);
+        let _ = terminal::disable_raw_mode();
+    }
+}
+
+fn new_game() -> Game {
+    let seed = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_nanos() as u64)
+        .unwrap_or_default();
+    let mut generator = Generator::new(seed);
+    generator.clues(28, 32);
+    Game::new(
+        generator
+            .generate()
+            .expect("Puzzles with 28 clues are easy to find"),
+    )
+}
+
+/// Reads the puzzle argument, which is a puzzle itself or a file containing one.
+fn open(arg: &str) -> Result<Game, String> {
+    match fs::read_to_string(arg) {
+        Ok(text) => Game::load(&text).map_err(|e| format!("{}: {}", arg, e)),
+        Err(_) => Game::load(arg),
+    }
+}
+
+/// Handles a key.  Returns false when the player quits.
+fn key(game: &mut Game, key: KeyEvent, save_file: &str) -> bool {
+    let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+    match key.code {
+        KeyCode::Char('c') if ctrl => return false,
+        KeyCode::Char('z') if ctrl => {
+            game.undo();
+        }
+        KeyCode::Char('y') if ctrl => {
+            game.redo();
+        }
+        KeyCode::Esc | KeyCode::Char('q') => return false,
+        KeyCode::Up | KeyCode::Char('k') => game.move_cursor(-1, 0),
+        KeyCode::Down | KeyCode::Char('j') => game.move_cursor(1, 0),
+        KeyCode::Left | KeyCode::Char('h') => game.move_cursor(0, -1),
+        KeyCode::Right | KeyCode::Char('l') => game.move_cursor(0, 1),
+        KeyCode::Char(c @ '1'..='9') => game.digit(c as u8 - b'0'),
+        KeyCode::Char('0') | KeyCode::Char('.') | KeyCode::Char(' ') => game.clear(),
+        KeyCode::Backspace | KeyCode::Delete => game.clear(),
+        KeyCode::Char('p') => game.pencil_mode = !game.pencil_mode,
+        KeyCode::Char('u') => {
+            game.undo();
+        }
+        KeyCode::Char('r') => {
+            game.redo();
+        }
+        KeyCode::Char('?') => game.hint(),
+        KeyCode::Char('s') => {
+            game.message = match fs::write(save_file, game.save()) {
+                Ok(()) => format!("Saved to {}", save_file),
+                Err(e) => format!("Could not save to {}: {}", save_file, e),
+            };
+        }
+        KeyCode::Char('o') => match fs::read_to_string(save_file)
+            .map_err(|e| e.to_string())
+            .and_then(|text| Game::load(&text))
+        {
+            Ok(loaded) => {
+                *game = loaded;
+                game.message = format!("Opened {}", save_file);
+            }
+            Err(e) => game.message = format!("Could not open {}: {}", save_file, e),
+        },
+        _ => (),
+    }
+    true
+}
+
+fn play(mut game: Game, save_file: &str) -> io::Result<()> {
+    let _terminal = Terminal::start()?;
+    let mut out = stdout();
+    loop {
+        ui::draw(&game, &mut out)?;
+        // Wake up every so often to update the clock
+        if !event::poll(Duration::from_millis(250))? {
+            continue;
+        }
+        if let Event::Key(k) = event::read()? {
+            if k.kind == KeyEventKind::Press && !key(&mut game, k, save_file) {
+                return Ok(());
+            }
+        }
+    }
+}
+
+fn main() {
+    let mut save_file = DEFAULT_SAVE.to_string();
+    let mut puzzle = None;
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-h" | "--help" => {
+                println!("{}", USAGE);
+                return;
+            }
+            "--save" => match args.next() {
+                Some(file) => save_file = file,
+                None => {
+                    eprintln!("--save needs a value\n\n{}", USAGE);
+                    process::exit(1);
+                }
+            },
+            _ if puzzle.is_none() => puzzle = Some(arg),
+            _ => {
+                eprintln!("Unexpected argument '{}'\n\n{}", arg, USAGE);
+                process::exit(1);
+            }
+        }
+    }
+
+    let game = match puzzle {
+        Some(arg) => match open(&arg) {
+            Ok(game) => game,
+            Err(e) => {
+                eprintln!("{}", e);
+                process::exit(2);
+            }
+        },
+        None => new_game(),
+    };
+    if let Err(e) = play(game, &save_file) {
+        eprintln!("{}", e);
+        process::exit(2);
+    }
+}
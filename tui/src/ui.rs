@@ -0,0 +1,120 @@
+//! Drawing the game in the terminal.
+use crate::game::{clock, Game, SIZE};
+use crossterm::cursor::MoveTo;
+use crossterm::queue;
+use crossterm::style::{
+    Attribute, Color, Print, ResetColor, SetAttribute, SetBackgroundColor, SetForegroundColor,
+};
+use crossterm::terminal::{Clear, ClearType};
+use std::io::{self, Write};
+use sudoku::get_cell;
+
+/// Each cell is drawn as three lines of this many characters, so that the pencil marks fit.
+const CELL_WIDTH: usize = 7;
+const BOX: usize = 3;
+
+pub const KEYS: &str = "arrows/hjkl move  1-9 fill  0/del clear  p pencil  u undo  r redo  \
+                        ? hint  s save  o open  q quit";
+
+fn border() -> String {
+    format!(
+        "{}+",
+        format!("+{}", "-".repeat(CELL_WIDTH * BOX)).repeat(SIZE / BOX)
+    )
+}
+
+/// The text of one of the three lines of a cell.
+fn cell_line(game: &Game, index: usize, line: usize) -> String {
+    let cell = &game.puzzle.cells[index];
+    if cell.num() != 0 {
+        if line == 1 {
+            format!("{:^width$}", cell.num(), width = CELL_WIDTH)
+        } else {
+            " ".repeat(CELL_WIDTH)
+        }
+    } else {
+        let marks = (1..=BOX as u8)
+            .map(|i| {
+                let val = line as u8 * BOX as u8 + i;
+                if cell.is_possible(val) {
+                    (b'0' + val) as char
+                } else {
+                    ' '
+                }
+            })
+            .map(|c| c.to_string())
+            .collect::<Vec<String>>();
+        format!("{:^width$}", marks.join(" "), width = CELL_WIDTH)
+    }
+}
+
+/// Moves to the start of the next line.  Raw mode does not do this for `\n`.
+fn next_line(out: &mut impl Write, y: &mut u16) -> io::Result<()> {
+    *y += 1;
+    queue!(out, MoveTo(0, *y))
+}
+
+pub fn draw(game: &Game, out: &mut impl Write) -> io::Result<()> {
+    queue!(out, Clear(ClearType::All), MoveTo(0, 0))?;
+    let mut y = 0;
+
+    for row in 0..SIZE {
+        if row % BOX == 0 {
+            queue!(out, Print(border()))?;
+            next_line(out, &mut y)?;
+        }
+        for line in 0..BOX {
+            for col in 0..SIZE {
+                if col % BOX == 0 {
+                    queue!(out, Print("|"))?;
+                }
+                let index = get_cell(row, col);
+                let cell = &game.puzzle.cells[index];
+                let color = if game.conflict(index) {
+                    Color::Red
+                } else if cell.given() {
+                    Color::Reset
+                } else if cell.num() != 0 {
+                    Color::Blue
+                } else {
+                    Color::DarkGrey
+                };
+                if cell.given() {
+                    queue!(out, SetAttribute(Attribute::Bold))?;
+                }
+                if index == game.cursor {
+                    queue!(out, SetAttribute(Attribute::Reverse))?;
+                } else if game.hint.contains(&index) {
+                    queue!(out, SetBackgroundColor(Color::Yellow))?;
+                }
+                queue!(
+                    out,
+                    SetForegroundColor(color),
+                    Print(cell_line(game, index, line)),
+                    SetAttribute(Attribute::Reset),
+                    ResetColor
+                )?;
+            }
+            queue!(out, Print("|"))?;
+            next_line(out, &mut y)?;
+        }
+    }
+    queue!(out, Print(border()))?;
+    next_line(out, &mut y)?;
+
+    let mode = if game.pencil_mode { "pencil" } else { "number" };
+    queue!(
+        out,
+        Print(format!("Time {}   Mode: {}", clock(game.elapsed()), mode))
+    )?;
+    next_line(out, &mut y)?;
+    queue!(out, Print(&game.message))?;
+    next_line(out, &mut y)?;
+    queue!(
+        out,
+        SetForegroundColor(Color::DarkGrey),
+        Print(KEYS),
+        ResetColor
+    )?;
+    out.flush()
+}
@@ -0,0 +1,369 @@
+//! JavaScript bindings for the sudoku library, built for `wasm32-unknown-unknown` with
+//! wasm-bindgen.  Nothing here uses threads or the clock, which are not available in the browser,
+//! so the generator takes its seed from the caller.
+//!
+//! Cells are numbered 0 to 80, row by row, and hold 1 to 9, or 0 when empty.  Errors are thrown as
+//! JavaScript `Error`s with the library's message.
+use sudoku::{
+    Alphabet, BruteForce, ParseMode, PuzInput, PuzOutput, Puzzle, SudError, Symmetry, Technique,
+};
+use wasm_bindgen::prelude::*;
+
+/// The number of cells in a puzzle.
+const CELLS: usize = 81;
+
+fn js_error(e: SudError) -> JsError {
+    JsError::new(&e.to_string())
+}
+
+/// Cells whose numbers are repeated in their row, column, or box.
+fn conflicts(puz: &Puzzle) -> Vec<u32> {
+    (0..CELLS)
+        .filter(|i| puz.cells[*i].num() != 0 && !puz.valid_entry(*i))
+        .map(|i| i as u32)
+        .collect()
+}
+
+/// A puzzle, with the givens and any numbers filled in since.
+#[wasm_bindgen]
+#[derive(Clone, Debug, PartialEq)]
+pub struct Sudoku {
+    puzzle: Puzzle,
+}
+
+#[wasm_bindgen]
+impl Sudoku {
+    /// Reads a puzzle in any format the library reads, such as 81 digits with `.` or `0` for
+    /// empty cells.
+    #[wasm_bindgen(constructor)]
+    pub fn new(text: &str) -> Result<Sudoku, JsError> {
+        text.parse()
+            .map(|puzzle| Sudoku { puzzle })
+            .map_err(js_error)
+    }
+
+    /// Starts a puzzle from 81 givens, from 0 to 9.
+    #[wasm_bindgen(js_name = fromValues)]
+    pub fn from_values(values: &[u8]) -> Result<Sudoku, JsError> {
+        let input = values
+            .chunks(9)
+            .map(|row| row.to_vec())
+            .collect::<Vec<Vec<u8>>>()
+            .as_input_mode(ParseMode::Strict)
+            .map_err(js_error)?;
+        let mut puzzle = Puzzle::new();
+        puzzle.set_initial(input);
+        Ok(Sudoku { puzzle })
+    }
+
+    /// The 81 values of the cells, givens and filled in.
+    pub fn values(&self) -> Vec<u8> {
+        self.puzzle.cells.iter().map(|c| c.num()).collect()
+    }
+
+    /// The cells as 81 characters, with `.` for an empty cell.
+    #[wasm_bindgen(js_name = toString)]
+    pub fn to_line(&self) -> String {
+        self.values().as_string().unwrap_or_default()
+    }
+
+    #[wasm_bindgen(js_name = isGiven)]
+    pub fn is_given(&self, index: usize) -> bool {
+        index < CELLS && self.puzzle.cells[index].given()
+    }
+
+    #[wasm_bindgen(js_name = isSolved)]
+    pub fn is_solved(&self) -> bool {
+        self.puzzle.is_solved()
+    }
+
+    /// Fills in a cell that is not a given, or empties it if `value` is 0.  The number is not
+    /// checked against the others, the way a player may make a mistake.
+    #[wasm_bindgen(js_name = setValue)]
+    pub fn set_value(&mut self, index: usize, value: u8) -> Result<(), JsError> {
+        if index >= CELLS || value > 9 {
+            return Err(JsError::new(&format!(
+                "Cell {} cannot be set to {}",
+                index, value
+            )));
+        }
+        if !self.puzzle.set_entry(index, value) {
+            return Err(JsError::new(&format!("Cell {} is a given", index)));
+        }
+        self.puzzle.reset_pencil_marks();
+        Ok(())
+    }
+
+    /// The numbers still possible in an empty cell, or none for a filled in cell.
+    pub fn candidates(&self, index: usize) -> Vec<u8> {
+        match self.puzzle.cells.get(index) {
+            Some(cell) if cell.num() == 0 => cell.poss_iter().cloned().collect(),
+            _ => Vec::new(),
+        }
+    }
+
+    /// Cells whose numbers are repeated in their row, column, or box.
+    pub fn conflicts(&self) -> Vec<u32> {
+        conflicts(&self.puzzle)
+    }
+
+    /// Solves the puzzle from the numbers filled in so far, stopping after `max` solutions.
+    pub fn solve(&self, max: usize) -> Vec<Sudoku> {
+        if !conflicts(&self.puzzle).is_empty() {
+            return Vec::new();
+        }
+        self.puzzle
+            .clone()
+            .brute_force_solve_max(max)
+            .into_iter()
+            .map(|solution| {
+                let mut puzzle = self.puzzle.clone();
+                for (index, value) in solution.into_iter().enumerate() {
+                    puzzle.set_entry(index, value);
+                }
+                Sudoku { puzzle }
+            })
+            .collect()
+    }
+
+    /// The next number to fill in, found with the easiest techniques that work, or `undefined` if
+    /// the puzzle is solved or a number filled in is wrong.
+    pub fn hint(&self) -> Option<Hint> {
+        if !conflicts(&self.puzzle).is_empty() {
+            return None;
+        }
+        self.puzzle.hint().map(|step| Hint {
+            index: step.placements[0].0 as u32,
+            value: step.placements[0].1,
+            eliminations: step.eliminations.len() as u32,
+            technique: step.technique,
+        })
+    }
+
+    /// Rates the puzzle by the techniques needed to solve it from the numbers filled in so far.
+    pub fn rate(&self) -> Rating {
+        let rating = self.puzzle.rate();
+        Rating {
+            hardest: rating.hardest(),
+            solutions: rating.solutions as u32,
+            naked_single: rating.count(Technique::NakedSingle) as u32,
+            hidden_single: rating.count(Technique::HiddenSingle) as u32,
+            naked_tuple: rating.count(Technique::NakedTuple) as u32,
+            locked_candidates: rating.count(Technique::LockedCandidates) as u32,
+            brute_force: rating.brute_force,
+        }
+    }
+}
+
+/// The next number to fill in.
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Hint {
+    /// The cell to fill in.
+    pub index: u32,
+    /// The number that goes in the cell.
+    pub value: u8,
+    /// How many pencil marks were removed on the way to finding the number.
+    pub eliminations: u32,
+    technique: Technique,
+}
+
+#[wasm_bindgen]
+impl Hint {
+    /// The hardest technique needed to find the number, such as "naked single".
+    #[wasm_bindgen(getter)]
+    pub fn technique(&self) -> String {
+        self.technique.name().to_string()
+    }
+}
+
+/// How hard a puzzle is to solve.
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Rating {
+    hardest: Option<Technique>,
+    /// The number of solutions, counting no more than 2.
+    pub solutions: u32,
+    /// The number of times each human technique was used.
+    #[wasm_bindgen(js_name = nakedSingle)]
+    pub naked_single: u32,
+    #[wasm_bindgen(js_name = hiddenSingle)]
+    pub hidden_single: u32,
+    #[wasm_bindgen(js_name = nakedTuple)]
+    pub naked_tuple: u32,
+    #[wasm_bindgen(js_name = lockedCandidates)]
+    pub locked_candidates: u32,
+    /// True if the human techniques could not finish the puzzle.
+    #[wasm_bindgen(js_name = bruteForce)]
+    pub brute_force: bool,
+}
+
+#[wasm_bindgen]
+impl Rating {
+    /// The hardest technique needed, or `undefined` if the puzzle was already solved.
+    #[wasm_bindgen(getter)]
+    pub fn hardest(&self) -> Option<String> {
+        self.hardest.map(|t| t.name().to_string())
+    }
+}
+
+/// What is wrong with a puzzle, if anything.
+#[wasm_bindgen(getter_with_clone)]
+#[derive(Clone, Debug, PartialEq)]
+pub struct Validation {
+    /// Why the text is not a puzzle, or `undefined` if it is one.
+    pub error: Option<String>,
+    /// Givens that are repeated in their row, column, or box.
+    pub conflicts: Vec<u32>,
+    /// The number of solutions, counting no more than 2.  A puzzle should have exactly 1.
+    pub solutions: u32,
+}
+
+/// Checks a puzzle without throwing, unlike `new Sudoku(text)`.
+#[wasm_bindgen]
+pub fn validate(text: &str) -> Validation {
+    let mut validation = Validation {
+        error: None,
+        conflicts: Vec::new(),
+        solutions: 0,
+    };
+    let input = match Alphabet::default().parse(text, ParseMode::Strict) {
+        Ok(input) => input,
+        // Read the givens again to find every conflict, not just the first
+        Err(SudError::ConflictingGivens { .. }) => text.as_input().unwrap_or_default(),
+        Err(e) => {
+            validation.error = Some(e.to_string());
+            return validation;
+        }
+    };
+    let mut puz = Puzzle::new();
+    puz.set_initial(input);
+    validation.conflicts = conflicts(&puz);
+    if validation.conflicts.is_empty() {
+        validation.solutions = puz.brute_force_solve_max(2).len() as u32;
+    }
+    validation
+}
+
+/// Generates puzzles with a unique solution.  The same seed and options generate the same puzzles.
+#[wasm_bindgen]
+pub struct Generator {
+    generator: sudoku::Generator,
+}
+
+#[wasm_bindgen]
+impl Generator {
+    /// JavaScript numbers are converted to a whole number for the seed, so `Date.now()` or
+    /// `Math.random() * 2 ** 53` both work.
+    #[wasm_bindgen(constructor)]
+    pub fn new(seed: f64) -> Generator {
+        Generator {
+            generator: sudoku::Generator::new(seed as u64),
+        }
+    }
+
+    /// The range of the number of givens, inclusive.
+    pub fn clues(&mut self, min: usize, max: usize) -> Result<(), JsError> {
+        if min > max || max > CELLS {
+            return Err(JsError::new(&format!(
+                "Clue range {}-{} must be within 0-{}",
+                min, max, CELLS
+            )));
+        }
+        self.generator.clues(min, max);
+        Ok(())
+    }
+
+    /// One of "none", "rotational", "quarter-turn", "horizontal", "vertical" or "diagonal".
+    pub fn symmetry(&mut self, name: &str) -> Result<(), JsError> {
+        let symmetry: Symmetry = name.parse().map_err(js_error)?;
+        self.generator.symmetry(symmetry);
+        Ok(())
+    }
+
+    /// Only keep puzzles whose hardest technique is `name`, such as "naked-single".
+    pub fn difficulty(&mut self, name: &str) -> Result<(), JsError> {
+        let technique: Technique = name.parse().map_err(js_error)?;
+        self.generator.difficulty(technique);
+        Ok(())
+    }
+
+    /// How many puzzles to try before giving up on finding one that matches the options.
+    pub fn attempts(&mut self, attempts: usize) {
+        self.generator.attempts(attempts);
+    }
+
+    /// Generates the next puzzle.
+    pub fn generate(&mut self) -> Result<Sudoku, JsError> {
+        self.generator
+            .generate()
+            .map(|puzzle| Sudoku { puzzle })
+            .map_err(js_error)
+    }
+}
+
+#[cfg(test)]
+mod wasm_tests {
+    use super::*;
+
+    const EXAMPLE: &str =
+        "53..7....6..195....98....6.8...6...34..8.3..17...2...6.6....28....419..5....8..79";
+    const SOLUTION: &str =
+        "534678912672195348198342567859761423426853791713924856961537284287419635345286179";
+
+    // Only the paths that do not create JavaScript values can run outside of wasm.  The rest is
+    // covered by `tests/node.js`.
+    #[test]
+    fn sudoku_test() {
+        let mut puz = Sudoku::new(EXAMPLE).unwrap();
+        assert_eq!(puz.to_line(), EXAMPLE);
+        assert_eq!(Sudoku::from_values(&puz.values()).unwrap(), puz);
+        assert_eq!(puz.candidates(2), vec![1, 2, 4]);
+        assert!(puz.candidates(0).is_empty());
+        assert!(puz.is_given(0) && !puz.is_given(2));
+
+        let solutions = puz.solve(2);
+        assert_eq!(solutions.len(), 1);
+        assert_eq!(solutions[0].to_line(), SOLUTION);
+
+        let hint = puz.hint().unwrap();
+        assert_eq!(hint.technique(), "naked single");
+        assert_eq!(hint.value, SOLUTION.as_bytes()[hint.index as usize] - b'0');
+        let rating = puz.rate();
+        assert_eq!(rating.solutions, 1);
+        assert!(rating.hardest().is_some());
+
+        // A wrong number that does not conflict yet
+        puz.set_value(2, 1).ok().unwrap();
+        assert!(puz.conflicts().is_empty());
+        assert_eq!(puz.hint(), None);
+        assert!(puz.solve(1).is_empty());
+        puz.set_value(3, 5).ok().unwrap();
+        assert_eq!(puz.conflicts(), vec![0, 3, 14]);
+    }
+
+    #[test]
+    fn validate_test() {
+        let valid = validate(EXAMPLE);
+        assert_eq!(valid.error, None);
+        assert_eq!(valid.solutions, 1);
+
+        let repeated = validate(&format!("55{}", &EXAMPLE[2..]));
+        assert_eq!(repeated.conflicts, vec![0, 1]);
+        assert_eq!(repeated.solutions, 0);
+
+        assert!(validate("53x").error.is_some());
+        assert_eq!(validate(&".".repeat(81)).solutions, 2);
+    }
+
+    #[test]
+    fn generator_test() {
+        let mut generator = Generator::new(7.0);
+        generator.clues(30, 34).ok().unwrap();
+        let puz = generator.generate().ok().unwrap();
+        assert_eq!(validate(&puz.to_line()).solutions, 1);
+        let mut again = Generator::new(7.0);
+        again.clues(30, 34).ok().unwrap();
+        assert_eq!(again.generate().ok().unwrap(), puz);
+    }
+}
@@ -0,0 +1,74 @@
+// Runs the wasm build under Node.  Takes the directory written by
+// `wasm-bindgen --target nodejs`, and prints "ok" if every check passes.
+"use strict";
+const assert = require("assert");
+const path = require("path");
+
+const { Sudoku, Generator, validate } = require(path.resolve(process.argv[2], "sudoku_wasm.js"));
+
+const EXAMPLE =
+  "53..7....6..195....98....6.8...6...34..8.3..17...2...6.6....28....419..5....8..79";
+const SOLUTION =
+  "534678912672195348198342567859761423426853791713924856961537284287419635345286179";
+
+// Parsing
+const puz = new Sudoku(EXAMPLE);
+assert.strictEqual(puz.toString(), EXAMPLE);
+assert.ok(puz.values() instanceof Uint8Array);
+assert.strictEqual(puz.values().length, 81);
+assert.strictEqual(Sudoku.fromValues(puz.values()).toString(), EXAMPLE);
+assert.throws(() => new Sudoku("55" + EXAMPLE.slice(2)), /repeated|conflict/i);
+assert.throws(() => Sudoku.fromValues(new Uint8Array(80)), Error);
+assert.deepStrictEqual(Array.from(puz.candidates(2)), [1, 2, 4]);
+assert.ok(puz.isGiven(0));
+
+// Solving
+const solutions = puz.solve(2);
+assert.strictEqual(solutions.length, 1);
+assert.strictEqual(solutions[0].toString(), SOLUTION);
+assert.ok(solutions[0].isSolved());
+assert.strictEqual(new Sudoku(".".repeat(81)).solve(3).length, 3);
+
+// Hints, followed to the end
+const game = new Sudoku(EXAMPLE);
+let hint = game.hint();
+assert.strictEqual(hint.technique, "naked single");
+while (hint !== undefined) {
+  assert.strictEqual(hint.value, Number(SOLUTION[hint.index]));
+  game.setValue(hint.index, hint.value);
+  hint = game.hint();
+}
+assert.strictEqual(game.toString(), SOLUTION);
+assert.throws(() => game.setValue(0, 1), /given/);
+assert.throws(() => game.setValue(81, 1), Error);
+
+// Rating
+const rating = puz.rate();
+assert.strictEqual(rating.solutions, 1);
+assert.strictEqual(rating.bruteForce, false);
+assert.ok(rating.nakedSingle > 0);
+assert.strictEqual(typeof rating.hardest, "string");
+
+// Validation
+assert.strictEqual(validate(EXAMPLE).error, undefined);
+assert.strictEqual(validate(EXAMPLE).solutions, 1);
+assert.deepStrictEqual(Array.from(validate("55" + EXAMPLE.slice(2)).conflicts), [0, 1]);
+assert.match(validate("53x").error, /unexpected character/);
+const wrong = new Sudoku(EXAMPLE);
+wrong.setValue(3, 5);
+assert.deepStrictEqual(Array.from(wrong.conflicts()), [0, 3, 14]);
+
+// Generation
+const generator = new Generator(2024);
+generator.clues(30, 34);
+generator.symmetry("rotational");
+const generated = generator.generate();
+assert.strictEqual(validate(generated.toString()).solutions, 1);
+const again = new Generator(2024);
+again.clues(30, 34);
+again.symmetry("rotational");
+assert.strictEqual(again.generate().toString(), generated.toString());
+assert.throws(() => generator.symmetry("spiral"), /Unknown symmetry/);
+assert.throws(() => generator.clues(40, 30), Error);
+
+console.log("ok");
@@ -0,0 +1,50 @@
+//! Builds the crate for `wasm32-unknown-unknown`, generates the JavaScript bindings, and runs
+//! `tests/node.js` with them.  It needs the wasm32 target, the `wasm-bindgen` command line tool
+//! and Node, so it only runs when asked for:
+//!
+//! ```text
+//! rustup target add wasm32-unknown-unknown
+//! cargo install wasm-bindgen-cli --version 0.2.129
+//! cargo test -p sudoku-wasm -- --ignored
+//! ```
+use std::env;
+use std::path::PathBuf;
+use std::process::Command;
+
+fn run(command: &mut Command) {
+    let status = command
+        .status()
+        .unwrap_or_else(|e| panic!("Could not run {:?}: {}", command, e));
+    assert!(status.success(), "{:?} failed", command);
+}
+
+#[test]
+#[ignore]
+fn node_test() {
+    let crate_dir = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    let target = env::var_os("CARGO_TARGET_DIR")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| crate_dir.parent().unwrap().join("target"));
+    let pkg = target.join("wasm-pkg");
+
+    run(Command::new(env!("CARGO"))
+        .args(["build", "--lib", "--target", "wasm32-unknown-unknown"])
+        .arg("--manifest-path")
+        .arg(crate_dir.join("Cargo.toml")));
+    run(Command::new("wasm-bindgen")
+        .args(["--target", "nodejs", "--out-dir"])
+        .arg(&pkg)
+        .arg(target.join("wasm32-unknown-unknown/debug/sudoku_wasm.wasm")));
+
+    let output = Command::new("node")
+        .arg(crate_dir.join("tests").join("node.js"))
+        .arg(&pkg)
+        .output()
+        .expect("Node is needed to run the test");
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "ok\n");
+}